@@ -0,0 +1,73 @@
+//! `devdust restore` subcommand
+//!
+//! Cleans run with `--archive DIR` pack artifacts into compressed archives in
+//! DIR; `restore` unpacks a batch back to where the artifacts came from.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use colored::*;
+use devdust_core::{format_size, Archive};
+
+/// Arguments for the `restore` subcommand
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// Archive folder given to --archive (restores its latest batch), or one batch folder in it
+    #[arg(value_name = "DIR")]
+    dir: PathBuf,
+}
+
+/// Runs the `restore` subcommand
+pub fn restore(args: RestoreArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", args.dir.display()).into());
+    }
+
+    // A batch folder is one of the batches of its parent
+    let archive = Archive::new(args.dir.clone());
+    let mut batches = archive.batches()?;
+    let (archive, batch) = if batches.is_empty() {
+        let parent = args.dir.parent().map(PathBuf::from).unwrap_or_default();
+        let archive = Archive::new(parent);
+        let batch = archive
+            .batches()?
+            .into_iter()
+            .find(|batch| batch.path == args.dir);
+        (archive, batch)
+    } else {
+        (archive, batches.pop())
+    };
+    let Some(batch) = batch else {
+        println!(
+            "{}",
+            format!("Nothing to restore: no archives in {}", args.dir.display()).yellow()
+        );
+        return Ok(());
+    };
+
+    let report = archive.restore(&batch)?;
+    for path in &report.restored {
+        println!("{} {}", "✓".green().bold(), path.display());
+    }
+    for (path, e) in &report.errors {
+        eprintln!("{} {}: {}", "✗".red().bold(), path.display(), e);
+    }
+
+    println!("\n{}", "═".repeat(50).cyan());
+    println!(
+        "{} {} directories, {}",
+        "Restored:".green().bold(),
+        report.restored.len().to_string().white().bold(),
+        format_size(report.bytes).white().bold()
+    );
+    if !report.errors.is_empty() {
+        return Err(format!(
+            "{} directories could not be restored and remain in {}",
+            report.errors.len(),
+            batch.path.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
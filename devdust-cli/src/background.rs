@@ -0,0 +1,99 @@
+//! Finishing the deletions of a clean run with `--background` or `--detach`
+//!
+//! Artifact directories are renamed out of the way and deleted on a worker
+//! thread while the clean goes on. At the end, devdust either waits for the
+//! worker or, with `--detach`, hands what is left to a hidden `remove-staged`
+//! process that keeps deleting after devdust has exited.
+
+use std::{
+    env,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{remove_staged, BackgroundRemover};
+
+/// Arguments for the hidden `remove-staged` subcommand
+#[derive(Args, Debug)]
+pub struct RemoveStagedArgs {
+    /// Directories renamed for deletion by a clean with --detach
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+}
+
+/// Runs the `remove-staged` subcommand
+pub fn run(args: RemoveStagedArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let failed = args
+        .paths
+        .iter()
+        .filter(|path| remove_staged(path).is_err())
+        .count();
+    if failed > 0 {
+        return Err(format!("{} directories could not be deleted", failed).into());
+    }
+    Ok(())
+}
+
+/// Waits for the background deletions, or leaves them to a detached process
+///
+/// Returns the number of directories that could not be deleted.
+pub fn finish(remover: &BackgroundRemover, detach: bool, quiet: bool) -> usize {
+    let report = if detach {
+        remover.detach()
+    } else {
+        if !quiet {
+            println!(
+                "{}",
+                "Waiting for background deletion to finish…".bright_black()
+            );
+        }
+        remover.wait()
+    };
+
+    let mut failed = report.errors.len();
+    if !report.remaining.is_empty() {
+        match spawn_detached(&report.remaining) {
+            Ok(()) => {
+                if !quiet {
+                    println!(
+                        "{} {} directories are still being deleted in the background",
+                        "Detached:".green().bold(),
+                        report.remaining.len().to_string().white().bold()
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} Could not detach ({}); deleting the rest now",
+                    "Warning:".yellow(),
+                    e
+                );
+                for path in &report.remaining {
+                    if let Err(e) = remove_staged(path) {
+                        eprintln!("  {} {}: {}", "✗".red().bold(), path.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+        }
+    }
+    for (path, e) in &report.errors {
+        eprintln!("  {} {}: {}", "✗".red().bold(), path.display(), e);
+    }
+
+    failed
+}
+
+/// Starts `devdust remove-staged` on `paths` without waiting for it
+fn spawn_detached(paths: &[PathBuf]) -> std::io::Result<()> {
+    Command::new(env::current_exe()?)
+        .arg("remove-staged")
+        .args(paths)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
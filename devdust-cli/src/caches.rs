@@ -0,0 +1,204 @@
+//! `devdust caches` subcommand
+//!
+//! Reports the size of global toolchain caches and prunes entries that have
+//! not been used for a while.
+
+use std::{
+    cmp::Reverse,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{format_size, parse_age, CleanError, GlobalCache};
+
+/// How many entries `--list` shows per cache
+const LISTED_ENTRIES: usize = 15;
+
+/// Arguments for the `caches` subcommand
+#[derive(Args, Debug)]
+pub struct CachesArgs {
+    /// Caches to include (e.g., cargo-registry, npm, huggingface); defaults to all
+    #[arg(value_name = "CACHE")]
+    caches: Vec<GlobalCache>,
+
+    /// Only consider entries not modified within this time (e.g., 90d)
+    #[arg(short, long, value_name = "TIME")]
+    older: Option<String>,
+
+    /// List the largest entries of each cache (e.g., downloaded models)
+    #[arg(short, long)]
+    list: bool,
+
+    /// Delete the matching cache entries
+    #[arg(short, long)]
+    prune: bool,
+
+    /// Don't ask for confirmation before pruning
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Show what would be pruned without deleting anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+/// Runs the `caches` subcommand
+pub fn run(args: CachesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let min_age_seconds = match args.older.as_deref() {
+        Some(age_str) => parse_age(age_str)?,
+        None => 0,
+    };
+
+    let caches = if args.caches.is_empty() {
+        GlobalCache::ALL.to_vec()
+    } else {
+        args.caches
+    };
+
+    println!("{}", "Global caches:".cyan().bold());
+
+    let mut total_size = 0u64;
+    let mut total_freed = 0u64;
+
+    for cache in caches {
+        let roots = cache.locate();
+        if roots.is_empty() {
+            continue;
+        }
+
+        let size = cache.size();
+        total_size += size;
+
+        println!(
+            "\n{} {} {}",
+            "●".blue().bold(),
+            cache.name().white().bold(),
+            format_size(size).yellow().bold()
+        );
+        for root in &roots {
+            println!("  {} {}", "Path:".bright_black(), root.display());
+        }
+
+        // Pure size report unless an age filter, listing or pruning was requested
+        if min_age_seconds == 0 && !args.list && !args.prune {
+            continue;
+        }
+
+        let mut stale = cache.stale_entries(min_age_seconds);
+        let stale_size: u64 = stale.iter().map(|(_, size)| size).sum();
+        println!(
+            "  {} {} entries, {}",
+            "Prunable:".bright_black(),
+            stale.len(),
+            format_size(stale_size)
+        );
+
+        if args.list {
+            stale.sort_by_key(|(_, size)| Reverse(*size));
+            print_entries(cache, &stale);
+        }
+
+        if !args.prune || stale.is_empty() {
+            continue;
+        }
+
+        if args.dry_run {
+            println!("  {} Would free {}", "→".blue(), format_size(stale_size));
+            total_freed += stale_size;
+            continue;
+        }
+
+        if !args.yes && !confirm(cache, stale_size)? {
+            continue;
+        }
+
+        match cache.prune(min_age_seconds) {
+            Ok(freed) => {
+                println!(
+                    "  {} Freed {}",
+                    "✓".green().bold(),
+                    format_size(freed).green()
+                );
+                total_freed += freed;
+            }
+            Err(CleanError::PartialFailure { deleted, errors }) => {
+                eprintln!(
+                    "  {} Freed {}, {} entries could not be removed",
+                    "✗".red().bold(),
+                    format_size(deleted),
+                    errors.len()
+                );
+                total_freed += deleted;
+            }
+            Err(e) => eprintln!("  {} Failed to prune: {}", "✗".red().bold(), e),
+        }
+    }
+
+    println!("\n{}", "═".repeat(50).cyan());
+    println!(
+        "{} {} in global caches",
+        "Total:".green().bold(),
+        format_size(total_size).white().bold()
+    );
+    if args.prune {
+        let label = if args.dry_run {
+            "would be freed"
+        } else {
+            "freed"
+        };
+        println!(
+            "{} {} {}",
+            "Pruned:".green().bold(),
+            format_size(total_freed).green().bold(),
+            label
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the largest entries with their sizes
+fn print_entries(cache: GlobalCache, entries: &[(PathBuf, u64)]) {
+    let shown = &entries[..LISTED_ENTRIES.min(entries.len())];
+    let width = shown
+        .iter()
+        .map(|(_, size)| format_size(*size).len())
+        .max()
+        .unwrap_or(0);
+    for (entry, size) in shown {
+        println!(
+            "    {:>width$} {}",
+            format_size(*size).yellow(),
+            cache.entry_label(entry)
+        );
+    }
+
+    let hidden = &entries[shown.len()..];
+    if !hidden.is_empty() {
+        let hidden_size: u64 = hidden.iter().map(|(_, size)| size).sum();
+        println!(
+            "    {} and {} more ({})",
+            "…".bright_black(),
+            hidden.len(),
+            format_size(hidden_size)
+        );
+    }
+}
+
+/// Asks the user whether to prune a cache
+fn confirm(cache: GlobalCache, size: u64) -> io::Result<bool> {
+    print!(
+        "  {} Prune {} from {}? [y/N]: ",
+        "?".yellow().bold(),
+        format_size(size),
+        cache.name().white().bold()
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,163 @@
+//! `devdust containers` subcommand
+//!
+//! Reports the disk usage of Docker and Podman and prunes build caches,
+//! dangling images and stopped containers.
+
+use std::io::{self, Write};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{format_size, ContainerEngine};
+
+/// Arguments for the `containers` subcommand
+#[derive(Args, Debug)]
+pub struct ContainersArgs {
+    /// Engines to include (docker, podman); defaults to all installed ones
+    #[arg(value_name = "ENGINE")]
+    engines: Vec<ContainerEngine>,
+
+    /// Prune the build cache, dangling images and stopped containers
+    #[arg(short, long)]
+    prune: bool,
+
+    /// Don't ask for confirmation before pruning
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Show what could be pruned without deleting anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+/// Runs the `containers` subcommand
+pub fn run(args: ContainersArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let explicit = !args.engines.is_empty();
+    let engines = if explicit {
+        args.engines
+    } else {
+        ContainerEngine::ALL.to_vec()
+    };
+
+    println!("{}", "Container engines:".cyan().bold());
+
+    let mut total_size = 0u64;
+    let mut total_freed = 0u64;
+    let mut found = false;
+
+    for engine in engines {
+        let usage = match engine.usage() {
+            Ok(usage) => usage,
+            // Engines that aren't installed are only worth mentioning when asked for
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => continue,
+            Err(e) => {
+                eprintln!(
+                    "\n{} {}: {}",
+                    "✗".red().bold(),
+                    engine.name().white().bold(),
+                    e
+                );
+                continue;
+            }
+        };
+        found = true;
+
+        let size: u64 = usage.iter().map(|u| u.size).sum();
+        total_size += size;
+        println!(
+            "\n{} {} {}",
+            "●".blue().bold(),
+            engine.name().white().bold(),
+            format_size(size).yellow().bold()
+        );
+        for u in &usage {
+            println!(
+                "  {:<12} {:>10}  {}",
+                format!("{}:", u.resource).bright_black(),
+                format_size(u.size),
+                format!("{} reclaimable", format_size(u.reclaimable)).bright_black()
+            );
+        }
+
+        if !args.prune {
+            continue;
+        }
+
+        for u in usage.iter().filter(|u| u.reclaimable > 0) {
+            let Some(target) = u.resource.prune_target() else {
+                continue;
+            };
+
+            if args.dry_run {
+                println!(
+                    "  {} Would prune {}, up to {}",
+                    "→".blue(),
+                    target,
+                    format_size(u.reclaimable)
+                );
+                total_freed += u.reclaimable;
+                continue;
+            }
+
+            if !args.yes && !confirm(engine, target, u.reclaimable)? {
+                continue;
+            }
+
+            match engine.prune(u.resource) {
+                Ok(freed) => {
+                    println!(
+                        "  {} Pruned {}, freed {}",
+                        "✓".green().bold(),
+                        target,
+                        format_size(freed).green()
+                    );
+                    total_freed += freed;
+                }
+                Err(e) => eprintln!("  {} Failed to prune {}: {}", "✗".red().bold(), target, e),
+            }
+        }
+    }
+
+    if !found {
+        println!("{}", "No running Docker or Podman found.".yellow());
+        return Ok(());
+    }
+
+    println!("\n{}", "═".repeat(50).cyan());
+    println!(
+        "{} {} used by container engines",
+        "Total:".green().bold(),
+        format_size(total_size).white().bold()
+    );
+    if args.prune {
+        let label = if args.dry_run {
+            "would be freed at most"
+        } else {
+            "freed"
+        };
+        println!(
+            "{} {} {}",
+            "Pruned:".green().bold(),
+            format_size(total_freed).green().bold(),
+            label
+        );
+    }
+
+    Ok(())
+}
+
+/// Asks the user whether to prune one kind of data
+fn confirm(engine: ContainerEngine, target: &str, size: u64) -> io::Result<bool> {
+    print!(
+        "  {} Prune {} from {} (up to {})? [y/N]: ",
+        "?".yellow().bold(),
+        target,
+        engine.name().white().bold(),
+        format_size(size)
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,82 @@
+//! `devdust discover` subcommand
+//!
+//! Lists big git-ignored directories that no known project type accounts for,
+//! as possible artifacts to review by hand. Nothing is deleted; directories
+//! worth cleaning regularly can be added as custom rules.
+
+use std::{env, path::PathBuf};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{find_unknown_artifacts, format_size, parse_size, Config, ScanOptions};
+
+/// Arguments for the `discover` subcommand
+#[derive(Args, Debug)]
+pub struct DiscoverArgs {
+    /// Directories to search (defaults to current directory)
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+
+    /// Only report directories at least this large (e.g., 500M, 2G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "100M")]
+    min_size: u64,
+}
+
+/// Runs the `discover` subcommand
+pub fn run(args: DiscoverArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = if args.paths.is_empty() {
+        vec![env::current_dir()?]
+    } else {
+        args.paths
+    };
+
+    let mut found = Vec::new();
+    for path in &paths {
+        if !path.is_dir() {
+            return Err(format!("Path is not a directory: {}", path.display()).into());
+        }
+        // Custom rules already cover some ignored directories
+        let config = Config::load(path)?;
+        let options = ScanOptions::builder().custom_rules(config.rules).build();
+        found.extend(find_unknown_artifacts(path, args.min_size, &options));
+    }
+
+    if found.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No unknown ignored directories of {} or more.",
+                format_size(args.min_size)
+            )
+            .green()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Possible artifacts (unknown):".cyan().bold());
+    let width = found
+        .iter()
+        .map(|artifact| format_size(artifact.size).len())
+        .max()
+        .unwrap_or(0);
+    for artifact in &found {
+        println!(
+            "  {:>width$} {}",
+            format_size(artifact.size).yellow(),
+            artifact.path.display()
+        );
+    }
+    let total: u64 = found.iter().map(|artifact| artifact.size).sum();
+    println!(
+        "{} {} in {} directories, ignored by git but not known to devdust.",
+        "Total:".bold(),
+        format_size(total).yellow().bold(),
+        found.len()
+    );
+    println!(
+        "{}",
+        "Review them by hand; add a custom rule for those that are build output.".bright_black()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! `devdust duplicates` subcommand
+//!
+//! Reports npm packages installed identically in several projects, e.g.
+//! "typescript@5.4.5 appears in 14 projects", to show what a shared package
+//! store such as pnpm's would save.
+
+use std::{env, path::PathBuf};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{
+    find_duplicate_packages, format_size, scan_directory, Config, ProjectType, ScanOptions,
+};
+
+/// Arguments for the `duplicates` subcommand
+#[derive(Args, Debug)]
+pub struct DuplicatesArgs {
+    /// Directories to scan for Node.js projects (defaults to current directory)
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+
+    /// Number of packages to show
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 20)]
+    top: usize,
+}
+
+/// Runs the `duplicates` subcommand
+pub fn run(args: DuplicatesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = if args.paths.is_empty() {
+        vec![env::current_dir()?]
+    } else {
+        args.paths
+    };
+
+    let mut projects = Vec::new();
+    for path in &paths {
+        if !path.is_dir() {
+            return Err(format!("Path is not a directory: {}", path.display()).into());
+        }
+        let config = Config::load(path)?;
+        let options = ScanOptions::builder()
+            .project_types([ProjectType::Node])
+            .exclude(config.exclude)
+            .custom_rules(config.rules)
+            .build();
+        options.validate()?;
+        projects.extend(scan_directory(path, &options).filter_map(Result::ok));
+    }
+
+    let duplicates = find_duplicate_packages(&projects);
+    if duplicates.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No package is installed in more than one of {} Node.js projects.",
+                projects.len()
+            )
+            .green()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        "Packages installed in several projects:".cyan().bold()
+    );
+    let shown = &duplicates[..args.top.min(duplicates.len())];
+    let width = shown
+        .iter()
+        .map(|package| package.name.len() + package.version.len() + 1)
+        .max()
+        .unwrap_or(0);
+    for package in shown {
+        let release = format!("{}@{}", package.name, package.version);
+        println!(
+            "  {} appears in {:>3} projects ≈ {} {}",
+            format!("{:<width$}", release).white().bold(),
+            package.projects.len(),
+            format_size(package.total_size()).yellow(),
+            format!("({} reclaimable)", format_size(package.reclaimable_size())).bright_black()
+        );
+    }
+    if duplicates.len() > shown.len() {
+        println!(
+            "  {} and {} more",
+            "…".bright_black(),
+            duplicates.len() - shown.len()
+        );
+    }
+
+    let reclaimable: u64 = duplicates.iter().map(|p| p.reclaimable_size()).sum();
+    println!(
+        "{} {} held in duplicate copies across {} projects.",
+        "Total:".bold(),
+        format_size(reclaimable).yellow().bold(),
+        projects.len()
+    );
+    println!(
+        "{}",
+        "pnpm keeps one copy of each package; cleaning node_modules of inactive projects also helps."
+            .bright_black()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! `devdust inspect` subcommand
+//!
+//! Lists the biggest files and subdirectories inside a project's artifact
+//! directories, e.g. which packages fill `node_modules` or which profiles
+//! fill `target/`.
+
+use std::{env, path::PathBuf};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{format_size, Config, Project, ScanOptions};
+
+/// Arguments for the `inspect` subcommand
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    /// Project directory to inspect (defaults to current directory)
+    #[arg(value_name = "PROJECT")]
+    project: Option<PathBuf>,
+
+    /// Number of entries to show
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 20)]
+    top: usize,
+
+    /// How many levels below the artifact directories to break down (e.g., 2 for target/debug/deps)
+    #[arg(short, long, value_name = "LEVELS", default_value_t = 1)]
+    depth: usize,
+}
+
+/// Runs the `inspect` subcommand
+pub fn run(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match args.project {
+        Some(path) => path,
+        None => env::current_dir()?,
+    };
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path.display()).into());
+    }
+
+    // Custom rules from the configuration recognize the same projects as a scan
+    let config = Config::load(&path)?;
+    let project = Project::detect(&path, &config.rules)
+        .ok_or_else(|| format!("No project found in {}", path.display()))?;
+    let options = ScanOptions::default();
+
+    let entries = project.largest_entries(&options, args.depth);
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    println!(
+        "{} {} {} {}",
+        "●".blue().bold(),
+        project.display_name().white().bold(),
+        format!("({})", project.type_name()).bright_black(),
+        format_size(total).yellow().bold()
+    );
+    if entries.is_empty() {
+        println!("{}", "No artifacts to inspect.".yellow());
+        return Ok(());
+    }
+
+    let shown = &entries[..args.top.min(entries.len())];
+    let width = shown
+        .iter()
+        .map(|entry| format_size(entry.size).len())
+        .max()
+        .unwrap_or(0);
+    for entry in shown {
+        let share = entry.size as f64 * 100.0 / total.max(1) as f64;
+        let mut path = entry.path.display().to_string();
+        if entry.is_dir {
+            path.push(std::path::MAIN_SEPARATOR);
+        }
+        println!(
+            "  {:>width$} {} {}",
+            format_size(entry.size).yellow(),
+            format!("{:>5.1}%", share).bright_black(),
+            path
+        );
+    }
+
+    let hidden = &entries[shown.len()..];
+    if !hidden.is_empty() {
+        let hidden_size: u64 = hidden.iter().map(|entry| entry.size).sum();
+        println!(
+            "  {} and {} more ({})",
+            "…".bright_black(),
+            hidden.len(),
+            format_size(hidden_size)
+        );
+    }
+
+    Ok(())
+}
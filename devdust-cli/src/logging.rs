@@ -0,0 +1,40 @@
+//! `--log-level` and `--log-file`
+//!
+//! The core library and the CLI emit `tracing` events for every project
+//! detected or skipped and every artifact removed. They are off by default and
+//! go to stderr, or to a file, which suits scheduled runs and bug reports.
+
+use std::{fs::OpenOptions, io, path::Path, sync::Mutex};
+
+use tracing_subscriber::filter::LevelFilter;
+
+/// Installs the event subscriber; with a file but no level, `info` is logged
+pub fn init(level: Option<LevelFilter>, file: Option<&Path>) -> io::Result<()> {
+    let default = if file.is_some() {
+        LevelFilter::INFO
+    } else {
+        LevelFilter::OFF
+    };
+    let level = level.unwrap_or(default);
+    if level == LevelFilter::OFF {
+        return Ok(());
+    }
+
+    let subscriber = tracing_subscriber::fmt().with_max_level(level);
+    match file {
+        // Appended to, so scheduled runs add to the same log
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            subscriber
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .init();
+        }
+        // Colored like the rest of the output, as decided by --color
+        None => subscriber
+            .with_ansi(colored::control::SHOULD_COLORIZE.should_colorize())
+            .with_writer(io::stderr)
+            .init(),
+    }
+    Ok(())
+}
@@ -6,16 +6,53 @@
 //! Author: Ext Rise <nayanchandradas@hotmail.com>
 //! Repository: https://github.com/extrise/devdust
 
+mod archive;
+mod background;
+mod caches;
+mod containers;
+mod discover;
+mod duplicates;
+mod inspect;
+mod logging;
+mod ncdu;
+mod output;
+mod pins;
+mod progress;
+mod quarantine;
+mod schedule;
+mod stats;
+mod table;
+mod timings;
+mod tui;
+
 use std::{
-    env,
-    io::{self, Write},
-    path::PathBuf,
-    process,
+    env, fs,
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitCode, Stdio},
+    sync::Arc,
+    thread,
+    time::Duration,
 };
 
-use clap::{Parser, ValueEnum};
+use clap::{
+    builder::{PossibleValuesParser, TypedValueParser},
+    Parser, Subcommand, ValueEnum,
+};
 use colored::*;
-use devdust_core::{format_elapsed_time, format_size, scan_directory, Project, ScanOptions};
+use devdust_core::{
+    format_elapsed_time, format_size, fs_kind, group_by_type, is_pinned, parse_age, parse_size,
+    rank_for_cleanup, scan_many, select_until_freed, sort_projects, AgeSource, Archive,
+    ArchiveBatch, BackgroundRemover, CleanError, CleanHooks, CleanReport, Config, GitPolicy,
+    History, HistoryEntry, PartialClean, Pins, Project, ProjectType, Quarantine, QuarantineBatch,
+    RetryPolicy, ScanOptions, ScanTimings, SizeCache, SortKey, TypeGroup, KEEP_FILE_NAME,
+};
+use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect};
+use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
+
+/// How long a native clean command (e.g., `gradle clean`) may run before falling back
+const NATIVE_CLEAN_TIMEOUT: Duration = Duration::from_secs(120);
 
 // ============================================================================
 // CLI Argument Parsing
@@ -29,41 +66,339 @@ use devdust_core::{format_elapsed_time, format_size, scan_directory, Project, Sc
     author = "Ext Rise <nayanchandradas@hotmail.com>",
     about = "Scan and clean build artifacts from development projects",
     long_about = "Dev Dust recursively scans directories to find development projects \
-                  (Rust, Node.js, Python, Go, Ruby, Terraform, Docker, Bazel, and 15+ more) \
-                  and cleans their build artifacts to reclaim disk space."
+                  (Rust, Node.js, Python, Go, Ruby, Terraform, Docker, Bazel, and 24+ more) \
+                  and cleans their build artifacts to reclaim disk space.",
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
-    /// Directories to scan (defaults to current directory)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Directories to scan (defaults to current directory; `-` reads them from stdin)
     #[arg(value_name = "PATHS")]
     paths: Vec<PathBuf>,
 
+    /// Read directories to scan from a file, one per line (`-` for stdin)
+    #[arg(long, value_name = "FILE", conflicts_with = "files_from0")]
+    files_from: Option<PathBuf>,
+
+    /// Like --files-from, but the directories are separated by NUL bytes (`find -print0`)
+    #[arg(long, value_name = "FILE")]
+    files_from0: Option<PathBuf>,
+
+    /// Measure sizes by allocated disk blocks instead of file lengths, like `du`
+    #[arg(long, overrides_with = "no_disk_usage")]
+    disk_usage: bool,
+
+    /// Measure file lengths, even if the config file asks for disk usage
+    #[arg(long, overrides_with = "disk_usage")]
+    no_disk_usage: bool,
+
+    /// Estimate sizes from a sample of each artifact folder, much faster on big trees
+    #[arg(long, conflicts_with = "disk_usage")]
+    estimate: bool,
+
+    /// Don't calculate sizes, only list projects and their artifact folders
+    #[arg(
+        long,
+        conflicts_with_all = ["disk_usage", "estimate", "min_size", "free", "interactive"]
+    )]
+    no_size: bool,
+
+    /// Report how long detection, sizing and deletion took, per root and per project
+    #[arg(long)]
+    timings: bool,
+
+    /// Count hardlinked files (pnpm stores, ccache) once per link instead of once
+    #[arg(long, overrides_with = "no_count_links")]
+    count_links: bool,
+
+    /// Count hardlinked files once, even if the config file counts every link
+    #[arg(long, overrides_with = "count_links")]
+    no_count_links: bool,
+
+    /// Also find node_modules, target and similar folders whose project sources are gone
+    #[arg(long, overrides_with = "no_orphans")]
+    orphans: bool,
+
+    /// Don't look for orphaned artifact folders, even if the config file enables it
+    #[arg(long, overrides_with = "orphans")]
+    no_orphans: bool,
+
+    /// Also list Cargo target directories shared by several projects, once each
+    #[arg(long)]
+    shared_targets: bool,
+
+    /// Only show projects whose dependencies or build output are older than their lockfile
+    #[arg(long)]
+    stale_only: bool,
+
     /// Clean all found projects without confirmation
     #[arg(short, long)]
     all: bool,
 
     /// Follow symbolic links during scanning
-    #[arg(short = 'L', long)]
+    #[arg(short = 'L', long, overrides_with = "no_follow_symlinks")]
     follow_symlinks: bool,
 
+    /// Don't follow symbolic links, even if the config file enables it
+    #[arg(long, overrides_with = "follow_symlinks")]
+    no_follow_symlinks: bool,
+
     /// Stay on the same filesystem (don't cross mount points)
-    #[arg(short = 's', long)]
+    #[arg(short = 's', long, overrides_with = "no_same_filesystem")]
     same_filesystem: bool,
 
+    /// Cross mount points, even if the config file says to stay on one filesystem
+    #[arg(long, overrides_with = "same_filesystem")]
+    no_same_filesystem: bool,
+
+    /// Only look for projects this many directory levels below each path
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
     /// Only show projects older than specified time (e.g., 30d, 2w, 6M)
     #[arg(short, long, value_name = "TIME")]
     older: Option<String>,
 
+    /// Measure project age from the last modification, last access or last git commit
+    #[arg(long, value_name = "SOURCE", value_parser = age_source_parser())]
+    age_source: Option<AgeSource>,
+
     /// Quiet mode (minimal output)
     #[arg(short, long)]
     quiet: bool,
 
+    /// Explain on stderr why directories and projects were skipped (hidden, excluded, too new, ...)
+    #[arg(short, long, conflicts_with = "quiet")]
+    verbose: bool,
+
     /// Dry run (show what would be deleted without actually deleting)
     #[arg(short = 'n', long)]
     dry_run: bool,
 
+    /// Only include these project types (e.g., rust,node,python)
+    #[arg(long = "type", value_name = "TYPES", value_delimiter = ',', value_parser = project_type_parser())]
+    types: Vec<ProjectType>,
+
+    /// Skip these project types (e.g., unity)
+    #[arg(long, value_name = "TYPES", value_delimiter = ',', value_parser = project_type_parser())]
+    exclude_type: Vec<ProjectType>,
+
+    /// Order of the project listing
+    #[arg(long, value_name = "KEY", default_value = "size", value_parser = sort_key_parser())]
+    sort: SortKey,
+
+    /// Reverse the listing order
+    #[arg(short = 'r', long)]
+    reverse: bool,
+
+    /// Group the listing, with subtotals before it (by project type)
+    #[arg(long, value_name = "KEY", value_enum, conflicts_with = "interactive")]
+    group_by: Option<GroupBy>,
+
+    /// Also clean environments such as Python virtualenvs (.venv, venv, .tox)
+    #[arg(long)]
+    envs: bool,
+
+    /// Also clean experiment runs and checkpoints (wandb, mlruns, lightning_logs)
+    #[arg(long)]
+    experiments: bool,
+
+    /// Keep tool caches such as __pycache__ and .pytest_cache
+    #[arg(long)]
+    no_caches: bool,
+
+    /// Only clean tool caches (.turbo, node_modules/.cache, __pycache__), keeping builds and dependencies
+    #[arg(long, conflicts_with_all = ["no_caches", "envs", "experiments"])]
+    caches_only: bool,
+
+    /// Only clean dependency folders (node_modules, vendor) next to a lockfile
+    #[arg(long, overrides_with = "no_require_lockfile")]
+    require_lockfile: bool,
+
+    /// Clean dependency folders without a lockfile, even if the config file requires one
+    #[arg(long, overrides_with = "require_lockfile")]
+    no_require_lockfile: bool,
+
+    /// Warn about or skip projects with uncommitted changes or unpushed commits
+    #[arg(long, value_name = "POLICY", value_parser = git_policy_parser())]
+    git: Option<GitPolicy>,
+
+    /// Skip projects with less than this much in artifacts (e.g., 100M)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    min_size: Option<u64>,
+
+    /// Clean the stalest, largest projects until this much space is freed (e.g., 50G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, conflicts_with = "interactive")]
+    free: Option<u64>,
+
+    /// Open a full-screen interface to browse, select and clean projects
+    #[arg(short = 'i', long, conflicts_with = "all")]
+    interactive: bool,
+
+    /// Pick the projects to clean from a checklist and confirm once, instead of per project
+    #[arg(long, conflicts_with_all = ["all", "interactive", "free"])]
+    select: bool,
+
+    /// Never prompt; fail unless --all or --dry-run says what to do (implied in CI and without a terminal)
+    #[arg(long, conflicts_with_all = ["interactive", "select"])]
+    non_interactive: bool,
+
+    /// Move artifacts to the system trash instead of deleting them permanently
+    #[arg(short = 't', long)]
+    trash: bool,
+
+    /// Move artifacts to the quarantine folder so `devdust undo` can restore them
+    #[arg(long, conflicts_with = "trash")]
+    quarantine: bool,
+
+    /// Pack artifacts into compressed archives in DIR before deleting them, for `devdust restore`
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["trash", "quarantine"])]
+    archive: Option<PathBuf>,
+
+    /// Move artifacts to DIR (--relocate=DIR; default: relocate_to in the config), leaving symlinks
+    #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true, conflicts_with_all = ["trash", "quarantine", "archive"])]
+    relocate: Option<Option<PathBuf>>,
+
+    /// Rename artifacts out of the way at once and delete them on a background thread
+    #[arg(long, conflicts_with_all = ["trash", "quarantine", "archive", "relocate"])]
+    background: bool,
+
+    /// Like --background, but leave deletions unfinished at exit to a detached process
+    #[arg(long, conflicts_with_all = ["trash", "quarantine", "archive", "relocate"])]
+    detach: bool,
+
+    /// Run the ecosystem's own cleaner (cargo clean, gradle clean, ...) where available
+    #[arg(long, conflicts_with_all = ["trash", "quarantine", "archive", "relocate", "background", "detach", "sweep", "sweep_toolchains", "sweep_debug"])]
+    use_native_clean: bool,
+
+    /// Clean artifacts on network and FUSE filesystems (NFS, SMB, sshfs, ...) too
+    #[arg(long)]
+    allow_network: bool,
+
+    /// Retry a failed deletion N times, waiting longer each time (default: 3 on Windows, else 0)
+    #[arg(long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// Report directories that could not be deleted as warnings and count the rest as cleaned
+    #[arg(long)]
+    ignore_errors: bool,
+
+    /// Only remove Rust build units unused for this long, keeping the rest of target/ (e.g., 30d)
+    #[arg(long, value_name = "TIME", conflicts_with_all = ["trash", "quarantine", "archive", "relocate", "background", "detach"])]
+    sweep: Option<String>,
+
+    /// Only remove Rust build units made by older compiler versions
+    #[arg(long, conflicts_with_all = ["trash", "quarantine", "archive", "relocate", "background", "detach"])]
+    sweep_toolchains: bool,
+
+    /// Only remove Rust debug builds, keeping release builds
+    #[arg(long, conflicts_with_all = ["trash", "quarantine", "archive", "relocate", "background", "detach"])]
+    sweep_debug: bool,
+
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value = "pretty")]
     format: OutputFormat,
+
+    /// Write CACHEDIR.TAG into the artifact folders found, so backup tools skip them, instead of cleaning
+    #[arg(long, conflicts_with_all = ["all", "free", "interactive", "select", "dry_run", "print0"])]
+    tag_caches: bool,
+
+    /// Only print the artifact paths that cleaning would remove, each followed by a NUL byte
+    #[arg(long, conflicts_with_all = ["format", "all", "free", "interactive", "select"])]
+    print0: bool,
+
+    /// When to use colors; `auto` honors NO_COLOR and disables them when not on a terminal
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto",
+        global = true
+    )]
+    color: ColorChoice,
+
+    /// Log detections, skips and deletions at this level to stderr (default: off, info with --log-file)
+    #[arg(long, value_name = "LEVEL", value_parser = log_level_parser(), global = true)]
+    log_level: Option<LevelFilter>,
+
+    /// Append the log to FILE instead of writing it to stderr
+    #[arg(long, value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Skip directories matching this glob (repeatable; e.g., 'vendor', '/srv/**')
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Read exclude patterns from a file, one per line
+    #[arg(long, value_name = "FILE")]
+    exclude_from: Vec<PathBuf>,
+
+    /// Show but never clean projects matching this glob (repeatable; e.g., '~/work/prod-app')
+    #[arg(long, value_name = "GLOB")]
+    protect: Vec<String>,
+
+    /// Recalculate all artifact sizes instead of using the size cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Don't read .devdustignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Don't walk directories that git ignores (.gitignore, .git/info/exclude, core.excludesFile)
+    #[arg(long, overrides_with = "no_respect_gitignore")]
+    respect_gitignore: bool,
+
+    /// Walk directories that git ignores, even if the config file says to skip them
+    #[arg(long, overrides_with = "respect_gitignore")]
+    no_respect_gitignore: bool,
+
+    /// Number of worker threads for scanning (defaults to the number of CPUs)
+    #[arg(short = 'j', long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+
+    /// Use this configuration file instead of the global and local ones
+    #[arg(short = 'c', long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+/// Subcommands beyond the default scan-and-clean behavior
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report and prune global toolchain caches (~/.cargo/registry, ~/.npm, ...)
+    Caches(caches::CachesArgs),
+    /// Report and prune Docker/Podman build caches, dangling images and stopped containers
+    Containers(containers::ContainersArgs),
+    /// Restore the artifacts moved to the quarantine by the last clean
+    Undo,
+    /// Permanently delete quarantined artifacts
+    Purge(quarantine::PurgeArgs),
+    /// Unpack artifacts archived by a clean with --archive
+    Restore(archive::RestoreArgs),
+    /// Run devdust periodically via systemd, launchd or Task Scheduler
+    Schedule(schedule::ScheduleArgs),
+    /// Show how much space past cleans reclaimed, per project type and month
+    Stats,
+    /// List or remove projects pinned at the clean prompt
+    Pins(pins::PinsArgs),
+    /// Show the biggest files and directories inside a project's artifacts
+    Inspect(inspect::InspectArgs),
+    /// List big git-ignored directories no project type knows, as possible artifacts
+    Discover(discover::DiscoverArgs),
+    /// Report npm packages installed identically in several projects
+    Duplicates(duplicates::DuplicatesArgs),
+    /// Delete directories a clean with --detach left behind (started by devdust itself)
+    #[command(hide = true)]
+    RemoveStaged(background::RemoveStagedArgs),
+}
+
+/// Ways to group the project listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    /// Project type, the type holding the most first
+    Type,
 }
 
 /// Output format options
@@ -73,32 +408,154 @@ enum OutputFormat {
     Pretty,
     /// Plain text output (no colors)
     Plain,
+    /// One aligned row per project: name, type, size, age and path
+    Table,
     /// JSON output
     Json,
+    /// One JSON object per line, streamed as projects are found
+    Ndjson,
+    /// Comma-separated values, one row per project
+    Csv,
+    /// The artifacts as an ncdu export, to browse with `ncdu -f`
+    Ncdu,
+}
+
+impl OutputFormat {
+    /// Returns true for formats meant to be parsed by other programs
+    fn is_machine_readable(self) -> bool {
+        matches!(self, Self::Json | Self::Ndjson | Self::Csv | Self::Ncdu)
+    }
+}
+
+/// When to emit ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Colors on a terminal, unless NO_COLOR is set
+    Auto,
+    /// Always use colors, even when piped
+    Always,
+    /// Never use colors
+    Never,
+}
+
+/// Process exit codes, so scripts can tell outcomes apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// Everything selected was cleaned (or reported)
+    Success = 0,
+    /// An error stopped the run
+    Error = 1,
+    /// No projects with artifacts were found
+    NothingFound = 2,
+    /// At least one project could not be cleaned completely
+    PartialFailure = 3,
+    /// The user quit at a prompt
+    Aborted = 4,
+}
+
+impl Exit {
+    /// Picks the exit code for a run that found and failed to clean these many projects
+    pub fn for_run(projects_found: usize, projects_failed: usize) -> Self {
+        if projects_found == 0 {
+            Self::NothingFound
+        } else if projects_failed > 0 {
+            Self::PartialFailure
+        } else {
+            Self::Success
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
 }
 
 // ============================================================================
 // Main Entry Point
 // ============================================================================
 
-fn main() {
+fn main() -> ExitCode {
     // Parse command-line arguments
     let args = Args::parse();
+    set_colors(args.color, args.format);
+    if let Err(e) = logging::init(args.log_level, args.log_file.as_deref()) {
+        eprintln!(
+            "{} Could not open the log file: {}",
+            "Error:".red().bold(),
+            e
+        );
+        return Exit::Error.into();
+    }
 
     // Run the application and handle errors
-    if let Err(e) = run(args) {
-        eprintln!("{} {}", "Error:".red().bold(), e);
-        process::exit(1);
+    match run(args) {
+        Ok(exit) => exit.into(),
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            Exit::Error.into()
+        }
     }
 }
 
 /// Main application logic
-fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
-    // Determine paths to scan
-    let paths = if args.paths.is_empty() {
-        vec![env::current_dir()?]
+fn run(args: Args) -> Result<Exit, Box<dyn std::error::Error>> {
+    if let Some(command) = args.command {
+        match command {
+            Command::Caches(caches_args) => caches::run(caches_args),
+            Command::Containers(containers_args) => containers::run(containers_args),
+            Command::Undo => quarantine::undo(),
+            Command::Purge(purge_args) => quarantine::purge(purge_args),
+            Command::Restore(restore_args) => archive::restore(restore_args),
+            Command::Schedule(schedule_args) => schedule::run(schedule_args),
+            Command::Stats => stats::run(),
+            Command::Pins(pins_args) => pins::run(pins_args),
+            Command::Inspect(inspect_args) => inspect::run(inspect_args),
+            Command::Discover(discover_args) => discover::run(discover_args),
+            Command::Duplicates(duplicates_args) => duplicates::run(duplicates_args),
+            Command::RemoveStaged(remove_args) => background::run(remove_args),
+        }?;
+        return Ok(Exit::Success);
+    }
+
+    // Load configuration (global + project-local, or an explicit file)
+    let config = match &args.config {
+        Some(path) => Config::from_file(path)?,
+        None => Config::load(&env::current_dir()?)?,
+    };
+
+    // Directory lists extend the CLI arguments; `-` stands for stdin
+    let path_list = match (&args.files_from, &args.files_from0) {
+        (Some(file), _) => Some((file.as_path(), b'\n')),
+        (_, Some(file)) => Some((file.as_path(), b'\0')),
+        (None, None) => None,
+    };
+    let stdin_dash = args.paths.iter().any(|path| path.as_os_str() == "-");
+    if stdin_dash && path_list.is_some_and(|(file, _)| file.as_os_str() == "-") {
+        return Err("stdin can only be read once: use either `-` or --files-from -".into());
+    }
+
+    let mut listed = Vec::new();
+    for path in &args.paths {
+        if path.as_os_str() == "-" {
+            listed.extend(read_path_list(path, b'\n')?);
+        } else {
+            listed.push(path.clone());
+        }
+    }
+    if let Some((file, separator)) = path_list {
+        listed.extend(read_path_list(file, separator)?);
+    }
+
+    // Determine paths to scan: CLI arguments, then config, then current directory.
+    // An empty directory list scans nothing rather than falling back.
+    let paths = if !args.paths.is_empty() || path_list.is_some() {
+        listed
+    } else if !config.paths.is_empty() {
+        config.paths.clone()
     } else {
-        args.paths
+        vec![env::current_dir()?]
     };
 
     // Validate paths
@@ -111,67 +568,338 @@ fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Parse age filter if provided
-    let min_age_seconds = if let Some(ref age_str) = args.older {
-        parse_age_filter(age_str)?
-    } else {
-        0
+    // Parse age filter if provided (the flag takes precedence over the config)
+    let min_age_seconds = match args.older.as_deref().or(config.older.as_deref()) {
+        Some(age_str) => parse_age(age_str)?,
+        None => 0,
     };
 
-    // Configure scan options
-    let scan_options = ScanOptions {
-        follow_symlinks: args.follow_symlinks,
-        same_filesystem: args.same_filesystem,
-        min_age_seconds,
+    // Exclude patterns: config first, then the command line
+    let mut exclude = config.exclude.clone();
+    for file in &args.exclude_from {
+        exclude.extend(read_exclude_file(file)?);
+    }
+    exclude.extend(args.exclude);
+    let mut protected = config.protected.clone();
+    protected.extend(args.protect);
+
+    // Worker threads: flag, then config, then all available cores
+    let jobs = match args.jobs.map(usize::from).or(config.jobs) {
+        Some(jobs) => jobs,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    // Size calculation shares the same worker count as detection
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()?;
+
+    // --sweep flags remove only the stale parts of artifact directories
+    let partial = PartialClean {
+        older_than_seconds: args.sweep.as_deref().map(parse_age).transpose()?,
+        stale_toolchains: args.sweep_toolchains,
+        debug_only: args.sweep_debug,
     };
 
+    // Per-type settings from the config; --exclude-type disables types entirely
+    let mut type_overrides = config.type_overrides()?;
+    for project_type in args.exclude_type {
+        type_overrides.entry(project_type).or_default().enabled = false;
+    }
+
+    // Configure scan options
+    let mut builder = ScanOptions::builder()
+        .follow_symlinks(switch(
+            args.follow_symlinks,
+            args.no_follow_symlinks,
+            config.follow_symlinks,
+        ))
+        .same_filesystem(switch(
+            args.same_filesystem,
+            args.no_same_filesystem,
+            config.same_filesystem,
+        ))
+        .min_age(Duration::from_secs(min_age_seconds))
+        .age_source(args.age_source.or(config.age_source).unwrap_or_default())
+        .min_artifact_size(args.min_size.unwrap_or(0))
+        .disk_usage(switch(
+            args.disk_usage,
+            args.no_disk_usage,
+            config.disk_usage,
+        ))
+        .dedupe_hardlinks(!switch(
+            args.count_links,
+            args.no_count_links,
+            config.count_links,
+        ))
+        .estimate_sizes(args.estimate)
+        .skip_sizes(args.no_size)
+        .orphans(switch(args.orphans, args.no_orphans, config.orphans))
+        .shared_targets(args.shared_targets)
+        .stale_only(args.stale_only)
+        .exclude(exclude)
+        .protected(protected)
+        .project_types(args.types)
+        .type_overrides(type_overrides)
+        .parallelism(jobs)
+        .custom_rules(config.rules.clone())
+        .ignore_files(!args.no_ignore && config.ignore_files.unwrap_or(true))
+        .respect_gitignore(switch(
+            args.respect_gitignore,
+            args.no_respect_gitignore,
+            config.respect_gitignore,
+        ))
+        .git_policy(args.git.or(config.git).unwrap_or_default())
+        .require_lockfile(switch(
+            args.require_lockfile,
+            args.no_require_lockfile,
+            config.require_lockfile,
+        ))
+        .clean_builds(!args.caches_only)
+        .clean_caches(!args.no_caches)
+        .clean_environments(args.envs)
+        .clean_experiments(args.experiments)
+        .partial_clean((!partial.is_empty()).then_some(partial));
+    if let Some(depth) = args.max_depth {
+        builder = builder.max_depth(depth);
+    }
+    if !args.no_cache {
+        builder = builder.size_cache(Arc::new(SizeCache::load_default()));
+    }
+    if args.timings {
+        builder = builder.timings(ScanTimings::new());
+    }
+    let mut scan_options = builder.build();
+    scan_options.validate()?;
+
+    // Machine-readable formats own stdout, so suppress all human output
+    let machine_readable = args.print0 || args.format.is_machine_readable();
+    let quiet = args.quiet || machine_readable;
+
     // Print header
-    if !args.quiet && matches!(args.format, OutputFormat::Pretty) {
+    if !quiet && matches!(args.format, OutputFormat::Pretty) {
         print_header();
     }
 
+    // Command-line flags win over the config; quarantine wins over trash
+    let method = if let Some(partial) = scan_options.partial_clean.clone() {
+        CleanMethod::Partial(partial)
+    } else if args.use_native_clean {
+        CleanMethod::Native
+    } else if let Some(dir) = &args.archive {
+        CleanMethod::Archive(Archive::new(dir.clone()).begin())
+    } else if let Some(dir) = &args.relocate {
+        let dir = dir
+            .clone()
+            .or_else(|| config.relocate_to.clone())
+            .ok_or("--relocate needs a directory, or relocate_to in the configuration file")?;
+        CleanMethod::Relocate(dir)
+    } else if args.background || args.detach {
+        CleanMethod::Background(Arc::new(BackgroundRemover::start()))
+    } else if args.quarantine || (!args.trash && config.quarantine.unwrap_or(false)) {
+        CleanMethod::Quarantine(Quarantine::open_default()?.begin())
+    } else if args.trash || config.trash.unwrap_or(false) {
+        CleanMethod::Trash
+    } else {
+        CleanMethod::Delete
+    };
+    let mut retry = RetryPolicy::default();
+    if let Some(retries) = args.retries.or(config.retries) {
+        retry.retries = retries;
+    }
+    let mode = CleanMode {
+        dry_run: args.dry_run,
+        method,
+        allow_network: args.allow_network,
+        retry,
+        hooks: CleanHooks {
+            pre_clean: config.pre_clean.clone(),
+            post_clean: config.post_clean.clone(),
+        },
+    };
+
+    // Deleting over the network is slow and may pull files from under other machines
+    if !args.allow_network && !args.quiet {
+        for path in &paths {
+            let kind = fs_kind(path);
+            if kind.is_remote() {
+                eprintln!(
+                    "{} {} is on a {} filesystem; its projects are only cleaned with --allow-network",
+                    "Warning:".yellow(),
+                    path.display(),
+                    kind
+                );
+            }
+        }
+    }
+
+    // --free picks projects itself and cleans them without asking
+    let mut auto_clean = args.all || args.free.is_some();
+
+    // Without --all there is nobody to confirm, so machine formats only report
+    let clean_all = auto_clean && !args.dry_run;
+
+    // A prompt would hang CI jobs and pipes, so refuse before scanning
+    let needs_terminal = args.interactive
+        || args.select
+        || !(auto_clean || args.dry_run || machine_readable || args.tag_caches);
+    if needs_terminal {
+        if let Some(reason) = non_interactive_reason(args.non_interactive) {
+            return Err(format!(
+                "cleaning needs confirmation, but {}; pass --all to clean without asking or --dry-run to preview",
+                reason
+            )
+            .into());
+        }
+    }
+
+    // Projects pinned at an earlier prompt are left out of every run
+    let pins = Pins::open_default().ok();
+    let pinned = pins
+        .as_ref()
+        .and_then(|pins| pins.list().ok())
+        .unwrap_or_default();
+
+    // Streaming output skips the buffering and sorting below entirely
+    if matches!(args.format, OutputFormat::Ndjson) {
+        if args.free.is_some() {
+            return Err("--free needs all projects up front and cannot stream ndjson".into());
+        }
+        if args.verbose {
+            scan_options.observer = Some(Arc::new(progress::SkipReporter::new(None)));
+        }
+        let result = output::stream_ndjson(&paths, &scan_options, &pinned, clean_all, &mode);
+        save_size_cache(&scan_options);
+        if let Some(scan_timings) = &scan_options.timings {
+            timings::print_scan(scan_timings);
+        }
+        return result;
+    }
+
     // Scan for projects
     let mut projects = Vec::new();
     let mut total_artifact_size = 0u64;
 
-    for path in &paths {
-        if !args.quiet {
-            println!(
-                "{} {}",
-                "Scanning:".cyan().bold(),
-                path.display().to_string().white()
-            );
-        }
+    // Live feedback while scanning; hidden in quiet mode and when not on a terminal
+    let progress = (!quiet).then(progress::ScanProgress::start);
+    scan_options.progress = progress.as_ref().map(|p| p.callback());
+    if args.verbose {
+        let reporter = progress::SkipReporter::new(progress.as_ref());
+        scan_options.observer = Some(Arc::new(reporter));
+    }
 
-        // Scan the directory
-        for result in scan_directory(path, &scan_options) {
-            match result {
-                Ok(project) => {
-                    // Calculate artifact size
-                    let artifact_size = project.calculate_artifact_size(&scan_options);
+    if let Some(progress) = &progress {
+        progress.suspend(|| {
+            for path in &paths {
+                println!(
+                    "{} {}",
+                    "Scanning:".cyan().bold(),
+                    path.display().to_string().white()
+                );
+            }
+        });
+    }
 
-                    // Skip projects with no artifacts
-                    if artifact_size == 0 {
-                        continue;
+    // All roots are scanned at once; sizes are calculated on the scan workers
+    let mut pinned_found = 0usize;
+    for result in scan_many(&paths, &scan_options) {
+        match result {
+            Ok((project, _)) if is_pinned(&pinned, &project.path) => {
+                pinned_found += 1;
+                if args.verbose {
+                    let report = || progress::SkipReporter::print(&project.path, "pinned");
+                    match &progress {
+                        Some(progress) => progress.suspend(report),
+                        None => report(),
                     }
-
-                    total_artifact_size += artifact_size;
-                    projects.push((project, artifact_size));
                 }
-                Err(e) => {
-                    if !args.quiet {
-                        eprintln!("{} {}", "Warning:".yellow(), e);
-                    }
+            }
+            Ok((project, artifact_size)) => {
+                total_artifact_size += artifact_size;
+                projects.push((project, artifact_size));
+            }
+            Err(e) => {
+                let warn = || eprintln!("{} {}", "Warning:".yellow(), e);
+                match &progress {
+                    Some(progress) => progress.suspend(warn),
+                    None if !args.quiet => warn(),
+                    None => {}
                 }
             }
         }
     }
 
-    // Sort projects by artifact size (largest first)
-    projects.sort_by(|a, b| b.1.cmp(&a.1));
+    // Later size calculations are not part of the scan
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+    scan_options.progress = None;
+    scan_options.observer = None;
+    save_size_cache(&scan_options);
+    if let Some(scan_timings) = &scan_options.timings {
+        timings::print_scan(scan_timings);
+    }
+
+    if pinned_found > 0 && !quiet {
+        println!(
+            "{} {} pinned projects (see {})",
+            "Skipping:".cyan().bold(),
+            pinned_found,
+            "devdust pins list".white().bold()
+        );
+    }
+
+    // Keep only the best candidates needed to reach the --free target
+    if let Some(target) = args.free {
+        // Protected projects can't contribute to the target
+        projects.retain(|(project, _)| !project.protected);
+        let available = projects.iter().map(|(_, size)| size).sum();
+        projects = select_until_freed(rank_for_cleanup(projects, &scan_options), target);
+        total_artifact_size = projects.iter().map(|(_, size)| size).sum();
+
+        if !quiet {
+            println!(
+                "{} free {} by cleaning {} projects ({})",
+                "Target:".cyan().bold(),
+                format_size(target).white().bold(),
+                projects.len(),
+                format_size(total_artifact_size)
+            );
+        }
+        if available < target && !args.quiet {
+            eprintln!(
+                "{} only {} of artifacts found, less than the {} target",
+                "Warning:".yellow(),
+                format_size(available),
+                format_size(target)
+            );
+        }
+    }
+
+    sort_projects(&mut projects, args.sort, args.reverse, &scan_options);
+    let groups = match args.group_by {
+        Some(GroupBy::Type) => group_by_type(&mut projects),
+        None => Vec::new(),
+    };
+
+    if args.print0 {
+        return output::print0(&projects, &scan_options);
+    }
+
+    match args.format {
+        OutputFormat::Json => {
+            return output::print_json(&projects, groups, &scan_options, clean_all, &mode)
+        }
+        OutputFormat::Csv => return output::print_csv(&projects, &scan_options, clean_all, &mode),
+        OutputFormat::Ncdu if clean_all => {
+            return Err("--format ncdu only exports what was found; drop --all".into())
+        }
+        OutputFormat::Ncdu => return ncdu::print(&paths, &projects, &scan_options),
+        _ => {}
+    }
 
     if projects.is_empty() {
-        if !args.quiet {
+        if !quiet {
             println!("\n{}", "Scan Finished...".green().bold());
             println!("{}", "No projects with build artifacts found.".yellow());
             println!("\n{}", "This could mean:".bright_black());
@@ -185,81 +913,453 @@ fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
                 "•".bright_black()
             );
         }
-        return Ok(());
+        return Ok(Exit::NothingFound);
+    }
+
+    if args.tag_caches {
+        return Ok(tag_caches(&projects, args.quiet));
+    }
+
+    // Hand over to the full-screen interface if requested
+    if args.interactive {
+        let found = projects.len();
+        let remover = background_remover(&mode);
+        let summary = tui::run(projects, &scan_options, mode, args.sort, args.reverse)?;
+        print_summary(
+            summary.projects_cleaned,
+            Some(summary.total_cleaned),
+            args.dry_run,
+        );
+        let failed = remover.map_or(0, |r| background::finish(&r, args.detach, args.quiet));
+        return Ok(Exit::for_run(found, summary.projects_failed + failed));
     }
 
     // Display results
-    if !args.quiet {
+    if !args.quiet && scan_options.skip_sizes {
+        println!(
+            "\n{} {} projects\n",
+            "Found:".green().bold(),
+            projects.len().to_string().white().bold()
+        );
+    } else if !args.quiet {
         println!(
             "\n{} {} projects with {} of artifacts\n",
             "Found:".green().bold(),
             projects.len().to_string().white().bold(),
-            format_size(total_artifact_size).white().bold()
+            format_measured(total_artifact_size, &scan_options)
+                .white()
+                .bold()
         );
     }
+    if !args.quiet && !groups.is_empty() {
+        print_groups(&groups, &scan_options);
+    }
+    // The table replaces the per-project cards below
+    let table = matches!(args.format, OutputFormat::Table);
+    if table && !args.quiet {
+        table::print(&projects, &scan_options);
+    }
+
+    // --select replaces the per-project prompts with one checklist
+    if args.select {
+        let Some(selected) = prompt_checklist(&projects, args.dry_run)? else {
+            println!("{}", "Nothing cleaned.".yellow());
+            return Ok(Exit::Aborted);
+        };
+        projects = projects
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| selected.contains(i))
+            .map(|(_, project)| project)
+            .collect();
+        auto_clean = true;
+    }
 
     // Display projects and prompt for cleaning
     let mut total_cleaned = 0u64;
     let mut projects_cleaned = 0usize;
+    let mut projects_failed = 0usize;
+    let mut aborted = false;
+    let mut deletions = Vec::new();
+    let projects_found = projects.len();
 
-    for (project, artifact_size) in projects {
+    for (index, (mut project, mut artifact_size)) in projects.into_iter().enumerate() {
         // Display project info
-        if !args.quiet {
+        if !args.quiet && !table {
             display_project(&project, artifact_size, &scan_options);
         }
 
+        // Protected projects are listed but never cleaned, not even with --all
+        if project.protected {
+            if !args.quiet && !table {
+                println!("  {} Protected, not cleaned\n", "■".magenta().bold());
+            }
+            continue;
+        }
+
         // Determine if we should clean this project
-        let should_clean = if args.all {
+        let should_clean = if auto_clean {
             true
         } else if args.dry_run {
             false
         } else {
-            prompt_clean(&project)?
+            match prompt_clean(&project, &scan_options)? {
+                Choice::Skip => false,
+                Choice::Clean => true,
+                Choice::All => {
+                    auto_clean = true;
+                    true
+                }
+                Choice::Pin => {
+                    let pins = pins.as_ref().ok_or("cannot determine data directory")?;
+                    pins.add(&project.path)?;
+                    println!(
+                        "  {} Pinned; future runs skip it ({} to undo)",
+                        "■".cyan().bold(),
+                        "devdust pins remove".white().bold()
+                    );
+                    false
+                }
+                Choice::Quit => {
+                    let remaining = projects_found - index;
+                    println!(
+                        "{} {} projects left unreviewed",
+                        "Exiting...".yellow(),
+                        remaining
+                    );
+                    aborted = true;
+                    break;
+                }
+                Choice::Only(dirs, size) => {
+                    let dirs: Vec<&str> = dirs.iter().map(String::as_str).collect();
+                    project = project.with_only_artifacts(&dirs);
+                    artifact_size = size;
+                    true
+                }
+            }
         };
 
         if should_clean {
             if args.dry_run {
-                if !args.quiet {
+                // The table already lists every size
+                if !args.quiet && !table && scan_options.skip_sizes {
+                    println!("  {} Would delete its artifacts", "→".blue());
+                } else if !args.quiet && !table {
                     println!(
                         "  {} Would delete {}",
                         "→".blue(),
-                        format_size(artifact_size)
+                        format_measured(artifact_size, &scan_options)
                     );
                 }
                 total_cleaned += artifact_size;
                 projects_cleaned += 1;
             } else {
                 // Actually clean the project
-                match project.clean() {
-                    Ok(deleted) => {
+                let result = clean_project(&project, &mode).and_then(|report| {
+                    if args.ignore_errors {
+                        Ok(report)
+                    } else {
+                        report.into_result()
+                    }
+                });
+                match result {
+                    Ok(report) => {
+                        for error in &report.failures {
+                            eprintln!("  {} Skipped: {}", "!".yellow().bold(), error);
+                        }
                         if !args.quiet {
+                            // Without the card above, say which project it was
+                            let name = if table {
+                                format!("{}: ", project.display_name())
+                            } else {
+                                String::new()
+                            };
                             println!(
-                                "  {} Cleaned {}",
+                                "  {} {}{} {}{}",
                                 "✓".green().bold(),
-                                format_size(deleted).green()
+                                name,
+                                mode.method.verb(),
+                                format_size(report.bytes_freed).green(),
+                                format_throughput(report.bytes_freed, report.duration)
+                                    .bright_black()
                             );
                         }
-                        total_cleaned += deleted;
+                        if args.timings {
+                            deletions.push((project.path.clone(), report.duration));
+                        }
+                        total_cleaned += report.bytes_freed;
                         projects_cleaned += 1;
                     }
                     Err(e) => {
                         eprintln!("  {} Failed to clean: {}", "✗".red().bold(), e);
+                        projects_failed += 1;
                     }
                 }
             }
         }
 
-        if !args.quiet {
+        if !args.quiet && !table {
             println!(); // Blank line between projects
         }
     }
 
-    // Print summary
-    if !args.quiet {
-        print_summary(projects_cleaned, total_cleaned, args.dry_run);
+    // Print summary; quitting at the prompt always shows what was done so far
+    if !args.quiet || aborted {
+        // Nothing was measured for a dry run without sizes
+        let total = Some(total_cleaned).filter(|_| !(args.dry_run && scan_options.skip_sizes));
+        print_summary(projects_cleaned, total, args.dry_run);
+        if matches!(mode.method, CleanMethod::Quarantine(_)) && projects_cleaned > 0 {
+            println!(
+                "{} Run {} to restore them",
+                "Undo:".green().bold(),
+                "devdust undo".white().bold()
+            );
+        }
+        if let CleanMethod::Archive(batch) = &mode.method {
+            if projects_cleaned > 0 {
+                println!(
+                    "{} Run {} to restore them",
+                    "Archived:".green().bold(),
+                    format!("devdust restore {}", batch.path().display())
+                        .white()
+                        .bold()
+                );
+            }
+        }
+    }
+
+    if args.timings {
+        timings::print_deletions(deletions);
+    }
+
+    if let Some(remover) = background_remover(&mode) {
+        projects_failed += background::finish(&remover, args.detach, args.quiet);
+    }
+
+    if aborted {
+        return Ok(Exit::Aborted);
+    }
+    Ok(Exit::for_run(projects_found, projects_failed))
+}
+
+/// Returns the remover deleting artifacts in the background, if the clean uses one
+fn background_remover(mode: &CleanMode) -> Option<Arc<BackgroundRemover>> {
+    match &mode.method {
+        CleanMethod::Background(remover) => Some(Arc::clone(remover)),
+        _ => None,
+    }
+}
+
+/// Settings that decide how selected projects are cleaned
+pub struct CleanMode {
+    /// Only report what would be freed
+    pub dry_run: bool,
+    /// What happens to the artifact directories
+    pub method: CleanMethod,
+    /// Whether artifacts on network and FUSE filesystems may be cleaned
+    pub allow_network: bool,
+    /// How often failed deletions are tried again
+    pub retry: RetryPolicy,
+    /// Commands run before and after each project is cleaned
+    pub hooks: CleanHooks,
+}
+
+/// What happens to cleaned artifact directories
+pub enum CleanMethod {
+    /// Delete them permanently
+    Delete,
+    /// Move them to the system trash
+    Trash,
+    /// Move them into a quarantine batch that `devdust undo` can restore
+    Quarantine(QuarantineBatch),
+    /// Pack them into compressed archives that `devdust restore` can unpack
+    Archive(ArchiveBatch),
+    /// Move them to another volume, leaving symbolic links behind
+    Relocate(PathBuf),
+    /// Rename them out of the way and delete them on a background thread
+    Background(Arc<BackgroundRemover>),
+    /// Delete only stale artifacts where a partial strategy exists
+    Partial(PartialClean),
+    /// Run the ecosystem's clean command, deleting directories where there is none
+    Native,
+}
+
+impl CleanMethod {
+    /// Describes a successful clean, e.g. "Moved to trash 1.2 GB"
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Delete => "Cleaned",
+            Self::Trash => "Moved to trash",
+            Self::Quarantine(_) => "Quarantined",
+            Self::Archive(_) => "Archived",
+            Self::Relocate(_) => "Relocated",
+            Self::Background(_) => "Cleaned",
+            Self::Partial(_) => "Swept",
+            Self::Native => "Cleaned",
+        }
+    }
+
+    /// Whether cleaned artifacts leave the disk, rather than wait in the trash or quarantine
+    pub fn frees_space(&self) -> bool {
+        !matches!(self, Self::Trash | Self::Quarantine(_))
+    }
+}
+
+/// Cleans a project with the given method
+///
+/// Projects whose artifacts a running process is using are skipped, so a build in
+/// progress is not left with a half-deleted target directory. A failing pre-clean
+/// hook skips the project too; a failing post-clean hook only warns.
+fn clean_project(project: &Project, mode: &CleanMode) -> Result<CleanReport, CleanError> {
+    if project.is_in_use() {
+        info!(path = %project.path.display(), reason = "in use", "not cleaning project");
+        return Err(CleanError::InUse {
+            path: project.path.clone(),
+        });
+    }
+    if !mode.allow_network {
+        if let Some((path, kind)) = project.remote_artifact_directory() {
+            info!(path = %project.path.display(), reason = "network filesystem", "not cleaning project");
+            return Err(CleanError::RemoteFilesystem { path, kind });
+        }
+    }
+    mode.hooks.before(project)?;
+
+    let result = match &mode.method {
+        CleanMethod::Delete => project.clean_with_retry(&mode.retry),
+        CleanMethod::Trash => project.clean_to_trash(),
+        CleanMethod::Quarantine(batch) => project.clean_to_quarantine(batch),
+        CleanMethod::Archive(batch) => project.clean_to_archive(batch),
+        CleanMethod::Relocate(dir) => project.relocate_artifacts(dir),
+        CleanMethod::Background(remover) => project.clean_in_background(remover),
+        CleanMethod::Partial(partial) => project.clean_partial(partial, &mode.retry),
+        CleanMethod::Native => project.clean_native(NATIVE_CLEAN_TIMEOUT),
+    };
+    if let Err(e) = mode.hooks.after(project, &result) {
+        eprintln!("{} {}", "Warning:".yellow(), e);
+    }
+    let report = result?;
+
+    if mode.method.frees_space() {
+        record_clean(project, report.bytes_freed);
+    }
+    Ok(report)
+}
+
+/// Adds a clean to the history used by `devdust stats`; failures only cost statistics
+fn record_clean(project: &Project, deleted: u64) {
+    if deleted == 0 {
+        return;
+    }
+
+    let result =
+        History::open_default().and_then(|h| h.record(&HistoryEntry::new(project, deleted)));
+    if let Err(e) = result {
+        eprintln!(
+            "{} Could not record clean history: {}",
+            "Warning:".yellow(),
+            e
+        );
+    }
+}
+
+/// Resolves an on/off option: `--x` or `--no-x` wins over the config file, which wins over off
+fn switch(on: bool, off: bool, config: Option<bool>) -> bool {
+    if on || off {
+        on
+    } else {
+        config.unwrap_or(false)
+    }
+}
+
+/// Parses project type identifiers, listing the valid ones in help output
+fn project_type_parser() -> impl TypedValueParser<Value = ProjectType> {
+    PossibleValuesParser::new(ProjectType::ALL.iter().map(ProjectType::id))
+        .map(|id| id.parse().unwrap_or(ProjectType::Custom))
+}
+
+/// Parses `--log-level` values, listing the valid levels in help output
+fn log_level_parser() -> impl TypedValueParser<Value = LevelFilter> {
+    PossibleValuesParser::new(["off", "error", "warn", "info", "debug", "trace"])
+        .map(|level| level.parse().unwrap_or(LevelFilter::OFF))
+}
+
+/// Parses `--git` values, listing the valid policies in help output
+fn git_policy_parser() -> impl TypedValueParser<Value = GitPolicy> {
+    PossibleValuesParser::new(GitPolicy::ALL.iter().map(GitPolicy::id))
+        .map(|policy| policy.parse().unwrap_or_default())
+}
+
+/// Parses `--age-source` values, listing the valid sources in help output
+fn age_source_parser() -> impl TypedValueParser<Value = AgeSource> {
+    PossibleValuesParser::new(AgeSource::ALL.iter().map(AgeSource::id))
+        .map(|source| source.parse().unwrap_or_default())
+}
+
+/// Parses `--sort` values, listing the valid keys in help output
+fn sort_key_parser() -> impl TypedValueParser<Value = SortKey> {
+    PossibleValuesParser::new(SortKey::ALL.iter().map(SortKey::id))
+        .map(|key| key.parse().unwrap_or_default())
+}
+
+/// Persists the size cache, if one is in use; failures only cost speed next time
+fn save_size_cache(options: &ScanOptions) {
+    if let Some(Err(e)) = options.size_cache.as_ref().map(|cache| cache.save()) {
+        eprintln!("{} Could not save size cache: {}", "Warning:".yellow(), e);
     }
+}
+
+/// Reads exclude patterns from a file, skipping blank lines and `#` comments
+fn read_exclude_file(path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read exclude file {}: {}", path.display(), e))?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Reads directories from a file (or stdin for `-`), split at `separator`
+///
+/// Blank entries are skipped, and with newlines a trailing `\r` is dropped.
+fn read_path_list(file: &Path, separator: u8) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let contents = if file.as_os_str() == "-" {
+        let mut contents = Vec::new();
+        io::stdin()
+            .lock()
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Cannot read directories from stdin: {}", e))?;
+        contents
+    } else {
+        fs::read(file)
+            .map_err(|e| format!("Cannot read directory list {}: {}", file.display(), e))?
+    };
+
+    Ok(contents
+        .split(|&byte| byte == separator)
+        .map(|entry| match separator {
+            b'\n' => entry.strip_suffix(b"\r").unwrap_or(entry),
+            _ => entry,
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(path_from_bytes)
+        .collect())
+}
+
+/// Converts raw bytes from a directory list into a path, lossily outside Unix
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
 
-    Ok(())
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
 }
 
 // ============================================================================
@@ -275,20 +1375,67 @@ fn print_header() {
     println!();
 }
 
+/// Explains why prompting is impossible, or returns `None` if it is fine to ask
+fn non_interactive_reason(forced: bool) -> Option<&'static str> {
+    let ci = env::var("CI").is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"));
+
+    if forced {
+        Some("--non-interactive was given")
+    } else if ci {
+        Some("a CI environment was detected (CI is set)")
+    } else if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        Some("devdust is not running in a terminal")
+    } else {
+        None
+    }
+}
+
+/// Turns colors on or off for everything printed afterwards
+///
+/// Applies to `colored` strings, the progress spinner and prompts; the
+/// full-screen mode reads the same setting.
+fn set_colors(choice: ColorChoice, format: OutputFormat) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            !matches!(format, OutputFormat::Plain)
+                && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && io::stdout().is_terminal()
+        }
+    };
+    colored::control::set_override(enabled);
+    dialoguer::console::set_colors_enabled(enabled);
+    dialoguer::console::set_colors_enabled_stderr(enabled);
+}
+
 /// Displays information about a project
 fn display_project(project: &Project, artifact_size: u64, options: &ScanOptions) {
     println!(
-        "{} {} {}",
+        "{} {}{} {}",
         "●".blue().bold(),
         project.display_name().white().bold(),
-        format!("({})", project.project_type.name()).bright_black()
+        manifest_label(project),
+        format!("({})", project.type_name()).bright_black()
     );
     println!("  {} {}", "Path:".bright_black(), project.path.display());
-    println!(
-        "  {} {}",
-        "Artifacts:".bright_black(),
-        format_size(artifact_size).yellow().bold()
-    );
+    if options.skip_sizes {
+        // Not measured; the directories are listed below
+    } else if options.disk_usage {
+        let apparent = project.artifact_disk_usage(options).apparent;
+        println!(
+            "  {} {} on disk {}",
+            "Artifacts:".bright_black(),
+            format_size(artifact_size).yellow().bold(),
+            format!("({} apparent)", format_size(apparent)).bright_black()
+        );
+    } else {
+        println!(
+            "  {} {}",
+            "Artifacts:".bright_black(),
+            format_measured(artifact_size, options).yellow().bold()
+        );
+    }
 
     // Show last modified time if available
     if let Ok(last_modified) = project.last_modified(options) {
@@ -301,20 +1448,196 @@ fn display_project(project: &Project, artifact_size: u64, options: &ScanOptions)
         }
     }
 
-    // List artifact directories
+    // Outdated dependencies are rebuilt on the next install anyway
+    let stale = project.stale_artifacts();
+    if !stale.is_empty() {
+        println!(
+            "  {} {} older than the lockfile; safe to clean, will be rebuilt anyway",
+            "Stale:".bright_black(),
+            stale.join(", ").green()
+        );
+    }
+
+    let kept = project.kept_artifacts();
+    if !kept.is_empty() {
+        println!(
+            "  {} {} (holds {}, never cleaned)",
+            "Kept:".bright_black(),
+            kept.join(", ").cyan(),
+            KEEP_FILE_NAME
+        );
+    }
+
+    // Show repository details so it's easier to judge whether the project is active
+    if let Some(info) = project.vcs_info() {
+        let mut details = vec![info.branch.unwrap_or_else(|| "detached HEAD".to_string())];
+        if let Some(elapsed) = info.last_commit.and_then(|t| t.elapsed().ok()) {
+            details.push(format!(
+                "last commit {}",
+                format_elapsed_time(elapsed.as_secs())
+            ));
+        }
+        let state = if info.dirty {
+            "dirty".yellow()
+        } else {
+            "clean".green()
+        };
+        println!(
+            "  {} {}, {}",
+            "Git:".bright_black(),
+            details.join(", ").bright_black(),
+            state
+        );
+    }
+
+    if options.git_policy == GitPolicy::Warn {
+        if let Some(status) = project.git_status().filter(|s| !s.is_clean()) {
+            println!(
+                "  {} {}",
+                "Warning:".yellow(),
+                status.describe().yellow().bold()
+            );
+        }
+    }
+
+    // List artifact directories with their sizes, largest first
+    let mut artifacts = project.artifacts(options);
+    artifacts.sort_by_key(|artifact| std::cmp::Reverse(artifact.size));
+    let names: Vec<String> = artifacts
+        .iter()
+        .map(|artifact| artifact.path.display().to_string())
+        .collect();
+    let width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+
     println!("  {} Artifact directories:", "→".bright_black());
-    for dir in project.project_type.artifact_directories() {
-        let dir_path = project.path.join(dir);
-        if dir_path.exists() {
-            println!("    • {}", dir.bright_black());
+    for (name, artifact) in names.iter().zip(&artifacts) {
+        if options.skip_sizes {
+            println!("    • {}", name.bright_black());
+            continue;
+        }
+        println!(
+            "    • {}  {}",
+            format!("{:width$}", name).bright_black(),
+            format_measured(artifact.size, options).yellow()
+        );
+    }
+}
+
+/// Prints the subtotals of a grouped listing, one line per group
+fn print_groups(groups: &[TypeGroup], options: &ScanOptions) {
+    let width = groups
+        .iter()
+        .map(|group| group.type_name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for group in groups {
+        let name = format!(
+            "{:width$}",
+            format!("{}:", group.type_name),
+            width = width + 1
+        );
+        if options.skip_sizes {
+            println!("  {} {} projects", name.white().bold(), group.projects);
+        } else {
+            println!(
+                "  {} {} projects, {}",
+                name.white().bold(),
+                group.projects,
+                format_measured(group.artifact_size, options).yellow()
+            );
         }
     }
+    println!();
+}
+
+/// Formats a size from the scan, marking estimates (e.g. "≈ 1.8 GB")
+fn format_measured(bytes: u64, options: &ScanOptions) -> String {
+    if options.estimate_sizes {
+        format!("≈ {}", format_size(bytes))
+    } else {
+        format_size(bytes)
+    }
+}
+
+/// Returns the manifest name and version shown after the folder name, with a leading space
+///
+/// The name is left out when it just repeats the folder name.
+fn manifest_label(project: &Project) -> String {
+    let metadata = project.metadata();
+    let name = metadata.name.filter(|name| *name != project.display_name());
+    let label: Vec<String> = name.into_iter().chain(metadata.version).collect();
+
+    if label.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", label.join(" ").cyan())
+    }
+}
+
+/// Tags the artifact directories of every project as caches instead of cleaning them
+fn tag_caches(projects: &[(Project, u64)], quiet: bool) -> Exit {
+    let mut tagged = 0usize;
+    let mut failed = 0usize;
+    for (project, _) in projects {
+        match project.tag_artifacts() {
+            Ok(count) => tagged += count,
+            Err(e) => {
+                failed += 1;
+                if !quiet {
+                    eprintln!(
+                        "{} could not tag {}: {}",
+                        "Warning:".yellow(),
+                        project.path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    if !quiet {
+        println!(
+            "{} {} artifact directories in {} projects (backup tools honoring CACHEDIR.TAG now skip them)",
+            "Tagged:".green().bold(),
+            tagged.to_string().white().bold(),
+            projects.len()
+        );
+    }
+    Exit::for_run(projects.len(), failed)
+}
+
+/// Formats how fast a clean went, e.g. " (420.5 MB/s in 3.2s)"
+///
+/// Cleans that finish almost at once say nothing useful about the disk and get
+/// an empty string.
+fn format_throughput(bytes: u64, elapsed: Duration) -> String {
+    if elapsed < Duration::from_millis(250) {
+        return String::new();
+    }
+    let per_second = bytes as f64 / elapsed.as_secs_f64();
+    format!(
+        " ({}/s in {:.1}s)",
+        format_size(per_second as u64),
+        elapsed.as_secs_f64()
+    )
 }
 
 /// Prints the final summary
-fn print_summary(projects_cleaned: usize, total_cleaned: u64, dry_run: bool) {
+fn print_summary(projects_cleaned: usize, total_cleaned: Option<u64>, dry_run: bool) {
     println!("{}", "═".repeat(50).cyan());
 
+    let Some(total_cleaned) = total_cleaned else {
+        println!(
+            "{} {} projects would be cleaned",
+            "Dry run:".yellow().bold(),
+            projects_cleaned.to_string().white().bold()
+        );
+        return;
+    };
     if dry_run {
         println!(
             "{} {} projects, {} would be freed",
@@ -336,70 +1659,180 @@ fn print_summary(projects_cleaned: usize, total_cleaned: u64, dry_run: bool) {
 // User Interaction
 // ============================================================================
 
+/// Answer to the clean prompt
+enum Choice {
+    Skip,
+    Clean,
+    /// Clean this project and all remaining ones without asking again
+    All,
+    /// Don't clean this project, now or in future runs
+    Pin,
+    /// Clean only these artifact directories, which hold this many bytes
+    Only(Vec<String>, u64),
+    /// Stop without looking at the remaining projects
+    Quit,
+}
+
 /// Prompts the user to confirm cleaning a project
-fn prompt_clean(project: &Project) -> Result<bool, Box<dyn std::error::Error>> {
+fn prompt_clean(
+    project: &Project,
+    options: &ScanOptions,
+) -> Result<Choice, Box<dyn std::error::Error>> {
+    loop {
+        print!(
+            "  {} Clean {} project? [y/N/s/a/p/o/q] (s = select directories, a = all remaining, p = pin, o = open folder): ",
+            "?".yellow().bold(),
+            project.display_name().white().bold()
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        return match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(Choice::Clean),
+            "n" | "no" | "" => Ok(Choice::Skip),
+            "s" | "select" => prompt_select(project, options),
+            "a" | "all" => Ok(Choice::All),
+            "p" | "pin" => Ok(Choice::Pin),
+            "q" | "quit" => Ok(Choice::Quit),
+            // Ask again once the user has had a look
+            "o" | "open" => {
+                if let Err(e) = open_in_file_manager(&project.path) {
+                    println!("  {} Cannot open the folder: {}", "!".red(), e);
+                }
+                continue;
+            }
+            _ => {
+                println!("  {} Invalid input, skipping...", "!".red());
+                Ok(Choice::Skip)
+            }
+        };
+    }
+}
+
+/// Shows a directory in Finder, Explorer or the desktop's file manager
+///
+/// Returns once the file manager is started, without waiting for it.
+pub(crate) fn open_in_file_manager(path: &Path) -> io::Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    process::Command::new(program)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// Lets the user tick the projects to clean, then confirm once
+///
+/// Protected projects are not offered. Returns the indices of the chosen
+/// projects, or `None` if the user backed out.
+fn prompt_checklist(
+    projects: &[(Project, u64)],
+    dry_run: bool,
+) -> Result<Option<Vec<usize>>, Box<dyn std::error::Error>> {
+    let theme = ColorfulTheme::default();
+    let candidates: Vec<usize> = (0..projects.len())
+        .filter(|&i| !projects[i].0.protected)
+        .collect();
+    let items: Vec<String> = candidates
+        .iter()
+        .map(|&i| {
+            let (project, size) = &projects[i];
+            format!(
+                "{:>10}  {} ({})  {}",
+                format_size(*size),
+                project.display_name(),
+                project.type_name(),
+                project.path.display()
+            )
+        })
+        .collect();
+
+    let Some(selected) = MultiSelect::with_theme(&theme)
+        .with_prompt("Projects to clean (space toggles, a toggles all, enter accepts)")
+        .items(&items)
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    if selected.is_empty() {
+        return Ok(None);
+    }
+    let selected: Vec<usize> = selected.into_iter().map(|i| candidates[i]).collect();
+
+    let total: u64 = selected.iter().map(|&i| projects[i].1).sum();
+    let confirmed = dry_run
+        || Confirm::with_theme(&theme)
+            .with_prompt(format!(
+                "Clean {} projects ({})?",
+                selected.len(),
+                format_size(total)
+            ))
+            .default(false)
+            .interact()?;
+
+    Ok(confirmed.then_some(selected))
+}
+
+/// Lets the user pick which artifact directories of a project to clean
+fn prompt_select(
+    project: &Project,
+    options: &ScanOptions,
+) -> Result<Choice, Box<dyn std::error::Error>> {
+    let sizes = project.artifact_sizes(options);
+    let width = sizes.iter().map(|(dir, _)| dir.len()).max().unwrap_or(0);
+    for (i, (dir, size)) in sizes.iter().enumerate() {
+        println!(
+            "    {} {:width$}  {}",
+            format!("{})", i + 1).cyan(),
+            dir,
+            format_size(*size).yellow()
+        );
+    }
     print!(
-        "  {} Clean {} project? [y/N/a/q]: ",
-        "?".yellow().bold(),
-        project.display_name().white().bold()
+        "  {} Directories to clean (e.g., 1 3), empty to skip: ",
+        "?".yellow().bold()
     );
     io::stdout().flush()?;
 
     let mut input = String::new();
     io::stdin().read_line(&mut input)?;
 
-    match input.trim().to_lowercase().as_str() {
-        "y" | "yes" => Ok(true),
-        "n" | "no" | "" => Ok(false),
-        "a" | "all" => {
-            // This would require refactoring to support "clean all remaining"
-            // For now, just treat as "yes"
-            Ok(true)
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    for token in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
         }
-        "q" | "quit" => {
-            println!("{}", "Exiting...".yellow());
-            process::exit(0);
-        }
-        _ => {
-            println!("  {} Invalid input, skipping...", "!".red());
-            Ok(false)
+        match token
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| sizes.get(n.wrapping_sub(1)))
+        {
+            Some((dir, size)) if !selected.contains(&dir.to_string()) => {
+                selected.push(dir.to_string());
+                total += size;
+            }
+            Some(_) => {}
+            None => {
+                println!("  {} Invalid selection {}, skipping...", "!".red(), token);
+                return Ok(Choice::Skip);
+            }
         }
     }
-}
 
-// ============================================================================
-// Utility Functions
-// ============================================================================
-
-/// Parses an age filter string (e.g., "30d", "2w", "6M") into seconds
-fn parse_age_filter(input: &str) -> Result<u64, String> {
-    const MINUTE: u64 = 60;
-    const HOUR: u64 = MINUTE * 60;
-    const DAY: u64 = HOUR * 24;
-    const WEEK: u64 = DAY * 7;
-    const MONTH: u64 = DAY * 30;
-    const YEAR: u64 = DAY * 365;
-
-    if input.is_empty() {
-        return Err("Age filter cannot be empty".to_string());
-    }
-
-    // Split into number and unit
-    let (num_str, unit) = input.split_at(input.len() - 1);
-
-    let number: u64 = num_str
-        .parse()
-        .map_err(|_| format!("Invalid number: {}", num_str))?;
-
-    let multiplier = match unit {
-        "m" => MINUTE,
-        "h" => HOUR,
-        "d" => DAY,
-        "w" => WEEK,
-        "M" => MONTH,
-        "y" => YEAR,
-        _ => return Err(format!("Invalid unit: {}. Use m, h, d, w, M, or y", unit)),
-    };
-
-    Ok(number * multiplier)
+    if selected.is_empty() {
+        Ok(Choice::Skip)
+    } else {
+        Ok(Choice::Only(selected, total))
+    }
 }
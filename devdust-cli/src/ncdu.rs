@@ -0,0 +1,137 @@
+//! ncdu export (`--format ncdu`)
+//!
+//! Writes the found artifacts in the JSON format `ncdu -f` reads, so they can be
+//! browsed and drilled into interactively. Only artifact directories and their
+//! contents are exported, below the directories that lead to them from the
+//! scanned paths.
+
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use devdust_core::{allocated_size, Project, ScanOptions};
+
+use crate::Exit;
+
+/// Directories leading to the exported artifacts
+#[derive(Default)]
+struct Dir {
+    children: BTreeMap<OsString, Dir>,
+    /// Exported with everything inside it, as found on disk
+    artifact: bool,
+}
+
+impl Dir {
+    fn insert(&mut self, relative: &Path) {
+        let dir = relative.iter().fold(self, |dir, name| {
+            dir.children.entry(name.to_owned()).or_default()
+        });
+        dir.artifact = true;
+    }
+}
+
+/// Prints the artifacts of `projects` as an ncdu export rooted where `paths` meet
+pub fn print(
+    paths: &[PathBuf],
+    projects: &[(Project, u64)],
+    options: &ScanOptions,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let roots = paths
+        .iter()
+        .map(std::path::absolute)
+        .collect::<io::Result<Vec<_>>>()?;
+    let root = common_ancestor(&roots);
+
+    let mut tree = Dir::default();
+    for (project, _) in projects {
+        for path in project.artifact_paths(options) {
+            let path = std::path::absolute(path)?;
+            if let Ok(relative) = path.strip_prefix(&root) {
+                tree.insert(relative);
+            }
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    writeln!(
+        out,
+        "[1,2,{{\"progname\":\"devdust\",\"progver\":{},\"timestamp\":{}}},",
+        quote(env!("CARGO_PKG_VERSION")),
+        timestamp
+    )?;
+    write_tree(&mut out, root.as_os_str(), &root, &tree)?;
+    writeln!(out, "]")?;
+    out.flush()?;
+
+    Ok(Exit::for_run(projects.len(), 0))
+}
+
+/// Writes a directory leading to artifacts, and the artifacts below it
+fn write_tree(out: &mut impl Write, name: &OsStr, path: &Path, dir: &Dir) -> io::Result<()> {
+    write!(out, "[{{\"name\":{}}}", quote(&name.to_string_lossy()))?;
+    for (child_name, child) in &dir.children {
+        out.write_all(b",\n")?;
+        let child_path = path.join(child_name);
+        if child.artifact {
+            write_entry(out, child_name, &child_path)?;
+        } else {
+            write_tree(out, child_name, &child_path, child)?;
+        }
+    }
+    out.write_all(b"]")
+}
+
+/// Writes a file or directory as found on disk, without following links
+fn write_entry(out: &mut impl Write, name: &OsStr, path: &Path) -> io::Result<()> {
+    let name = quote(&name.to_string_lossy());
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return write!(out, "{{\"name\":{},\"read_error\":true}}", name);
+    };
+    if !metadata.is_dir() {
+        let notreg = if metadata.is_file() {
+            ""
+        } else {
+            ",\"notreg\":true"
+        };
+        return write!(
+            out,
+            "{{\"name\":{},\"asize\":{},\"dsize\":{}{}}}",
+            name,
+            metadata.len(),
+            allocated_size(path, &metadata),
+            notreg
+        );
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        return write!(out, "[{{\"name\":{},\"read_error\":true}}]", name);
+    };
+    write!(out, "[{{\"name\":{}}}", name)?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        out.write_all(b",\n")?;
+        write_entry(out, &entry.file_name(), &entry.path())?;
+    }
+    out.write_all(b"]")
+}
+
+/// Returns the deepest directory containing all of `paths`
+fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let mut ancestor = paths.first().cloned().unwrap_or_default();
+    for path in paths.iter().skip(1) {
+        while !path.starts_with(&ancestor) && ancestor.pop() {}
+    }
+    ancestor
+}
+
+/// Quotes a string as a JSON string literal
+fn quote(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
@@ -0,0 +1,402 @@
+//! Machine-readable output formats
+//!
+//! These formats write only structured data to stdout so the results can be
+//! piped into other tools; warnings and errors still go to stderr.
+
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use devdust_core::{
+    format_timestamp, is_pinned, scan_many, Artifact, CleanReport, Project, ProjectMetadata,
+    ScanOptions, TypeGroup,
+};
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use serde::Serialize;
+
+use crate::{CleanMode, Exit};
+
+// ============================================================================
+// JSON Records
+// ============================================================================
+
+/// A project as it appears in machine-readable output
+#[derive(Serialize)]
+struct ProjectRecord<'a> {
+    name: String,
+    #[serde(flatten)]
+    project: &'a Project,
+    type_name: Cow<'a, str>,
+    /// Name and version from the project's manifest
+    #[serde(skip_serializing_if = "ProjectMetadata::is_empty")]
+    metadata: ProjectMetadata,
+    /// Artifact directories that exist, relative to the project root
+    artifact_directories: Vec<String>,
+    /// What a clean removes, with the size of each part
+    artifacts: Vec<Artifact>,
+    /// Artifact directories older than their lockfile
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stale_artifacts: Vec<String>,
+    /// Artifact directories holding a `.devdustkeep` file, never cleaned
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    kept_artifacts: Vec<String>,
+    artifact_size: u64,
+    /// Sum of file lengths, reported next to the on-disk `artifact_size` with `--disk-usage`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    apparent_size: Option<u64>,
+    /// `artifact_size` and the artifact sizes were estimated with `--estimate`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    size_estimated: bool,
+    /// Sizes were not calculated with `--no-size`, so `artifact_size` and the artifact sizes are 0
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    size_skipped: bool,
+    /// Last modification time as seconds since the Unix epoch
+    last_modified: Option<u64>,
+    clean: CleanOutcome,
+}
+
+/// What happened when the project was (or would have been) cleaned
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum CleanOutcome {
+    /// Only reported, nothing was deleted
+    Skipped,
+    /// Dry run: this many bytes would be freed
+    WouldClean { bytes: u64 },
+    /// Includes what was removed per artifact directory
+    Cleaned {
+        #[serde(flatten)]
+        report: CleanReport,
+    },
+    Failed {
+        #[serde(flatten)]
+        report: CleanReport,
+        errors: Vec<String>,
+    },
+}
+
+impl CleanOutcome {
+    /// Returns the status name used in JSON output
+    fn status(&self) -> &'static str {
+        match self {
+            Self::Skipped => "skipped",
+            Self::WouldClean { .. } => "would_clean",
+            Self::Cleaned { .. } => "cleaned",
+            Self::Failed { .. } => "failed",
+        }
+    }
+
+    /// Returns the bytes freed, or that would be freed on a dry run
+    fn bytes(&self) -> u64 {
+        match self {
+            Self::Skipped => 0,
+            Self::WouldClean { bytes } => *bytes,
+            Self::Cleaned { report } | Self::Failed { report, .. } => report.bytes_freed,
+        }
+    }
+}
+
+/// Totals for the whole run
+#[derive(Serialize)]
+struct Summary {
+    projects_found: usize,
+    total_artifact_size: u64,
+    projects_cleaned: usize,
+    projects_failed: usize,
+    bytes_freed: u64,
+    dry_run: bool,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    projects: Vec<ProjectRecord<'a>>,
+    summary: Summary,
+    /// Subtotals per project type with `--group-by type`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<TypeGroup>,
+}
+
+impl<'a> ProjectRecord<'a> {
+    fn new(project: &'a Project, artifact_size: u64, options: &ScanOptions) -> Self {
+        let last_modified = project
+            .last_modified(options)
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        Self {
+            name: project.display_name(),
+            project,
+            type_name: project.type_name(),
+            metadata: project.metadata(),
+            artifact_directories: project
+                .artifact_directories()
+                .into_iter()
+                .filter(|dir| project.path.join(dir).exists())
+                .collect(),
+            artifacts: project.artifacts(options),
+            stale_artifacts: project.stale_artifacts(),
+            kept_artifacts: project.kept_artifacts(),
+            artifact_size,
+            apparent_size: options
+                .disk_usage
+                .then(|| project.artifact_disk_usage(options).apparent),
+            size_estimated: options.estimate_sizes,
+            size_skipped: options.skip_sizes,
+            last_modified,
+            clean: CleanOutcome::Skipped,
+        }
+    }
+}
+
+impl Summary {
+    fn new(dry_run: bool) -> Self {
+        Self {
+            projects_found: 0,
+            total_artifact_size: 0,
+            projects_cleaned: 0,
+            projects_failed: 0,
+            bytes_freed: 0,
+            dry_run,
+        }
+    }
+
+    /// Adds a project and its clean outcome to the totals
+    fn record(&mut self, artifact_size: u64, outcome: &CleanOutcome) {
+        self.projects_found += 1;
+        self.total_artifact_size += artifact_size;
+
+        match outcome {
+            CleanOutcome::Cleaned { report } => {
+                self.projects_cleaned += 1;
+                self.bytes_freed += report.bytes_freed;
+            }
+            CleanOutcome::Failed { report, .. } => {
+                self.projects_failed += 1;
+                self.bytes_freed += report.bytes_freed;
+            }
+            CleanOutcome::Skipped | CleanOutcome::WouldClean { .. } => {}
+        }
+    }
+
+    /// Returns the exit code for the run
+    fn exit(&self) -> Exit {
+        Exit::for_run(self.projects_found, self.projects_failed)
+    }
+}
+
+/// A line of NDJSON output
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum StreamRecord<'a> {
+    Project(Box<ProjectRecord<'a>>),
+    Summary(Summary),
+}
+
+// ============================================================================
+// Output Functions
+// ============================================================================
+
+/// Prints all projects as a single JSON document, optionally cleaning them first
+pub fn print_json(
+    projects: &[(Project, u64)],
+    groups: Vec<TypeGroup>,
+    options: &ScanOptions,
+    clean: bool,
+    mode: &CleanMode,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let mut records = Vec::with_capacity(projects.len());
+    let mut summary = Summary::new(mode.dry_run);
+
+    for (project, artifact_size) in projects {
+        let mut record = ProjectRecord::new(project, *artifact_size, options);
+        record.clean = clean_outcome(project, *artifact_size, clean, mode);
+        summary.record(*artifact_size, &record.clean);
+        records.push(record);
+    }
+
+    let exit = summary.exit();
+    let report = Report {
+        projects: records,
+        summary,
+        groups,
+    };
+
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &report)?;
+    writeln!(stdout)?;
+    Ok(exit)
+}
+
+/// Header row of the CSV output
+const CSV_HEADER: &[&str] = &[
+    "path",
+    "name",
+    "type",
+    "artifact_size",
+    "last_modified",
+    "artifact_directories",
+    "status",
+    "bytes",
+];
+
+/// Prints one CSV row per project, optionally cleaning them first
+///
+/// Sizes are in bytes, times are RFC 3339 timestamps in UTC and artifact
+/// directories are separated by `;`, so the file imports cleanly into spreadsheets.
+pub fn print_csv(
+    projects: &[(Project, u64)],
+    options: &ScanOptions,
+    clean: bool,
+    mode: &CleanMode,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let mut summary = Summary::new(mode.dry_run);
+    let mut stdout = io::stdout().lock();
+    write_csv_row(&mut stdout, CSV_HEADER)?;
+
+    for (project, artifact_size) in projects {
+        let mut record = ProjectRecord::new(project, *artifact_size, options);
+        record.clean = clean_outcome(project, *artifact_size, clean, mode);
+        summary.record(*artifact_size, &record.clean);
+
+        let last_modified = record
+            .last_modified
+            .map(|secs| format_timestamp(UNIX_EPOCH + Duration::from_secs(secs)))
+            .unwrap_or_default();
+        write_csv_row(
+            &mut stdout,
+            &[
+                &project.path.display().to_string(),
+                &record.name,
+                &record.type_name,
+                &artifact_size.to_string(),
+                &last_modified,
+                &record.artifact_directories.join(";"),
+                record.clean.status(),
+                &record.clean.bytes().to_string(),
+            ],
+        )?;
+    }
+
+    Ok(summary.exit())
+}
+
+/// Writes a CSV row, quoting fields that contain separators, quotes or line breaks
+fn write_csv_row(out: &mut impl Write, fields: &[&str]) -> io::Result<()> {
+    let row: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    writeln!(out, "{}", row.join(","))
+}
+
+/// Prints the paths cleaning would remove, each terminated by a NUL byte
+///
+/// Nothing is cleaned; protected projects are left out so the list can be
+/// passed straight to `xargs -0 rm -rf`.
+pub fn print0(
+    projects: &[(Project, u64)],
+    options: &ScanOptions,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+
+    for (project, _) in projects.iter().filter(|(project, _)| !project.protected) {
+        for path in project.artifact_paths(options) {
+            stdout.write_all(path.as_os_str().as_encoded_bytes())?;
+            stdout.write_all(b"\0")?;
+        }
+    }
+
+    stdout.flush()?;
+    Ok(Exit::for_run(projects.len(), 0))
+}
+
+/// Scans the given roots and prints one JSON line per project as soon as it is found
+///
+/// Projects appear in discovery order; a final `summary` record closes the stream.
+pub fn stream_ndjson(
+    paths: &[PathBuf],
+    options: &ScanOptions,
+    pinned: &BTreeSet<PathBuf>,
+    clean: bool,
+    mode: &CleanMode,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let summary = Mutex::new(Summary::new(mode.dry_run));
+
+    scan_many(paths, options)
+        .par_bridge()
+        .try_for_each(|result| -> io::Result<()> {
+            let (project, artifact_size) = match result {
+                Ok((project, _)) if is_pinned(pinned, &project.path) => return Ok(()),
+                Ok(found) => found,
+                Err(e) => {
+                    eprintln!("Warning: {}", e);
+                    return Ok(());
+                }
+            };
+
+            let mut record = ProjectRecord::new(&project, artifact_size, options);
+            record.clean = clean_outcome(&project, artifact_size, clean, mode);
+            summary
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .record(artifact_size, &record.clean);
+
+            write_line(&StreamRecord::Project(Box::new(record)))
+        })?;
+
+    let summary = summary.into_inner().unwrap_or_else(PoisonError::into_inner);
+    let exit = summary.exit();
+    write_line(&StreamRecord::Summary(summary))?;
+    Ok(exit)
+}
+
+/// Writes a record as a single line and flushes so consumers see it immediately
+fn write_line(record: &StreamRecord) -> io::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&line)?;
+    stdout.flush()
+}
+
+/// Cleans the project if requested and describes the result
+fn clean_outcome(
+    project: &Project,
+    artifact_size: u64,
+    clean: bool,
+    mode: &CleanMode,
+) -> CleanOutcome {
+    if mode.dry_run {
+        return CleanOutcome::WouldClean {
+            bytes: artifact_size,
+        };
+    }
+    if !clean || project.protected {
+        return CleanOutcome::Skipped;
+    }
+
+    match crate::clean_project(project, mode) {
+        Ok(report) if report.is_complete() => CleanOutcome::Cleaned { report },
+        Ok(report) => CleanOutcome::Failed {
+            errors: report.failures.iter().map(ToString::to_string).collect(),
+            report,
+        },
+        Err(e) => CleanOutcome::Failed {
+            report: CleanReport::default(),
+            errors: vec![e.to_string()],
+        },
+    }
+}
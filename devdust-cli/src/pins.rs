@@ -0,0 +1,57 @@
+//! `devdust pins` subcommand
+//!
+//! Projects pinned with `p` at the clean prompt are skipped by later runs;
+//! `pins list` shows them and `pins remove` makes them eligible again.
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use colored::*;
+use devdust_core::Pins;
+
+/// Arguments for the `pins` subcommand
+#[derive(Args, Debug)]
+pub struct PinsArgs {
+    #[command(subcommand)]
+    command: PinsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum PinsCommand {
+    /// Show the pinned projects
+    List,
+    /// Unpin projects so they are offered for cleaning again
+    Remove {
+        /// Project directories to unpin
+        #[arg(value_name = "PATH", required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+/// Runs the `pins` subcommand
+pub fn run(args: PinsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let pins = Pins::open_default()?;
+
+    match args.command {
+        PinsCommand::List => {
+            let list = pins.list()?;
+            if list.is_empty() {
+                println!("{}", "No pinned projects.".yellow());
+            }
+            for path in list {
+                println!("{}", path.display());
+            }
+        }
+        PinsCommand::Remove { paths } => {
+            for path in paths {
+                if pins.remove(&path)? {
+                    println!("{} Unpinned {}", "✓".green().bold(), path.display());
+                } else {
+                    eprintln!("{} Not pinned: {}", "!".yellow(), path.display());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
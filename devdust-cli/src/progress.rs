@@ -0,0 +1,120 @@
+//! Live progress display while scanning
+//!
+//! Shows a spinner on stderr with the number of directories scanned, projects
+//! found and their cumulative artifact size, fed by the core progress hook.
+//! With `--verbose`, skipped directories and projects are listed above it.
+
+use std::{
+    fmt::Display,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use colored::*;
+use devdust_core::{format_size, ProgressCallback, ProgressEvent, ScanObserver, SkipReason};
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Counters shared between the scan workers and the spinner
+#[derive(Default)]
+struct Counters {
+    directories: AtomicU64,
+    projects: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// Spinner that tracks a running scan
+pub struct ScanProgress {
+    bar: ProgressBar,
+    counters: Arc<Counters>,
+}
+
+impl ScanProgress {
+    /// Starts the spinner; it stays hidden when stderr is not a terminal
+    pub fn start() -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner:.cyan} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        bar.enable_steady_tick(Duration::from_millis(100));
+
+        Self {
+            bar,
+            counters: Arc::default(),
+        }
+    }
+
+    /// Returns the callback to install in `ScanOptions::progress`
+    pub fn callback(&self) -> ProgressCallback {
+        let bar = self.bar.clone();
+        let counters = Arc::clone(&self.counters);
+
+        ProgressCallback::new(move |event| {
+            match event {
+                ProgressEvent::DirectoryScanned(_) => {
+                    counters.directories.fetch_add(1, Ordering::Relaxed);
+                }
+                ProgressEvent::ProjectFound(_) => {
+                    counters.projects.fetch_add(1, Ordering::Relaxed);
+                }
+                ProgressEvent::ArtifactsSized(_, size) => {
+                    counters.bytes.fetch_add(size, Ordering::Relaxed);
+                }
+            }
+
+            bar.set_message(format!(
+                "Scanned {} directories, found {} projects with {} of artifacts",
+                counters.directories.load(Ordering::Relaxed),
+                counters.projects.load(Ordering::Relaxed),
+                format_size(counters.bytes.load(Ordering::Relaxed))
+            ));
+        })
+    }
+
+    /// Hides the spinner while `f` writes to the terminal
+    pub fn suspend<F: FnOnce()>(&self, f: F) {
+        self.bar.suspend(f)
+    }
+
+    /// Removes the spinner from the terminal
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Prints each skipped directory or project with the reason (`--verbose`)
+pub struct SkipReporter {
+    bar: Option<ProgressBar>,
+}
+
+impl SkipReporter {
+    /// Creates a reporter that writes above `progress`'s spinner, if there is one
+    pub fn new(progress: Option<&ScanProgress>) -> Self {
+        Self {
+            bar: progress.map(|progress| progress.bar.clone()),
+        }
+    }
+
+    /// Prints one skipped path on stderr, e.g. "Skipped: ~/code/app (too new)"
+    pub fn print(path: &Path, reason: impl Display) {
+        eprintln!(
+            "{} {} {}",
+            "Skipped:".bright_black(),
+            path.display(),
+            format!("({})", reason).bright_black()
+        );
+    }
+}
+
+impl ScanObserver for SkipReporter {
+    fn skipped(&self, path: &Path, reason: SkipReason) {
+        match &self.bar {
+            Some(bar) => bar.suspend(|| Self::print(path, reason)),
+            None => Self::print(path, reason),
+        }
+    }
+}
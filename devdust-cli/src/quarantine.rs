@@ -0,0 +1,130 @@
+//! `devdust undo` and `devdust purge` subcommands
+//!
+//! Cleans run with `--quarantine` move artifacts into a quarantine folder;
+//! `undo` puts the last batch back and `purge` deletes old batches for good.
+
+use std::io::{self, Write};
+
+use clap::Args;
+use colored::*;
+use devdust_core::{format_size, parse_age, CleanError, Quarantine};
+
+/// Arguments for the `purge` subcommand
+#[derive(Args, Debug)]
+pub struct PurgeArgs {
+    /// Only purge batches quarantined at least this long ago (e.g., 30d)
+    #[arg(short, long, value_name = "TIME")]
+    older: Option<String>,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Show what would be purged without deleting anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+/// Runs the `undo` subcommand
+pub fn undo() -> Result<(), Box<dyn std::error::Error>> {
+    let quarantine = Quarantine::open_default()?;
+
+    let Some(report) = quarantine.restore_last()? else {
+        println!("{}", "Nothing to undo: the quarantine is empty.".yellow());
+        return Ok(());
+    };
+
+    for path in &report.restored {
+        println!("{} {}", "✓".green().bold(), path.display());
+    }
+    for (path, e) in &report.errors {
+        eprintln!("{} {}: {}", "✗".red().bold(), path.display(), e);
+    }
+
+    println!("\n{}", "═".repeat(50).cyan());
+    println!(
+        "{} {} directories, {}",
+        "Restored:".green().bold(),
+        report.restored.len().to_string().white().bold(),
+        format_size(report.bytes).white().bold()
+    );
+    if !report.errors.is_empty() {
+        return Err(format!(
+            "{} directories could not be restored and remain in {}",
+            report.errors.len(),
+            quarantine.root().display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Runs the `purge` subcommand
+pub fn purge(args: PurgeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let min_age_seconds = match args.older.as_deref() {
+        Some(age_str) => parse_age(age_str)?,
+        None => 0,
+    };
+
+    let quarantine = Quarantine::open_default()?;
+    let batches: Vec<_> = quarantine
+        .batches()?
+        .into_iter()
+        .filter(|b| b.created.elapsed().map_or(0, |d| d.as_secs()) >= min_age_seconds)
+        .collect();
+
+    if batches.is_empty() {
+        println!("{}", "Nothing to purge.".yellow());
+        return Ok(());
+    }
+
+    let size: u64 = batches.iter().map(|b| b.size()).sum();
+    println!(
+        "{} {} batches with {} in {}",
+        "Quarantine:".cyan().bold(),
+        batches.len(),
+        format_size(size).yellow().bold(),
+        quarantine.root().display()
+    );
+
+    if args.dry_run {
+        println!("{} Would free {}", "→".blue(), format_size(size));
+        return Ok(());
+    }
+    if !args.yes && !confirm(size)? {
+        return Ok(());
+    }
+
+    match quarantine.purge(min_age_seconds) {
+        Ok(freed) => println!(
+            "{} Freed {}",
+            "✓".green().bold(),
+            format_size(freed).green()
+        ),
+        Err(CleanError::PartialFailure { deleted, errors }) => {
+            for e in &errors {
+                eprintln!("{} {}", "✗".red().bold(), e);
+            }
+            println!("{} Freed {}", "✓".green().bold(), format_size(deleted));
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
+
+/// Asks the user whether to purge
+fn confirm(size: u64) -> io::Result<bool> {
+    print!(
+        "{} Permanently delete {}? [y/N]: ",
+        "?".yellow().bold(),
+        format_size(size)
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,482 @@
+//! `devdust schedule` subcommand
+//!
+//! Registers a recurring non-interactive run with the platform scheduler: a
+//! systemd user timer on Linux, a launchd agent on macOS and a Task Scheduler
+//! entry on Windows. Scheduled runs use `--all --quiet`; what gets cleaned is
+//! decided by the configuration file (the stored policy) plus any extra flags
+//! given after `--`.
+//!
+//! Schedulers start runs in the home directory, `/` or `System32`, so `install`
+//! refuses to register a run without directories to scan. They come from the
+//! arguments after `--` or the policy's `paths` and are written into the
+//! command as absolute paths.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use clap::{ArgGroup, Args, CommandFactory, Subcommand};
+use colored::*;
+use devdust_core::Config;
+
+/// Name used for the systemd units and the Windows task
+const TASK_NAME: &str = "devdust";
+
+/// Label of the launchd agent
+const LAUNCHD_LABEL: &str = "com.extrise.devdust";
+
+/// Arguments for the `schedule` subcommand
+#[derive(Args, Debug)]
+pub struct ScheduleArgs {
+    #[command(subcommand)]
+    command: ScheduleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ScheduleCommand {
+    /// Register a recurring clean with the system scheduler
+    Install(InstallArgs),
+    /// Remove the scheduled clean
+    Uninstall,
+}
+
+/// Arguments for `schedule install`
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("frequency").required(true).args(["daily", "weekly", "monthly"])))]
+struct InstallArgs {
+    /// Run every day
+    #[arg(long)]
+    daily: bool,
+
+    /// Run every week
+    #[arg(long)]
+    weekly: bool,
+
+    /// Run on the first day of every month
+    #[arg(long)]
+    monthly: bool,
+
+    /// Policy file for scheduled runs (defaults to the global configuration)
+    #[arg(short = 'c', long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Print the generated files and commands without installing anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Extra flags for each run (e.g., -- ~/projects --older 30d --quarantine)
+    #[arg(last = true, value_name = "ARGS")]
+    args: Vec<String>,
+}
+
+/// How often the scheduled clean runs
+#[derive(Debug, Clone, Copy)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A file to write and the commands that register it
+struct Plan {
+    files: Vec<(PathBuf, String)>,
+    commands: Vec<Vec<String>>,
+}
+
+/// Runs the `schedule` subcommand
+pub fn run(args: ScheduleArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        ScheduleCommand::Install(install_args) => install(install_args),
+        ScheduleCommand::Uninstall => uninstall(),
+    }
+}
+
+fn install(args: InstallArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let frequency = if args.daily {
+        Frequency::Daily
+    } else if args.monthly {
+        Frequency::Monthly
+    } else {
+        Frequency::Weekly
+    };
+
+    // The command line every scheduled run executes
+    let mut command = vec![
+        env::current_exe()?.display().to_string(),
+        "--all".to_string(),
+        "--quiet".to_string(),
+    ];
+    let config = match &args.config {
+        Some(path) => {
+            command.push("--config".to_string());
+            command.push(std::path::absolute(path)?.display().to_string());
+            Config::from_file(path)?
+        }
+        None => match Config::global_path().filter(|path| path.is_file()) {
+            Some(path) => Config::from_file(&path)?,
+            None => Config::default(),
+        },
+    };
+    command.extend(with_roots(args.args, &config)?);
+
+    let plan = if cfg!(target_os = "linux") {
+        systemd_plan(&command, frequency)?
+    } else if cfg!(target_os = "macos") {
+        launchd_plan(&command, frequency)?
+    } else if cfg!(windows) {
+        schtasks_plan(&command, frequency)
+    } else {
+        return Err("Scheduling is not supported on this platform".into());
+    };
+
+    execute(&plan, args.dry_run)?;
+
+    if !args.dry_run {
+        println!(
+            "{} devdust will run {} as: {}",
+            "✓".green().bold(),
+            frequency.describe(),
+            command.join(" ").white()
+        );
+    }
+    Ok(())
+}
+
+fn uninstall() -> Result<(), Box<dyn std::error::Error>> {
+    if cfg!(target_os = "linux") {
+        let dir = systemd_dir()?;
+        let timer = format!("{}.timer", TASK_NAME);
+        run_command(&["systemctl", "--user", "disable", "--now", &timer])?;
+        remove_if_exists(&dir.join(&timer))?;
+        remove_if_exists(&dir.join(format!("{}.service", TASK_NAME)))?;
+        run_command(&["systemctl", "--user", "daemon-reload"])?;
+    } else if cfg!(target_os = "macos") {
+        let plist = launchd_path()?;
+        run_command(&["launchctl", "unload", "-w", &plist.display().to_string()])?;
+        remove_if_exists(&plist)?;
+    } else if cfg!(windows) {
+        run_command(&["schtasks", "/Delete", "/TN", TASK_NAME, "/F"])?;
+    } else {
+        return Err("Scheduling is not supported on this platform".into());
+    }
+
+    println!("{} Scheduled clean removed", "✓".green().bold());
+    Ok(())
+}
+
+impl Frequency {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+}
+
+// ============================================================================
+// Platform Backends
+// ============================================================================
+
+/// systemd user service plus timer
+fn systemd_plan(
+    command: &[String],
+    frequency: Frequency,
+) -> Result<Plan, Box<dyn std::error::Error>> {
+    let dir = systemd_dir()?;
+    let exec = command
+        .iter()
+        .map(|arg| systemd_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let service = format!(
+        "[Unit]\n\
+         Description=Clean development build artifacts\n\n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart={}\n",
+        exec
+    );
+    let timer = format!(
+        "[Unit]\n\
+         Description=Run devdust {}\n\n\
+         [Timer]\n\
+         OnCalendar={}\n\
+         Persistent=true\n\n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        frequency.describe(),
+        frequency.describe()
+    );
+
+    let timer_name = format!("{}.timer", TASK_NAME);
+    Ok(Plan {
+        files: vec![
+            (dir.join(format!("{}.service", TASK_NAME)), service),
+            (dir.join(&timer_name), timer),
+        ],
+        commands: vec![
+            strings(&["systemctl", "--user", "daemon-reload"]),
+            strings(&["systemctl", "--user", "enable", "--now", &timer_name]),
+        ],
+    })
+}
+
+/// launchd agent running at 03:00
+fn launchd_plan(
+    command: &[String],
+    frequency: Frequency,
+) -> Result<Plan, Box<dyn std::error::Error>> {
+    let path = launchd_path()?;
+    let arguments: String = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+        .collect();
+    let interval = match frequency {
+        Frequency::Daily => "",
+        Frequency::Weekly => "        <key>Weekday</key>\n        <integer>0</integer>\n",
+        Frequency::Monthly => "        <key>Day</key>\n        <integer>1</integer>\n",
+    };
+
+    let plist = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n    \
+             <key>Label</key>\n    \
+             <string>{}</string>\n    \
+             <key>ProgramArguments</key>\n    \
+             <array>\n{}    </array>\n    \
+             <key>StartCalendarInterval</key>\n    \
+             <dict>\n{}        <key>Hour</key>\n        <integer>3</integer>\n    </dict>\n\
+         </dict>\n\
+         </plist>\n",
+        LAUNCHD_LABEL, arguments, interval
+    );
+
+    let path_str = path.display().to_string();
+    Ok(Plan {
+        files: vec![(path, plist)],
+        commands: vec![strings(&["launchctl", "load", "-w", &path_str])],
+    })
+}
+
+/// Task Scheduler entry running at 03:00
+fn schtasks_plan(command: &[String], frequency: Frequency) -> Plan {
+    let task = command
+        .iter()
+        .map(|arg| format!("\"{}\"", arg.replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let schedule = match frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+    };
+
+    Plan {
+        files: Vec::new(),
+        commands: vec![strings(&[
+            "schtasks", "/Create", "/TN", TASK_NAME, "/TR", &task, "/SC", schedule, "/ST", "03:00",
+            "/F",
+        ])],
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Makes the directories in `extra` absolute, or adds the policy's `paths` if it names none
+///
+/// Fails when neither gives a directory, since the run would then clean
+/// whatever directory the scheduler starts it in.
+fn with_roots(
+    mut extra: Vec<String>,
+    config: &Config,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let paths = scan_paths(&extra)?;
+    if paths.is_empty() {
+        if config.paths.is_empty() {
+            return Err(
+                "Scheduled runs need directories to scan: list them after `--` \
+                        (e.g., -- ~/projects) or in `paths` of the configuration file"
+                    .into(),
+            );
+        }
+        let roots = config
+            .paths
+            .iter()
+            .map(|path| Ok(std::path::absolute(path)?.display().to_string()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        extra.splice(0..0, roots);
+        return Ok(extra);
+    }
+
+    // Rewrite the directory arguments in place; clap reports their values, not positions
+    let mut pending = paths.clone();
+    for arg in &mut extra {
+        let Some(index) = pending
+            .iter()
+            .position(|path| path.as_os_str() == arg.as_str())
+        else {
+            continue;
+        };
+        let path = pending.remove(index);
+        if path.as_os_str() == "-" {
+            return Err("Scheduled runs cannot read directories from stdin".into());
+        }
+        *arg = std::path::absolute(&path)?.display().to_string();
+    }
+
+    let absolute = paths
+        .iter()
+        .map(std::path::absolute)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if scan_paths(&extra)? != absolute {
+        return Err(
+            "Cannot tell the directories to scan apart from flag values; \
+                    give them as absolute paths"
+                .into(),
+        );
+    }
+    Ok(extra)
+}
+
+/// The directories a run with `extra` arguments would scan, as given
+fn scan_paths(extra: &[String]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let argv = std::iter::once(TASK_NAME).chain(extra.iter().map(String::as_str));
+    let matches = crate::Args::command().try_get_matches_from(argv)?;
+    Ok(matches
+        .get_many::<PathBuf>("paths")
+        .map(|paths| paths.cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Writes the plan's files and runs its commands, or just prints them
+fn execute(plan: &Plan, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    for (path, contents) in &plan.files {
+        if dry_run {
+            println!(
+                "{} {}\n{}",
+                "Would write:".cyan().bold(),
+                path.display(),
+                contents
+            );
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        println!("{} {}", "Wrote:".cyan().bold(), path.display());
+    }
+
+    for command in &plan.commands {
+        if dry_run {
+            println!("{} {}", "Would run:".cyan().bold(), command.join(" "));
+            continue;
+        }
+        let args: Vec<&str> = command.iter().map(String::as_str).collect();
+        run_command(&args)?;
+    }
+
+    Ok(())
+}
+
+/// Runs an external command, failing if it exits unsuccessfully
+fn run_command(args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new(args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(|e| format!("Cannot run {}: {}", args[0], e))?;
+
+    if !status.success() {
+        return Err(format!("`{}` failed ({})", args.join(" "), status).into());
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn systemd_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .ok_or("Cannot determine the home directory")?;
+    Ok(config.join("systemd").join("user"))
+}
+
+fn launchd_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home = dirs::home_dir().ok_or("Cannot determine the home directory")?;
+    Ok(home
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{}.plist", LAUNCHD_LABEL)))
+}
+
+/// Quotes an argument for a systemd `ExecStart=` line
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        strings(args)
+    }
+
+    #[test]
+    fn test_install_without_paths_fails() {
+        let config = Config::default();
+        assert!(with_roots(Vec::new(), &config).is_err());
+        assert!(with_roots(args(&["--older", "30d", "--quarantine"]), &config).is_err());
+        assert!(with_roots(args(&["-"]), &config).is_err());
+    }
+
+    #[test]
+    fn test_install_roots_are_absolute() {
+        let cwd = env::current_dir().unwrap();
+        let projects = cwd.join("projects").display().to_string();
+
+        let command = with_roots(args(&["--older", "30d", "projects"]), &Config::default());
+        assert_eq!(command.unwrap(), args(&["--older", "30d", &projects]));
+
+        let config = Config {
+            paths: vec![PathBuf::from("projects")],
+            ..Config::default()
+        };
+        let command = with_roots(args(&["--older", "30d"]), &config);
+        assert_eq!(command.unwrap(), args(&[&projects, "--older", "30d"]));
+    }
+}
@@ -0,0 +1,49 @@
+//! `devdust stats` subcommand
+//!
+//! Summarizes the clean history: total space reclaimed, and how it splits up
+//! per project type and per month.
+
+use colored::*;
+use devdust_core::{format_elapsed_time, format_size, History, StatsRow};
+
+/// Runs the `stats` subcommand
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let history = History::open_default()?;
+    let stats = history.stats()?;
+
+    let Some(since) = stats.since else {
+        println!("{}", "No cleans recorded yet.".yellow());
+        return Ok(());
+    };
+
+    let age = since.elapsed().map_or(0, |d| d.as_secs());
+    println!(
+        "{} {} in {} cleans, first {}",
+        "Reclaimed:".green().bold(),
+        format_size(stats.bytes).white().bold(),
+        stats.cleans.to_string().white().bold(),
+        format_elapsed_time(age).bright_black()
+    );
+
+    println!("\n{}", "By project type:".cyan().bold());
+    print_rows(&stats.by_type);
+
+    println!("\n{}", "By month:".cyan().bold());
+    print_rows(&stats.by_month);
+
+    Ok(())
+}
+
+/// Prints one aligned line per row
+fn print_rows(rows: &[StatsRow]) {
+    let width = rows.iter().map(|r| r.label.len()).max().unwrap_or(0);
+    for row in rows {
+        println!(
+            "  {:<width$}  {:>10}  {}",
+            row.label,
+            format_size(row.bytes).yellow(),
+            format!("({} cleans)", row.cleans).bright_black(),
+            width = width
+        );
+    }
+}
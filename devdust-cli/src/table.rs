@@ -0,0 +1,74 @@
+//! Compact table listing (`--format table`)
+//!
+//! One aligned row per project instead of the multi-line card, which reads
+//! better on wide terminals and with many results.
+
+use colored::*;
+use devdust_core::{format_elapsed_time, Project, ScanOptions};
+use rayon::prelude::*;
+
+use crate::format_measured;
+
+const HEADERS: [&str; 5] = ["NAME", "TYPE", "SIZE", "AGE", "PATH"];
+
+/// Prints every project as a table row, in the given order
+pub fn print(projects: &[(Project, u64)], options: &ScanOptions) {
+    // Ages walk each project, so they are worked out in parallel
+    let rows: Vec<[String; 5]> = projects
+        .par_iter()
+        .map(|(project, artifact_size)| row(project, *artifact_size, options))
+        .collect();
+
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let [name, kind, size, age, _] = widths;
+
+    let [n, k, s, a, p] = HEADERS;
+    let header = format!("{n:name$}  {k:kind$}  {s:>size$}  {a:age$}  {p}");
+    println!("{}", header.bold());
+    for [n, k, s, a, p] in &rows {
+        println!(
+            "{}  {}  {}  {}  {}",
+            format!("{:name$}", n).white().bold(),
+            format!("{:kind$}", k).bright_black(),
+            format!("{:>size$}", s).yellow(),
+            format!("{:age$}", a).bright_black(),
+            p
+        );
+    }
+    println!();
+}
+
+fn row(project: &Project, artifact_size: u64, options: &ScanOptions) -> [String; 5] {
+    let mut name = project.display_name();
+    if project.protected {
+        name.push_str(" (protected)");
+    }
+    let size = if options.skip_sizes {
+        "-".to_string()
+    } else {
+        format_measured(artifact_size, options)
+    };
+    let age = project
+        .last_modified(options)
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map_or_else(
+            || "-".to_string(),
+            |elapsed| {
+                let age = format_elapsed_time(elapsed.as_secs());
+                age.trim_end_matches(" ago").to_string()
+            },
+        );
+    [
+        name,
+        project.type_name().into_owned(),
+        size,
+        age,
+        project.path.display().to_string(),
+    ]
+}
@@ -0,0 +1,56 @@
+//! `--timings` report
+//!
+//! Printed to stderr, so it can be combined with machine-readable output.
+
+use std::{path::PathBuf, time::Duration};
+
+use colored::*;
+use devdust_core::ScanTimings;
+
+/// Prints how long each root took to walk and size, and the slowest projects to size
+pub fn print_scan(timings: &ScanTimings) {
+    eprintln!("\n{}", "Scan timings:".cyan().bold());
+    for root in timings.roots() {
+        eprintln!("  {}", root.path.display().to_string().white().bold());
+        eprintln!(
+            "    detection {}, sizing {}",
+            format_duration(root.detection),
+            format_duration(root.sizing)
+        );
+        eprintln!(
+            "    {} dirs visited, {} files stat'ed",
+            root.dirs_visited, root.files_stated
+        );
+    }
+
+    let projects = timings.projects();
+    if projects.is_empty() {
+        return;
+    }
+    eprintln!("  {}", "Sizing, slowest first:".bright_black());
+    for project in projects {
+        eprintln!(
+            "    {:>8}  {}  {}",
+            format_duration(project.sizing),
+            project.path.display(),
+            format!("({} files)", project.files_stated).bright_black()
+        );
+    }
+}
+
+/// Prints how long each project took to clean, slowest first
+pub fn print_deletions(mut deletions: Vec<(PathBuf, Duration)>) {
+    if deletions.is_empty() {
+        return;
+    }
+    deletions.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+    eprintln!("\n{}", "Deletion timings, slowest first:".cyan().bold());
+    for (path, elapsed) in deletions {
+        eprintln!("    {:>8}  {}", format_duration(elapsed), path.display());
+    }
+}
+
+/// Formats a duration with millisecond precision, e.g. "1.250s"
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
@@ -0,0 +1,512 @@
+//! Full-screen interactive mode
+//!
+//! Lists every discovered project in a selectable table. Projects are toggled
+//! with the spacebar, the list can be sorted (and reversed) and filtered by type, and the
+//! selection is cleaned in one go with a progress view.
+
+use std::{io, time::SystemTime};
+
+use devdust_core::{
+    format_elapsed_time, format_size, CleanReport, Project, ProjectType, ScanOptions, SortFields,
+    SortKey,
+};
+
+use crate::{CleanMethod, CleanMode};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Gauge, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+// ============================================================================
+// Application State
+// ============================================================================
+
+/// Totals reported back to the caller once the UI exits
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub projects_cleaned: usize,
+    pub total_cleaned: u64,
+    pub projects_failed: usize,
+}
+
+/// Outcome of cleaning a single project
+enum Status {
+    Pending,
+    Cleaned(u64),
+    Failed(String),
+}
+
+/// A project row in the list
+struct Entry {
+    project: Project,
+    size: u64,
+    modified: Option<SystemTime>,
+    selected: bool,
+    status: Status,
+}
+
+impl Entry {
+    fn sort_fields(&self) -> SortFields<'_> {
+        SortFields {
+            project: &self.project,
+            artifact_size: self.size,
+            modified: self.modified,
+        }
+    }
+}
+
+/// Which screen is currently shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Browse,
+    Confirm,
+    Cleaning,
+    Done,
+}
+
+struct App {
+    entries: Vec<Entry>,
+    /// Indices into `entries` after filtering and sorting
+    visible: Vec<usize>,
+    list_state: ListState,
+    sort: SortKey,
+    reverse: bool,
+    filter: Option<ProjectType>,
+    screen: Screen,
+    /// Bytes processed so far while cleaning
+    progress: u64,
+    mode: CleanMode,
+    /// Shown in the footer instead of the keys until the next key press
+    notice: Option<String>,
+}
+
+impl App {
+    fn new(
+        projects: Vec<(Project, u64)>,
+        options: &ScanOptions,
+        mode: CleanMode,
+        sort: SortKey,
+        reverse: bool,
+    ) -> Self {
+        let entries = projects
+            .into_iter()
+            .map(|(project, size)| Entry {
+                modified: project.last_modified(options).ok(),
+                project,
+                size,
+                selected: false,
+                status: Status::Pending,
+            })
+            .collect();
+
+        let mut app = Self {
+            entries,
+            visible: Vec::new(),
+            list_state: ListState::default(),
+            sort,
+            reverse,
+            filter: None,
+            screen: Screen::Browse,
+            progress: 0,
+            mode,
+            notice: None,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Recomputes the visible rows after a sort or filter change
+    fn refresh(&mut self) {
+        let entries = &self.entries;
+        self.visible = (0..entries.len())
+            .filter(|&i| {
+                self.filter
+                    .is_none_or(|t| entries[i].project.project_types().contains(&t))
+            })
+            .collect();
+
+        // Same ordering as the non-interactive listing
+        self.visible.sort_by(|&a, &b| {
+            let ordering = self
+                .sort
+                .compare(&entries[a].sort_fields(), &entries[b].sort_fields());
+            if self.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let selected = match self.visible.len() {
+            0 => None,
+            len => Some(self.list_state.selected().unwrap_or(0).min(len - 1)),
+        };
+        self.list_state.select(selected);
+    }
+
+    /// Cycles the type filter through all types present in the results
+    fn next_filter(&mut self) {
+        let mut types: Vec<ProjectType> = Vec::new();
+        for project_type in self.entries.iter().flat_map(|e| e.project.project_types()) {
+            if !types.contains(&project_type) {
+                types.push(project_type);
+            }
+        }
+        types.sort_by_key(|t| t.name());
+
+        self.filter = match self.filter {
+            None => types.first().copied(),
+            Some(current) => types
+                .iter()
+                .position(|&t| t == current)
+                .and_then(|i| types.get(i + 1).copied()),
+        };
+        self.refresh();
+    }
+
+    /// Opens the highlighted project's directory in the file manager
+    fn open_current(&mut self) {
+        if let Some(&index) = self.list_state.selected().and_then(|i| self.visible.get(i)) {
+            let path = &self.entries[index].project.path;
+            if let Err(e) = crate::open_in_file_manager(path) {
+                self.notice = Some(format!("cannot open {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    fn toggle_current(&mut self) {
+        if let Some(&index) = self.list_state.selected().and_then(|i| self.visible.get(i)) {
+            let entry = &mut self.entries[index];
+            entry.selected = !entry.selected && !entry.project.protected;
+        }
+    }
+
+    /// Selects every visible row, or clears them if all are already selected
+    fn toggle_all(&mut self) {
+        let selectable: Vec<usize> = self
+            .visible
+            .iter()
+            .copied()
+            .filter(|&i| !self.entries[i].project.protected)
+            .collect();
+        let all_selected = selectable.iter().all(|&i| self.entries[i].selected);
+        for i in selectable {
+            self.entries[i].selected = !all_selected;
+        }
+    }
+
+    fn selected_size(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter(|e| e.selected)
+            .map(|e| e.size)
+            .sum()
+    }
+
+    fn selected_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.selected).count()
+    }
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+/// Runs the interactive UI until the user quits, returning what was cleaned
+pub fn run(
+    projects: Vec<(Project, u64)>,
+    options: &ScanOptions,
+    mode: CleanMode,
+    sort: SortKey,
+    reverse: bool,
+) -> io::Result<Summary> {
+    let mut app = App::new(projects, options, mode, sort, reverse);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result?;
+
+    let mut summary = Summary::default();
+    for entry in &app.entries {
+        match entry.status {
+            Status::Cleaned(bytes) => {
+                summary.projects_cleaned += 1;
+                summary.total_cleaned += bytes;
+            }
+            Status::Failed(_) => summary.projects_failed += 1,
+            Status::Pending => {}
+        }
+    }
+    Ok(summary)
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        app.notice = None;
+
+        match app.screen {
+            Screen::Browse => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => app.list_state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => app.list_state.select_previous(),
+                KeyCode::Char(' ') => {
+                    app.toggle_current();
+                    app.list_state.select_next();
+                }
+                KeyCode::Char('a') => app.toggle_all(),
+                KeyCode::Char('s') => {
+                    app.sort = app.sort.next();
+                    app.refresh();
+                }
+                KeyCode::Char('r') => {
+                    app.reverse = !app.reverse;
+                    app.refresh();
+                }
+                KeyCode::Char('t') => app.next_filter(),
+                KeyCode::Char('o') => app.open_current(),
+                KeyCode::Enter | KeyCode::Char('c') if app.selected_count() > 0 => {
+                    app.screen = Screen::Confirm;
+                }
+                _ => {}
+            },
+            Screen::Confirm => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => clean_selected(terminal, app)?,
+                _ => app.screen = Screen::Browse,
+            },
+            Screen::Cleaning => {}
+            Screen::Done => return Ok(()),
+        }
+    }
+}
+
+/// Cleans every selected project, redrawing the progress view after each one
+fn clean_selected(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    app.screen = Screen::Cleaning;
+    app.progress = 0;
+
+    let indices: Vec<usize> = (0..app.entries.len())
+        .filter(|&i| app.entries[i].selected)
+        .collect();
+
+    for index in indices {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        let entry = &mut app.entries[index];
+        entry.status = if app.mode.dry_run {
+            Status::Cleaned(entry.size)
+        } else {
+            match crate::clean_project(&entry.project, &app.mode).and_then(CleanReport::into_result)
+            {
+                Ok(report) => Status::Cleaned(report.bytes_freed),
+                Err(e) => Status::Failed(e.to_string()),
+            }
+        };
+        entry.selected = false;
+        app.progress += entry.size;
+    }
+
+    app.screen = Screen::Done;
+    Ok(())
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, header, app);
+    draw_list(frame, body, app);
+    draw_footer(frame, footer, app);
+
+    match app.screen {
+        Screen::Confirm => draw_confirm(frame, app),
+        Screen::Cleaning | Screen::Done => draw_progress(frame, app),
+        Screen::Browse => {}
+    }
+
+    // --color=never and NO_COLOR keep bold and reversed text but drop colors
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        for cell in &mut frame.buffer_mut().content {
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+    }
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, app: &App) {
+    let total: u64 = app.visible.iter().map(|&i| app.entries[i].size).sum();
+    let filter = app.filter.map_or("all types", |t| t.name());
+
+    let line = Line::from(vec![
+        Span::styled(" Dev Dust ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+        Span::raw(format!(
+            "  {} projects, {}  │  sort: {}{}  │  showing: {}  │  selected: {} ({})",
+            app.visible.len(),
+            format_size(total),
+            app.sort,
+            if app.reverse { " (reversed)" } else { "" },
+            filter,
+            app.selected_count(),
+            format_size(app.selected_size()),
+        )),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_list(frame: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = app
+        .visible
+        .iter()
+        .map(|&i| {
+            let entry = &app.entries[i];
+            let checkbox = match (entry.project.protected, entry.selected) {
+                (true, _) => "[-]",
+                (false, true) => "[x]",
+                (false, false) => "[ ]",
+            };
+            let age = entry
+                .modified
+                .and_then(|m| m.elapsed().ok())
+                .map_or_else(|| "-".to_string(), |e| format_elapsed_time(e.as_secs()));
+
+            let status = match &entry.status {
+                Status::Pending => Span::raw(""),
+                Status::Cleaned(bytes) => {
+                    Span::styled(format!("  ✓ {}", format_size(*bytes)), Color::Green)
+                }
+                Status::Failed(e) => Span::styled(format!("  ✗ {}", e), Color::Red),
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{} ", checkbox)),
+                Span::styled(
+                    format!("{:>10} ", format_size(entry.size)),
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{:<24} ", entry.project.display_name()),
+                    Modifier::BOLD,
+                ),
+                Span::styled(format!("{:<14} ", entry.project.type_name()), Color::Blue),
+                Span::styled(format!("{:<16} ", age), Color::DarkGray),
+                Span::styled(entry.project.path.display().to_string(), Color::DarkGray),
+                status,
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::bordered().title(" Projects "))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("› ");
+
+    frame.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+    if let Some(notice) = &app.notice {
+        frame.render_widget(Paragraph::new(notice.as_str().red()), area);
+        return;
+    }
+    let keys = match app.screen {
+        Screen::Browse => {
+            "↑/↓ move  space select  a all  s sort  r reverse  t filter type  o open  enter clean  q quit"
+        }
+        Screen::Confirm => "y confirm  any other key cancel",
+        Screen::Cleaning => "cleaning…",
+        Screen::Done => "press any key to exit",
+    };
+    frame.render_widget(Paragraph::new(keys.dark_gray()), area);
+}
+
+fn draw_confirm(frame: &mut Frame, app: &App) {
+    let area = centered(frame.area(), 60, 5);
+    let action = match &app.mode.method {
+        _ if app.mode.dry_run => "Simulate cleaning",
+        CleanMethod::Trash => "Move to trash",
+        CleanMethod::Quarantine(_) => "Quarantine artifacts of",
+        CleanMethod::Archive(_) => "Archive artifacts of",
+        CleanMethod::Relocate(_) => "Relocate artifacts of",
+        CleanMethod::Background(_) => "Permanently delete artifacts of",
+        CleanMethod::Delete => "Permanently delete artifacts of",
+        CleanMethod::Partial(_) => "Delete stale artifacts of",
+        CleanMethod::Native => "Run native clean for",
+    };
+    let text = format!(
+        "{} {} projects ({})?\n\n[y] yes   [n] no",
+        action,
+        app.selected_count(),
+        format_size(app.selected_size())
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text)
+            .centered()
+            .block(Block::bordered().title(" Confirm ").yellow()),
+        area,
+    );
+}
+
+fn draw_progress(frame: &mut Frame, app: &App) {
+    let area = centered(frame.area(), 60, 3);
+    let total: u64 = app
+        .entries
+        .iter()
+        .filter(|e| e.selected || !matches!(e.status, Status::Pending))
+        .map(|e| e.size)
+        .sum();
+    let ratio = if total == 0 {
+        1.0
+    } else {
+        (app.progress as f64 / total as f64).min(1.0)
+    };
+    let title = if app.screen == Screen::Done {
+        " Done "
+    } else {
+        " Cleaning "
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(title))
+            .gauge_style(Color::Green)
+            .ratio(ratio)
+            .label(format!(
+                "{} / {}",
+                format_size(app.progress),
+                format_size(total)
+            )),
+        area,
+    );
+}
+
+/// Returns a rectangle of the given size centered inside `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
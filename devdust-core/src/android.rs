@@ -0,0 +1,111 @@
+//! Android SDK system images
+//!
+//! Every emulator (AVD) boots from a system image in the SDK, at roughly 2-8 GB
+//! each. Images stay installed after the last AVD using them is deleted; those
+//! are safe to remove, while images an AVD still points to are left alone. An
+//! AVD's image is the `image.sysdir.1` entry of its `config.ini`, relative to
+//! the SDK root.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Returns the Android SDK root, honoring `ANDROID_HOME` and `ANDROID_SDK_ROOT`
+pub(crate) fn sdk_dir() -> Option<PathBuf> {
+    if let Some(sdk) = ["ANDROID_HOME", "ANDROID_SDK_ROOT"]
+        .iter()
+        .filter_map(env::var_os)
+        .find(|value| !value.is_empty())
+    {
+        return Some(PathBuf::from(sdk));
+    }
+
+    // Where Android Studio installs the SDK by default
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|h| h.join("Library").join("Android").join("sdk"))
+    } else if cfg!(windows) {
+        dirs::data_local_dir().map(|d| d.join("Android").join("Sdk"))
+    } else {
+        dirs::home_dir().map(|h| h.join("Android").join("Sdk"))
+    }
+}
+
+/// Returns the directory holding the AVDs
+fn avd_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+
+    env_dir("ANDROID_AVD_HOME")
+        .or_else(|| env_dir("ANDROID_USER_HOME").map(|d| d.join("avd")))
+        .or_else(|| dirs::home_dir().map(|h| h.join(".android").join("avd")))
+}
+
+/// Returns the system image directories some AVD boots from
+pub(crate) fn used_system_images() -> Vec<PathBuf> {
+    match (sdk_dir(), avd_dir()) {
+        (Some(sdk), Some(avds)) => used_images(&sdk, &avds),
+        _ => Vec::new(),
+    }
+}
+
+/// Reads the system images referenced by the AVDs in `avd_dir`
+fn used_images(sdk: &Path, avd_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(avd_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "avd"))
+        .filter_map(|e| fs::read_to_string(e.path().join("config.ini")).ok())
+        .filter_map(|config| {
+            config.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "image.sysdir.1").then(|| {
+                    value
+                        .trim()
+                        .split(['/', '\\'])
+                        .filter(|c| !c.is_empty())
+                        .fold(sdk.to_path_buf(), |path, c| path.join(c))
+                })
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_used_images() {
+        let root = tempfile::tempdir().unwrap();
+        let sdk = root.path().join("sdk");
+        let avds = root.path().join("avd");
+        fs::create_dir_all(avds.join("Pixel_8_API_34.avd")).unwrap();
+        fs::write(
+            avds.join("Pixel_8_API_34.avd").join("config.ini"),
+            "hw.lcd.density=420\nimage.sysdir.1=system-images/android-34/google_apis/x86_64/\n",
+        )
+        .unwrap();
+        // Pointer file next to the AVD folder, not an AVD itself
+        fs::write(avds.join("Pixel_8_API_34.ini"), "path=/elsewhere").unwrap();
+
+        assert_eq!(
+            used_images(&sdk, &avds),
+            vec![sdk
+                .join("system-images")
+                .join("android-34")
+                .join("google_apis")
+                .join("x86_64")]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Compressed archives of cleaned artifacts
+//!
+//! Instead of deleting artifact directories, a clean can pack each one into a
+//! zstd-compressed tarball in a destination of the user's choosing, such as a
+//! slow external drive, and delete it afterwards. Batches are laid out like the
+//! quarantine's: one directory per clean run, holding the archives and a
+//! manifest of where they came from, so they can be unpacked again later.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    quarantine::{format_entry, write_manifest, MANIFEST_FILE_NAME},
+    remove, BatchInfo, QuarantinedEntry, RestoreReport,
+};
+
+/// Extension of the archive files
+const EXTENSION: &str = "tar.zst";
+
+/// zstd's default level; higher ones are much slower for little gain on build output
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// A destination folder holding archive batches
+#[derive(Debug, Clone)]
+pub struct Archive {
+    root: PathBuf,
+}
+
+/// One clean run's worth of archived artifact directories
+///
+/// The batch directory is only created once the first artifact is archived.
+#[derive(Debug)]
+pub struct ArchiveBatch {
+    dir: PathBuf,
+    /// Number of archives written so far, used to name them
+    count: Mutex<usize>,
+}
+
+impl Archive {
+    /// Opens the archive folder at `root`; nothing is created until something is archived
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Returns the archive folder
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Starts a new batch for one clean run
+    pub fn begin(&self) -> ArchiveBatch {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        ArchiveBatch {
+            dir: self.root.join(format!("{}-{}", secs, process::id())),
+            count: Mutex::new(0),
+        }
+    }
+
+    /// Lists all batches, oldest first
+    pub fn batches(&self) -> io::Result<Vec<BatchInfo>> {
+        let read_dir = match fs::read_dir(&self.root) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut batches: Vec<BatchInfo> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| BatchInfo::read(&entry.path()))
+            .collect();
+        batches.sort_by_key(|b| (b.created, b.path.clone()));
+        Ok(batches)
+    }
+
+    /// Unpacks the most recent batch to where its directories came from
+    ///
+    /// Returns `None` if there are no batches.
+    pub fn restore_last(&self) -> io::Result<Option<RestoreReport>> {
+        match self.batches()?.pop() {
+            Some(batch) => self.restore(&batch).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Unpacks every entry of `batch` whose original location is free again
+    ///
+    /// Unpacked archives are deleted, and the batch with them once it is empty.
+    /// Entries whose original location exists again stay and are reported as errors.
+    pub fn restore(&self, batch: &BatchInfo) -> io::Result<RestoreReport> {
+        let mut report = RestoreReport::default();
+        let mut remaining = Vec::new();
+
+        for entry in &batch.entries {
+            let file = batch.path.join(&entry.name);
+            let result = if entry.original.exists() {
+                Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "original location exists again",
+                ))
+            } else {
+                unpack(&file, &entry.original).inspect_err(|_| {
+                    // Leave nothing half-unpacked behind to block the next attempt
+                    let _ = remove::remove_path(&entry.original);
+                })
+            };
+
+            match result {
+                Ok(()) => {
+                    fs::remove_file(&file)?;
+                    report.bytes += entry.size;
+                    report.restored.push(entry.original.clone());
+                }
+                Err(e) => {
+                    report.errors.push((entry.original.clone(), e));
+                    remaining.push(entry.clone());
+                }
+            }
+        }
+
+        if remaining.is_empty() {
+            fs::remove_dir_all(&batch.path)?;
+        } else {
+            write_manifest(&batch.path, &remaining)?;
+        }
+
+        Ok(report)
+    }
+}
+
+impl ArchiveBatch {
+    /// Returns the batch directory
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Packs a directory into the batch, records it in the manifest and deletes it
+    ///
+    /// The directory is only deleted once its archive is complete.
+    pub fn store(&self, path: &Path, size: u64) -> io::Result<()> {
+        let original = std::path::absolute(path)?;
+
+        // Serialize archiving so file names and manifest lines stay in step
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        fs::create_dir_all(&self.dir)?;
+
+        let entry = QuarantinedEntry {
+            name: format!("{}.{}", count, EXTENSION),
+            size,
+            original,
+        };
+        let target = self.dir.join(&entry.name);
+        // A run interrupted halfway leaves a .partial file, never a truncated archive
+        let partial = target.with_extension("zst.partial");
+        pack(path, &partial)?;
+        fs::rename(&partial, &target)?;
+
+        let mut manifest = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(MANIFEST_FILE_NAME))?;
+        writeln!(manifest, "{}", format_entry(&entry))?;
+        *count += 1;
+        drop(count);
+
+        remove::remove_path(path)
+    }
+}
+
+/// Writes `path` as a zstd-compressed tarball to `file`, under its own name
+///
+/// Some artifacts are single files (LaTeX leftovers, exported games), so the
+/// tarball holds the directory or file itself rather than a directory's contents.
+fn pack(path: &Path, file: &Path) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no name"))?;
+    let encoder = zstd::Encoder::new(fs::File::create(file)?, COMPRESSION_LEVEL)?;
+    let mut builder = tar::Builder::new(encoder);
+    // Symlinks are archived as links, as they were found
+    builder.follow_symlinks(false);
+    if path.is_dir() {
+        builder.append_dir_all(name, path)?;
+    } else {
+        builder.append_path_with_name(path, name)?;
+    }
+    builder.into_inner()?.finish()?.sync_all()
+}
+
+/// Unpacks a tarball written by `pack` so it recreates `original`
+fn unpack(file: &Path, original: &Path) -> io::Result<()> {
+    let parent = original
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no parent"))?;
+    fs::create_dir_all(parent)?;
+    let decoder = zstd::Decoder::new(fs::File::open(file)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    archive.set_preserve_mtime(true);
+    archive.unpack(parent)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_restore() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("app").join("target");
+        fs::create_dir_all(target.join("debug")).unwrap();
+        fs::write(target.join("debug").join("app"), [7u8; 4096]).unwrap();
+
+        let archive = Archive::new(root.path().join("archive"));
+        let batch = archive.begin();
+        batch.store(&target, 4096).unwrap();
+        assert!(!target.exists());
+
+        let batches = archive.batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].entries[0].name, "0.tar.zst");
+        assert_eq!(batches[0].size(), 4096);
+        let packed = fs::metadata(batch.path().join("0.tar.zst")).unwrap().len();
+        assert!(packed < 4096);
+
+        let report = archive.restore_last().unwrap().unwrap();
+        assert_eq!(report.restored, vec![target.clone()]);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            fs::read(target.join("debug").join("app")).unwrap(),
+            [7u8; 4096]
+        );
+        assert!(archive.batches().unwrap().is_empty());
+        assert!(archive.restore_last().unwrap().is_none());
+    }
+}
@@ -0,0 +1,342 @@
+//! Resolving artifact directory patterns
+//!
+//! Artifact lists may contain `*` and `?` wildcards (e.g. `*.egg-info`,
+//! `cmake-build-*`). Since `Path::join` takes them literally, patterns are
+//! expanded against the project directory before use. Each path component
+//! may hold wildcards; names without any are passed through unchanged whether
+//! or not they exist. A `**` component matches any number of directories, so
+//! `**/__pycache__` finds the cache of every package; the search doesn't enter
+//! hidden directories or directories any project type treats as artifacts, and
+//! stops [`RECURSIVE_MAX_DEPTH`] levels down.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{matches_wildcard, CustomRule, Project, ProjectType};
+
+/// Pattern component matching any number of directories
+const RECURSIVE: &str = "**";
+
+/// Deepest level below its start that a `**` component matches
+const RECURSIVE_MAX_DEPTH: usize = 12;
+
+/// Names of all artifact directories any project type may have
+///
+/// Used while walking to cheaply rule out directories before checking whether
+/// their parent really is a project that owns them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KnownArtifacts {
+    names: HashSet<String>,
+    patterns: Vec<String>,
+    custom_rules: Vec<CustomRule>,
+    /// Most path components in any artifact (3 for `android/app/build`)
+    max_components: usize,
+    /// Whether any artifact may lie arbitrarily deep (`**/__pycache__`)
+    recursive: bool,
+}
+
+impl KnownArtifacts {
+    pub(crate) fn new(custom_rules: &[CustomRule]) -> Self {
+        let mut known = Self {
+            custom_rules: custom_rules.to_vec(),
+            ..Self::default()
+        };
+
+        let built_in = ProjectType::ALL
+            .iter()
+            .flat_map(|t| t.artifact_directories().iter().copied());
+        let custom = custom_rules
+            .iter()
+            .flat_map(|rule| rule.artifacts.iter().map(String::as_str));
+
+        for artifact in built_in.chain(custom) {
+            let components = artifact.split('/').filter(|c| !c.is_empty()).count();
+            known.max_components = known.max_components.max(components);
+            known.recursive |= artifact.split('/').any(|c| c == RECURSIVE);
+
+            // Only the last component is compared against walked directory names
+            let name = artifact.rsplit('/').next().unwrap_or(artifact);
+            if is_pattern(name) {
+                known.patterns.push(name.to_string());
+            } else {
+                known.names.insert(name.to_string());
+            }
+        }
+
+        known
+    }
+
+    /// Returns true if `dir` is an artifact directory of the project containing it
+    ///
+    /// Nothing below such a directory (dependencies, vendored sources, packaged
+    /// crates) is worth scanning for projects of its own.
+    pub(crate) fn is_artifact_dir(&self, dir: &Path) -> bool {
+        let Some(name) = dir.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        let known =
+            self.names.contains(name) || self.patterns.iter().any(|p| matches_wildcard(p, name));
+        if !known {
+            return false;
+        }
+
+        // Nested artifacts such as `vendor/bundle` belong to a project further up
+        dir.ancestors()
+            .skip(1)
+            .take(self.max_components)
+            .any(|ancestor| {
+                let Ok(relative) = dir.strip_prefix(ancestor) else {
+                    return false;
+                };
+                Project::detect(ancestor, &self.custom_rules)
+                    .is_some_and(|project| project.owns_artifact(relative))
+            })
+    }
+
+    /// Returns the artifacts of `project` that an enclosing project lists as its own
+    ///
+    /// A React Native app lists `android/.gradle`, which is also an artifact of
+    /// the Gradle project in `android/`; it is sized and cleaned with the app.
+    /// At most `levels` parent directories are checked.
+    pub(crate) fn claimed_by_enclosing(&self, project: &Project, levels: usize) -> Vec<String> {
+        let own = project.artifact_directories();
+        let mut claimed: Vec<String> = Vec::new();
+        let levels = if self.recursive {
+            levels
+        } else {
+            levels.min(self.max_components)
+        };
+
+        for ancestor in project.path.ancestors().skip(1).take(levels) {
+            let Some(enclosing) = Project::detect(ancestor, &self.custom_rules) else {
+                continue;
+            };
+            for dir in &own {
+                let path: PathBuf = project.path.join(dir);
+                let Ok(relative) = path.strip_prefix(ancestor) else {
+                    continue;
+                };
+                if enclosing.owns_artifact(relative) && !claimed.contains(dir) {
+                    claimed.push(dir.clone());
+                }
+            }
+        }
+
+        claimed
+    }
+}
+
+/// Returns true if `pattern` contains wildcards
+pub(crate) fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Expands artifact patterns into directory names relative to `root`
+///
+/// Wildcard patterns yield only the entries that exist, in sorted order.
+/// Duplicates (e.g. `bazel-*` also matching `bazel-out`) are listed once.
+pub(crate) fn resolve(root: &Path, patterns: &[&str]) -> Vec<String> {
+    let mut resolved: Vec<String> = Vec::new();
+    // Several patterns starting with `**` share one walk
+    let mut walked = HashMap::new();
+
+    for pattern in patterns {
+        let names = if is_pattern(pattern) {
+            expand(root, pattern, &mut walked)
+        } else {
+            vec![pattern.to_string()]
+        };
+
+        for name in names {
+            if !resolved.contains(&name) {
+                resolved.push(name);
+            }
+        }
+    }
+
+    resolved
+}
+
+/// Expands a wildcard pattern one path component at a time
+///
+/// `walked` holds the directories found below each prefix a `**` was expanded at.
+fn expand(root: &Path, pattern: &str, walked: &mut HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut matches = vec![String::new()];
+
+    for component in pattern.split('/').filter(|c| !c.is_empty()) {
+        let mut next = Vec::new();
+        for prefix in &matches {
+            let dir = root.join(prefix);
+            if component == RECURSIVE {
+                let below = walked.entry(prefix.clone()).or_insert_with(|| {
+                    let mut below = Vec::new();
+                    subdirectories(root, prefix, 1, &mut below);
+                    below
+                });
+                next.push(prefix.clone());
+                next.extend(below.iter().cloned());
+                continue;
+            }
+            if !is_pattern(component) {
+                next.push(join(prefix, component));
+                continue;
+            }
+
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| matches_wildcard(component, name))
+                .collect();
+            names.sort();
+            next.extend(names.iter().map(|name| join(prefix, name)));
+        }
+        matches = next;
+    }
+
+    // Literal components after a wildcard may not exist
+    matches.retain(|name| !name.is_empty() && root.join(name).exists());
+    matches
+}
+
+/// Collects every directory below `prefix` that may hold nested artifacts
+///
+/// `depth` is the level below the `**` component that the entries of `prefix` are at.
+fn subdirectories(root: &Path, prefix: &str, depth: usize, found: &mut Vec<String>) {
+    if depth > RECURSIVE_MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(root.join(prefix)) else {
+        return;
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        // Symlinks are not followed
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') && !is_artifact_name(name))
+        .collect();
+    names.sort();
+
+    for name in names {
+        let path = join(prefix, &name);
+        found.push(path.clone());
+        subdirectories(root, &path, depth + 1, found);
+    }
+}
+
+/// Returns true if any project type lists `name` as an artifact directory
+fn is_artifact_name(name: &str) -> bool {
+    ProjectType::ALL.iter().any(|t| {
+        t.artifact_directories().iter().any(|artifact| {
+            let last = artifact.rsplit('/').next().unwrap_or(artifact);
+            matches_wildcard(last, name)
+        })
+    })
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_patterns() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [
+            "pkg.egg-info",
+            "other.egg-info",
+            "cmake-build-debug",
+            "cmake-build-relwithdebinfo",
+            "sub/nested.egg-info",
+        ] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+
+        assert_eq!(
+            resolve(root.path(), &["build", "*.egg-info", "cmake-build-*"]),
+            vec![
+                "build",
+                "other.egg-info",
+                "pkg.egg-info",
+                "cmake-build-debug",
+                "cmake-build-relwithdebinfo",
+            ]
+        );
+        assert_eq!(
+            resolve(root.path(), &["*/*.egg-info"]),
+            vec!["sub/nested.egg-info"]
+        );
+        assert!(resolve(root.path(), &["*.missing"]).is_empty());
+
+        // `**` stops at a fixed depth
+        let deep = |levels: usize| vec!["d"; levels].join("/") + "/__pycache__";
+        fs::create_dir_all(root.path().join(deep(RECURSIVE_MAX_DEPTH))).unwrap();
+        fs::create_dir_all(root.path().join(deep(RECURSIVE_MAX_DEPTH + 1))).unwrap();
+        assert_eq!(
+            resolve(root.path(), &["**/__pycache__"]),
+            vec![deep(RECURSIVE_MAX_DEPTH)]
+        );
+    }
+
+    #[test]
+    fn test_known_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let web = root.path().join("web");
+        fs::create_dir_all(web.join("node_modules")).unwrap();
+        fs::create_dir_all(web.join("src")).unwrap();
+        fs::create_dir_all(root.path().join("node_modules")).unwrap();
+        fs::write(web.join("package.json"), "{}").unwrap();
+
+        let known = KnownArtifacts::new(&[]);
+        assert!(known.is_artifact_dir(&web.join("node_modules")));
+        assert!(!known.is_artifact_dir(&web.join("src")));
+        // Not owned by a project
+        assert!(!known.is_artifact_dir(&root.path().join("node_modules")));
+    }
+
+    #[test]
+    fn test_nested_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        fs::create_dir_all(app.join("android").join(".gradle")).unwrap();
+        fs::create_dir_all(app.join("android").join("build")).unwrap();
+        fs::write(app.join("package.json"), "{}").unwrap();
+        fs::write(app.join("android").join("build.gradle"), "").unwrap();
+
+        let known = KnownArtifacts::new(&[]);
+        assert!(known.is_artifact_dir(&app.join("android").join(".gradle")));
+        assert!(!known.is_artifact_dir(&app.join("android")));
+
+        let gradle = Project::detect(&app.join("android"), &[]).unwrap();
+        assert_eq!(known.claimed_by_enclosing(&gradle, 1), vec![".gradle"]);
+        assert!(known.claimed_by_enclosing(&gradle, 0).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_deduplicates() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("bazel-out")).unwrap();
+
+        assert_eq!(
+            resolve(root.path(), &["bazel-out", "bazel-*"]),
+            vec!["bazel-out"]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Async scanning API (`async` feature)
+//!
+//! The scan itself is blocking filesystem work. It runs on Tokio's blocking
+//! thread pool and hands projects over through a bounded channel, so async
+//! frontends can consume results as a `Stream` without tying up their workers.
+
+use std::path::Path;
+
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+use crate::{scan_directory, Project, ScanError, ScanOptions};
+
+/// How many scanned projects may wait for the consumer before the scan pauses
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Scans a directory like [`scan_directory`], yielding projects as a stream
+///
+/// Must be called from within a Tokio runtime. Dropping the stream stops the
+/// scan once the current directory has been processed.
+pub fn scan_directory_async<P: AsRef<Path>>(
+    path: P,
+    options: &ScanOptions,
+) -> impl Stream<Item = Result<Project, ScanError>> + Send + 'static {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    let path = path.as_ref().to_path_buf();
+    let options = options.clone();
+
+    tokio::task::spawn_blocking(move || {
+        for result in scan_directory(&path, &options) {
+            if sender.blocking_send(result).is_err() {
+                // The stream was dropped
+                break;
+            }
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tokio_stream::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scan_directory_async() {
+        let root = tempfile::tempdir().unwrap();
+        for (name, marker, artifact) in [
+            ("app", "Cargo.toml", "target"),
+            ("web", "package.json", "node_modules"),
+        ] {
+            let dir = root.path().join(name);
+            fs::create_dir_all(dir.join(artifact)).unwrap();
+            fs::write(dir.join(marker), "").unwrap();
+        }
+
+        let mut names: Vec<String> = scan_directory_async(root.path(), &ScanOptions::default())
+            .filter_map(Result::ok)
+            .map(|project| project.display_name())
+            .collect()
+            .await;
+        names.sort();
+
+        assert_eq!(names, ["app", "web"]);
+    }
+}
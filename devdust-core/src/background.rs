@@ -0,0 +1,179 @@
+//! Deleting artifact directories in the background
+//!
+//! Removing a large tree takes a while, but its name can be freed at once:
+//! each directory is renamed to a hidden sibling, which is atomic on the same
+//! volume, and a worker thread deletes it from there. Builds can recreate the
+//! artifact right away and the clean moves on to the next project. The caller
+//! either waits for the worker at the end or detaches, taking over whatever is
+//! still queued, e.g. to hand it to another process.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::remove::{self, STAGING_PREFIX};
+
+/// Deletes artifact directories on a worker thread after renaming them out of the way
+///
+/// Dropping it waits for the queued directories, like [`BackgroundRemover::wait`].
+#[derive(Debug)]
+pub struct BackgroundRemover {
+    sender: Mutex<Option<mpsc::Sender<PathBuf>>>,
+    /// Tells the worker to leave the rest of the queue alone
+    stop: Arc<AtomicBool>,
+    worker: Mutex<Option<JoinHandle<BackgroundReport>>>,
+}
+
+/// What a [`BackgroundRemover`] did by the time it finished
+#[derive(Debug, Default)]
+pub struct BackgroundReport {
+    /// Number of directories deleted
+    pub removed: usize,
+    /// Renamed directories that could not be deleted
+    pub errors: Vec<(PathBuf, io::Error)>,
+    /// Renamed directories left alone after detaching
+    pub remaining: Vec<PathBuf>,
+}
+
+impl BackgroundRemover {
+    /// Starts the worker thread
+    pub fn start() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || work(receiver, &stop))
+        };
+
+        Self {
+            sender: Mutex::new(Some(sender)),
+            stop,
+            worker: Mutex::new(Some(worker)),
+        }
+    }
+
+    /// Renames `path` to a hidden sibling and queues it for deletion
+    ///
+    /// If it can't be renamed (on Windows, open files prevent it) or the worker
+    /// has finished, it is deleted before returning instead.
+    pub fn remove(&self, path: &Path) -> io::Result<()> {
+        let Ok(staged) = remove::stage(path) else {
+            return remove::remove_path(path);
+        };
+        match lock(&self.sender).as_ref() {
+            Some(sender) if sender.send(staged.clone()).is_ok() => Ok(()),
+            _ => remove::remove_path(&staged),
+        }
+    }
+
+    /// Waits until every queued directory is deleted
+    pub fn wait(&self) -> BackgroundReport {
+        self.finish(false)
+    }
+
+    /// Stops after the directory being deleted and returns the rest in `remaining`
+    pub fn detach(&self) -> BackgroundReport {
+        self.finish(true)
+    }
+
+    fn finish(&self, stop: bool) -> BackgroundReport {
+        self.stop.store(stop, Ordering::Relaxed);
+        // Closing the channel ends the worker's loop once the queue is drained
+        drop(lock(&self.sender).take());
+        lock(&self.worker)
+            .take()
+            .and_then(|worker| worker.join().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for BackgroundRemover {
+    fn drop(&mut self) {
+        self.wait();
+    }
+}
+
+/// Deletes a directory renamed by a [`BackgroundRemover`], e.g. one it left after detaching
+///
+/// Refuses paths whose name doesn't show they were renamed for deletion.
+pub fn remove_staged(path: &Path) -> io::Result<()> {
+    let is_staged = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(STAGING_PREFIX));
+    if !is_staged {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} was not renamed for deletion", path.display()),
+        ));
+    }
+    remove::remove_path(path)
+}
+
+/// Deletes queued directories until the channel closes
+fn work(receiver: mpsc::Receiver<PathBuf>, stop: &AtomicBool) -> BackgroundReport {
+    let mut report = BackgroundReport::default();
+    for path in receiver {
+        if stop.load(Ordering::Relaxed) {
+            report.remaining.push(path);
+            continue;
+        }
+        match remove::remove_path(&path) {
+            Ok(()) => report.removed += 1,
+            Err(e) => report.errors.push((path, e)),
+        }
+    }
+    report
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_background_removal() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("target");
+        fs::create_dir_all(target.join("debug")).unwrap();
+        fs::write(target.join("debug").join("app"), [0u8; 64]).unwrap();
+
+        let remover = BackgroundRemover::start();
+        remover.remove(&target).unwrap();
+        // The name is free before the deletion finishes
+        assert!(!target.exists());
+        fs::create_dir(&target).unwrap();
+
+        let report = remover.wait();
+        assert_eq!(report.removed, 1);
+        assert!(report.errors.is_empty());
+        let left: Vec<_> = fs::read_dir(root.path()).unwrap().collect();
+        assert_eq!(left.len(), 1);
+
+        // Detaching hands over what the worker hasn't started
+        let remover = BackgroundRemover::start();
+        remover.stop.store(true, Ordering::Relaxed);
+        remover.remove(&target).unwrap();
+        let report = remover.detach();
+        assert_eq!(report.removed, 0);
+        assert_eq!(report.remaining.len(), 1);
+        assert!(report.remaining[0].exists());
+
+        assert!(remove_staged(root.path()).is_err());
+        remove_staged(&report.remaining[0]).unwrap();
+        assert!(!report.remaining[0].exists());
+    }
+}
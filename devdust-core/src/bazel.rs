@@ -0,0 +1,129 @@
+//! Bazel output bases
+//!
+//! Bazel doesn't build inside the workspace: `bazel-bin`, `bazel-out` and the
+//! other `bazel-*` entries are convenience symlinks into the workspace's output
+//! base (under `~/.cache/bazel` on Linux), which holds the build outputs, the
+//! execroot and every fetched external repository. Deleting the links frees
+//! nothing and sizing them counts the same tree several times, so the output
+//! base itself is sized and cleaned, and the links are removed along with it,
+//! just like `bazel clean --expunge` does.
+//!
+//! A Bazel server keeps running for hours after a build, holding the output
+//! base open. Before the output base is removed by any method, `bazel
+//! shutdown` stops the workspace's server; if Bazel is not installed, the
+//! directory is removed as is. `--use-native-clean` runs `bazel clean
+//! --expunge` instead, which does both.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use tracing::debug;
+
+use crate::native;
+
+/// Prefix of the convenience symlinks Bazel creates in the workspace
+const LINK_PREFIX: &str = "bazel-";
+
+/// How long `bazel shutdown` may take before the output base is removed anyway
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns the output base the workspace's convenience symlinks point into
+///
+/// Every link target lies below `<output base>/execroot/`. Targets that would
+/// put the workspace itself inside the output base are ignored.
+pub(crate) fn output_base(project_dir: &Path) -> Option<PathBuf> {
+    convenience_links(project_dir).into_iter().find_map(|link| {
+        let target = project_dir.join(fs::read_link(&link).ok()?);
+        let execroot = target
+            .ancestors()
+            .find(|dir| dir.file_name().is_some_and(|name| name == "execroot"))?;
+        let base = execroot.parent()?.to_path_buf();
+        (base.is_dir() && !project_dir.starts_with(&base)).then_some(base)
+    })
+}
+
+/// Stops the workspace's Bazel server, if one is running, so it lets go of the output base
+pub(crate) fn shutdown_server(project_dir: &Path) {
+    let command = ["bazel", "shutdown"];
+    if let Err(e) = native::run_command(command[0], &command, project_dir, SHUTDOWN_TIMEOUT) {
+        debug!(path = %project_dir.display(), error = %e, "could not shut down bazel server");
+    }
+}
+
+/// Returns true if `path` is one of the workspace's convenience symlinks
+fn is_convenience_link(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(LINK_PREFIX))
+        && path.symlink_metadata().is_ok_and(|m| m.is_symlink())
+}
+
+/// Unlinks convenience symlinks whose target no longer exists
+pub(crate) fn remove_dangling_links(project_dir: &Path) {
+    for link in convenience_links(project_dir) {
+        if !link.exists() {
+            let _ = fs::remove_file(&link).or_else(|_| fs::remove_dir(&link));
+        }
+    }
+}
+
+fn convenience_links(project_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(project_dir) else {
+        return Vec::new();
+    };
+    let mut links: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| is_convenience_link(path))
+        .collect();
+    links.sort();
+    links
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Project;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cleans_output_base() {
+        use std::os::unix::fs::symlink;
+
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join("cache").join("3f2a9c");
+        let main = base.join("execroot").join("_main");
+        let bin = main.join("bazel-out").join("k8-fastbuild").join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(base.join("external").join("rules_go")).unwrap();
+        fs::write(bin.join("server"), vec![0u8; 2048]).unwrap();
+
+        let workspace = root.path().join("monorepo");
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("MODULE.bazel"), "").unwrap();
+        symlink(&bin, workspace.join("bazel-bin")).unwrap();
+        symlink(main.join("bazel-out"), workspace.join("bazel-out")).unwrap();
+        symlink(&main, workspace.join("bazel-monorepo")).unwrap();
+
+        assert_eq!(output_base(&workspace), Some(base.clone()));
+        let project = Project::detect(&workspace, &[]).unwrap();
+        assert_eq!(
+            project.artifact_directories(),
+            vec![base.to_string_lossy().into_owned()]
+        );
+
+        // The links all point into the same tree, which is counted once
+        let freed = project.clean().unwrap().bytes_freed;
+        assert!((2048..8192).contains(&freed));
+        assert!(!base.exists());
+        assert!(convenience_links(&workspace).is_empty());
+        assert!(workspace.join("MODULE.bazel").exists());
+    }
+}
@@ -0,0 +1,86 @@
+//! Cache directory tags
+//!
+//! The Cache Directory Tagging Specification (<https://bford.info/cachedir/>)
+//! marks a directory as disposable with a `CACHEDIR.TAG` file that starts with
+//! a fixed signature. Backup tools such as restic, borg and GNU tar skip tagged
+//! directories. devdust treats them as caches wherever they are, and can tag
+//! the artifact directories it recognizes so backups leave those out too.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Name of the tag file
+pub(crate) const TAG_FILE: &str = "CACHEDIR.TAG";
+
+/// Header every valid tag starts with
+const SIGNATURE: &[u8] = b"Signature: 8a477f597d28d172789f06886806bc55";
+
+/// Returns true if `dir` holds a valid cache directory tag
+pub(crate) fn is_tagged(dir: &Path) -> bool {
+    let Ok(mut file) = fs::File::open(dir.join(TAG_FILE)) else {
+        return false;
+    };
+    let mut header = [0u8; SIGNATURE.len()];
+    file.read_exact(&mut header).is_ok() && header == SIGNATURE
+}
+
+/// Returns the names of the tagged directories directly inside `dir`, sorted
+pub(crate) fn tagged_children(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| is_tagged(&e.path()))
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Writes a tag into `dir` unless it already has a valid one
+///
+/// Returns whether a tag was written.
+pub(crate) fn tag(dir: &Path) -> io::Result<bool> {
+    if is_tagged(dir) {
+        return Ok(false);
+    }
+    let mut contents = SIGNATURE.to_vec();
+    contents.extend_from_slice(
+        b"\n# This file is a cache directory tag created by devdust.\n\
+          # For information about cache directory tags, see:\n\
+          #\thttps://bford.info/cachedir/\n",
+    );
+    fs::write(dir.join(TAG_FILE), contents)?;
+    Ok(true)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = root.path().join("cache");
+        let notes = root.path().join("notes");
+        fs::create_dir_all(&cache).unwrap();
+        fs::create_dir_all(&notes).unwrap();
+        // Not a valid tag, just a file with the same name
+        fs::write(notes.join(TAG_FILE), "keep me").unwrap();
+
+        assert!(tag(&cache).unwrap());
+        assert!(!tag(&cache).unwrap());
+        assert!(is_tagged(&cache));
+        assert!(!is_tagged(&notes));
+        assert_eq!(tagged_children(root.path()), vec!["cache"]);
+    }
+}
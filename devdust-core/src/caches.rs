@@ -0,0 +1,536 @@
+//! Global toolchain caches
+//!
+//! Package managers keep shared download and build caches outside of any
+//! project (`~/.cargo/registry`, `~/.npm`, `~/.m2/repository`, ...). These are
+//! safe to prune because the tools re-download anything that goes missing.
+//! The same goes for model hubs (Hugging Face, PyTorch, Keras), whose
+//! downloaded weights often outgrow every project artifact combined.
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use crate::{android, calculate_directory_size, xcode, CleanError, ScanOptions};
+
+// ============================================================================
+// Cache Definitions
+// ============================================================================
+
+/// A shared cache maintained by a language toolchain or package manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlobalCache {
+    /// Downloaded and extracted crates (`~/.cargo/registry`)
+    CargoRegistry,
+    /// Git dependencies (`~/.cargo/git`)
+    CargoGit,
+    /// npm package cache (`~/.npm/_cacache`)
+    Npm,
+    /// Yarn classic package cache
+    Yarn,
+    /// Gradle dependency, transform and build caches (`~/.gradle/caches`)
+    Gradle,
+    /// Downloaded Gradle distributions (`~/.gradle/wrapper/dists`)
+    GradleWrapper,
+    /// Maven local repository (`~/.m2/repository`)
+    Maven,
+    /// pip HTTP and wheel cache
+    Pip,
+    /// uv package cache
+    Uv,
+    /// Go build cache (`GOCACHE`)
+    GoBuild,
+    /// Hugging Face models and datasets (`~/.cache/huggingface`)
+    HuggingFace,
+    /// PyTorch hub checkpoints (`~/.cache/torch`)
+    Torch,
+    /// Keras models and datasets (`~/.keras`)
+    Keras,
+    /// Xcode build folders of all projects (macOS only)
+    XcodeDerivedData,
+    /// Archived Xcode builds, including their debug symbols (macOS only)
+    XcodeArchives,
+    /// Simulator devices whose runtime is no longer installed (macOS only)
+    Simulators,
+    /// Android emulator system images no AVD uses
+    AndroidSystemImages,
+}
+
+/// Directory name of the Yarn cache inside the platform cache directory
+const YARN_CACHE_NAME: &str = if cfg!(target_os = "macos") {
+    "Yarn"
+} else {
+    "yarn"
+};
+
+/// How a cache is split into individually prunable entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    /// Entries are the directories or files found at this depth below a root
+    Depth(usize),
+    /// Every file is its own entry (content-addressed caches)
+    Files,
+    /// Device folders of unavailable simulators, deleted through `simctl`
+    Simulators,
+}
+
+impl GlobalCache {
+    /// All known caches
+    pub const ALL: &'static [GlobalCache] = &[
+        Self::CargoRegistry,
+        Self::CargoGit,
+        Self::Npm,
+        Self::Yarn,
+        Self::Gradle,
+        Self::GradleWrapper,
+        Self::Maven,
+        Self::Pip,
+        Self::Uv,
+        Self::GoBuild,
+        Self::HuggingFace,
+        Self::Torch,
+        Self::Keras,
+        Self::XcodeDerivedData,
+        Self::XcodeArchives,
+        Self::Simulators,
+        Self::AndroidSystemImages,
+    ];
+
+    /// Returns the short identifier used on the command line
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::CargoRegistry => "cargo-registry",
+            Self::CargoGit => "cargo-git",
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Gradle => "gradle",
+            Self::GradleWrapper => "gradle-wrapper",
+            Self::Maven => "maven",
+            Self::Pip => "pip",
+            Self::Uv => "uv",
+            Self::GoBuild => "go-build",
+            Self::HuggingFace => "huggingface",
+            Self::Torch => "torch",
+            Self::Keras => "keras",
+            Self::XcodeDerivedData => "xcode-derived-data",
+            Self::XcodeArchives => "xcode-archives",
+            Self::Simulators => "simulators",
+            Self::AndroidSystemImages => "android-system-images",
+        }
+    }
+
+    /// Returns the human-readable name of the cache
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CargoRegistry => "Cargo registry",
+            Self::CargoGit => "Cargo git checkouts",
+            Self::Npm => "npm",
+            Self::Yarn => "Yarn",
+            Self::Gradle => "Gradle",
+            Self::GradleWrapper => "Gradle distributions",
+            Self::Maven => "Maven repository",
+            Self::Pip => "pip",
+            Self::Uv => "uv",
+            Self::GoBuild => "Go build cache",
+            Self::HuggingFace => "Hugging Face",
+            Self::Torch => "PyTorch hub",
+            Self::Keras => "Keras",
+            Self::XcodeDerivedData => "Xcode DerivedData",
+            Self::XcodeArchives => "Xcode archives",
+            Self::Simulators => "iOS simulators",
+            Self::AndroidSystemImages => "Android system images",
+        }
+    }
+
+    /// Returns the candidate root directories of this cache, honoring tool env vars
+    pub fn roots(&self) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        let cache = dirs::cache_dir();
+        let cargo_home = env_path("CARGO_HOME").or_else(|| home.as_ref().map(|h| h.join(".cargo")));
+        let gradle_home =
+            env_path("GRADLE_USER_HOME").or_else(|| home.as_ref().map(|h| h.join(".gradle")));
+        // Python ML libraries use ~/.cache on every platform
+        let xdg_cache =
+            env_path("XDG_CACHE_HOME").or_else(|| home.as_ref().map(|h| h.join(".cache")));
+
+        let developer = xcode::developer_dir();
+
+        let roots = match self {
+            Self::CargoRegistry => cargo_home.map(|c| {
+                vec![
+                    c.join("registry").join("cache"),
+                    c.join("registry").join("src"),
+                ]
+            }),
+            Self::CargoGit => {
+                cargo_home.map(|c| vec![c.join("git").join("checkouts"), c.join("git").join("db")])
+            }
+            Self::Npm => env_path("npm_config_cache")
+                .or_else(|| home.as_ref().map(|h| h.join(".npm")))
+                .map(|npm| vec![npm.join("_cacache")]),
+            Self::Yarn => env_path("YARN_CACHE_FOLDER")
+                .or_else(|| cache.as_ref().map(|c| c.join(YARN_CACHE_NAME)))
+                .map(|yarn| vec![yarn]),
+            Self::Gradle => gradle_home.map(|gradle| gradle_cache_roots(&gradle.join("caches"))),
+            Self::GradleWrapper => {
+                gradle_home.map(|gradle| vec![gradle.join("wrapper").join("dists")])
+            }
+            Self::Maven => home.map(|h| vec![h.join(".m2").join("repository")]),
+            Self::Pip => env_path("PIP_CACHE_DIR")
+                .or_else(|| {
+                    cache.map(|c| {
+                        if cfg!(windows) {
+                            c.join("pip").join("Cache")
+                        } else {
+                            c.join("pip")
+                        }
+                    })
+                })
+                .map(|pip| vec![pip]),
+            Self::Uv => env_path("UV_CACHE_DIR")
+                .or_else(|| {
+                    if cfg!(windows) {
+                        cache.map(|c| c.join("uv").join("cache"))
+                    } else {
+                        home.map(|h| h.join(".cache").join("uv"))
+                    }
+                })
+                .map(|uv| vec![uv]),
+            Self::GoBuild => env_path("GOCACHE")
+                .or_else(|| cache.map(|c| c.join("go-build")))
+                .map(|go| vec![go]),
+            Self::HuggingFace => {
+                let hf_home = env_path("HF_HOME")
+                    .or_else(|| xdg_cache.as_ref().map(|c| c.join("huggingface")));
+                let hub =
+                    env_path("HF_HUB_CACHE").or_else(|| hf_home.as_ref().map(|h| h.join("hub")));
+                let datasets = env_path("HF_DATASETS_CACHE")
+                    .or_else(|| hf_home.as_ref().map(|h| h.join("datasets")));
+                Some(hub.into_iter().chain(datasets).collect())
+            }
+            Self::Torch => env_path("TORCH_HOME")
+                .or_else(|| xdg_cache.map(|c| c.join("torch")))
+                .map(|torch| vec![torch.join("hub").join("checkpoints")]),
+            Self::Keras => env_path("KERAS_HOME")
+                .or_else(|| home.map(|h| h.join(".keras")))
+                .map(|keras| vec![keras.join("models"), keras.join("datasets")]),
+            Self::XcodeDerivedData => developer.map(|d| vec![d.join("Xcode").join("DerivedData")]),
+            Self::XcodeArchives => developer.map(|d| vec![d.join("Xcode").join("Archives")]),
+            Self::Simulators => developer.map(|d| vec![d.join("CoreSimulator").join("Devices")]),
+            Self::AndroidSystemImages => {
+                android::sdk_dir().map(|sdk| vec![sdk.join("system-images")])
+            }
+        };
+
+        roots.unwrap_or_default()
+    }
+
+    /// Returns the roots of this cache that exist on disk
+    pub fn locate(&self) -> Vec<PathBuf> {
+        self.roots().into_iter().filter(|p| p.is_dir()).collect()
+    }
+
+    /// Calculates the total size of the cache in bytes
+    pub fn size(&self) -> u64 {
+        let options = ScanOptions::default();
+        self.locate()
+            .iter()
+            .map(|root| calculate_directory_size(root, &options))
+            .sum()
+    }
+
+    /// Returns the entries not modified for at least `min_age_seconds`, with their sizes
+    ///
+    /// An age of zero selects every entry.
+    pub fn stale_entries(&self, min_age_seconds: u64) -> Vec<(PathBuf, u64)> {
+        let options = ScanOptions::default();
+        let mut entries = Vec::new();
+        let in_use = match self {
+            Self::AndroidSystemImages => android::used_system_images(),
+            _ => Vec::new(),
+        };
+
+        for root in self.locate() {
+            for entry in list_entries(&root, self.granularity(&root)) {
+                if min_age_seconds > 0 && !is_older_than(&entry, min_age_seconds) {
+                    continue;
+                }
+                if in_use.contains(&entry) {
+                    continue;
+                }
+                let size = calculate_directory_size(&entry, &options);
+                entries.push((entry, size));
+            }
+        }
+
+        entries
+    }
+
+    /// Deletes the entries not modified for at least `min_age_seconds`
+    ///
+    /// Returns the number of bytes freed.
+    pub fn prune(&self, min_age_seconds: u64) -> Result<u64, CleanError> {
+        let mut deleted = 0u64;
+        let mut errors = Vec::new();
+
+        for (entry, size) in self.stale_entries(min_age_seconds) {
+            let result = if *self == Self::Simulators {
+                xcode::delete_simulator(&entry)
+            } else if entry.is_dir() {
+                fs::remove_dir_all(&entry)
+            } else {
+                fs::remove_file(&entry)
+            };
+
+            match result {
+                Ok(_) => deleted += size,
+                Err(e) => errors.push(CleanError::at(entry, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(deleted)
+        } else {
+            Err(CleanError::PartialFailure { deleted, errors })
+        }
+    }
+
+    /// Returns how a root of the cache is split into prunable entries
+    fn granularity(&self, root: &Path) -> Granularity {
+        match self {
+            // <registry>/<crate> inside cache/ and src/
+            Self::CargoRegistry => Granularity::Depth(2),
+            Self::CargoGit => Granularity::Depth(1),
+            Self::Yarn => Granularity::Depth(2),
+            // <group>/<artifact> for dependencies, hashed entries elsewhere
+            Self::Gradle if root.ends_with(Path::new("modules-2").join("files-2.1")) => {
+                Granularity::Depth(2)
+            }
+            Self::Gradle | Self::GradleWrapper => Granularity::Depth(1),
+            // <api level>/<variant>/<abi>
+            Self::AndroidSystemImages => Granularity::Depth(3),
+            Self::Uv => Granularity::Depth(2),
+            Self::Npm | Self::Maven | Self::Pip | Self::GoBuild => Granularity::Files,
+            // One entry per model, dataset or weights file
+            Self::HuggingFace | Self::Torch | Self::Keras => Granularity::Depth(1),
+            Self::XcodeDerivedData => Granularity::Depth(1),
+            // <date>/<name>.xcarchive
+            Self::XcodeArchives => Granularity::Depth(2),
+            Self::Simulators => Granularity::Simulators,
+        }
+    }
+
+    /// Returns a readable name for one of the cache's entries
+    ///
+    /// Hugging Face repositories are shown as `owner/name`, everything else by
+    /// its path below the cache root (e.g. `android-34/google_apis/x86_64`).
+    pub fn entry_label(&self, entry: &Path) -> String {
+        let name = entry
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if *self == Self::HuggingFace {
+            // e.g. models--meta-llama--Llama-3.1-8B
+            if let Some((kind, repo)) = name.split_once("--") {
+                if matches!(kind, "models" | "datasets" | "spaces") {
+                    return format!(
+                        "{} ({})",
+                        repo.replace("--", "/"),
+                        kind.trim_end_matches('s')
+                    );
+                }
+            }
+        }
+
+        self.roots()
+            .iter()
+            .find_map(|root| entry.strip_prefix(root).ok())
+            .map(|relative| relative.to_string_lossy().into_owned())
+            .unwrap_or(name)
+    }
+}
+
+impl fmt::Display for GlobalCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GlobalCache {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown cache: {}", s))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Reads a path from an environment variable, ignoring empty values
+fn env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns the roots inside `~/.gradle/caches`
+///
+/// Downloaded dependencies are pruned per artifact. The other folders
+/// (`transforms-*`, `jars-*`, `build-cache-*` and one per Gradle version) are
+/// pruned per entry; Gradle rebuilds them on demand. Only lock files and the
+/// rest of `modules-2` (resolution metadata) are left out.
+fn gradle_cache_roots(caches: &Path) -> Vec<PathBuf> {
+    let dependencies = caches.join("modules-2").join("files-2.1");
+    let Ok(entries) = fs::read_dir(caches) else {
+        return vec![dependencies];
+    };
+
+    let mut roots: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()) && e.file_name() != "modules-2")
+        .map(|e| e.path())
+        .collect();
+    roots.sort();
+    roots.insert(0, dependencies);
+    roots
+}
+
+/// Lists the prunable entries below a cache root
+fn list_entries(root: &Path, granularity: Granularity) -> Vec<PathBuf> {
+    let walker = walkdir::WalkDir::new(root).min_depth(1);
+
+    match granularity {
+        Granularity::Depth(depth) => walker
+            .min_depth(depth)
+            .max_depth(depth)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .collect(),
+        Granularity::Files => walker
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect(),
+        Granularity::Simulators => xcode::unavailable_simulators(root),
+    }
+}
+
+/// Returns true if nothing inside `path` was modified within `min_age_seconds`
+fn is_older_than(path: &Path, min_age_seconds: u64) -> bool {
+    let newest = walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max();
+
+    match newest.map(|m| SystemTime::now().duration_since(m)) {
+        Some(Ok(elapsed)) => elapsed.as_secs() >= min_age_seconds,
+        // Unknown or future timestamps are treated as recent
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_ids_round_trip() {
+        for cache in GlobalCache::ALL {
+            assert_eq!(cache.id().parse(), Ok(*cache));
+        }
+        assert!("bower".parse::<GlobalCache>().is_err());
+    }
+
+    #[test]
+    fn test_list_entries_by_depth() {
+        let root = tempfile::tempdir().unwrap();
+        let registry = root.path().join("index.crates.io");
+        fs::create_dir_all(registry.join("serde-1.0.0").join("src")).unwrap();
+        fs::write(registry.join("serde-1.0.0").join("src").join("lib.rs"), "").unwrap();
+        fs::write(registry.join("rand-0.8.5.crate"), "").unwrap();
+
+        let mut depth = list_entries(root.path(), Granularity::Depth(2));
+        depth.sort();
+        assert_eq!(
+            depth,
+            vec![
+                registry.join("rand-0.8.5.crate"),
+                registry.join("serde-1.0.0")
+            ]
+        );
+
+        let files = list_entries(root.path(), Granularity::Files);
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    fn test_model_entry_labels() {
+        let hub = Path::new("hub");
+        assert_eq!(
+            GlobalCache::HuggingFace.entry_label(&hub.join("models--meta-llama--Llama-3.1-8B")),
+            "meta-llama/Llama-3.1-8B (model)"
+        );
+        assert_eq!(
+            GlobalCache::HuggingFace.entry_label(&hub.join("datasets--squad")),
+            "squad (dataset)"
+        );
+        assert_eq!(
+            GlobalCache::Torch.entry_label(&hub.join("resnet50-0676ba61.pth")),
+            "resnet50-0676ba61.pth"
+        );
+    }
+
+    #[test]
+    fn test_gradle_cache_roots() {
+        let gradle = tempfile::tempdir().unwrap();
+        let caches = gradle.path().join("caches");
+        fs::create_dir_all(caches.join("modules-2").join("files-2.1")).unwrap();
+        fs::create_dir_all(caches.join("modules-2").join("metadata-2.107")).unwrap();
+        fs::create_dir_all(caches.join("transforms-4")).unwrap();
+        fs::create_dir_all(caches.join("8.7")).unwrap();
+        fs::write(caches.join("journal-1.lock"), "").unwrap();
+
+        let roots = gradle_cache_roots(&caches);
+        assert_eq!(
+            roots,
+            vec![
+                caches.join("modules-2").join("files-2.1"),
+                caches.join("8.7"),
+                caches.join("transforms-4"),
+            ]
+        );
+        assert_eq!(
+            GlobalCache::Gradle.granularity(&roots[0]),
+            Granularity::Depth(2)
+        );
+        assert_eq!(
+            GlobalCache::Gradle.granularity(&roots[1]),
+            Granularity::Depth(1)
+        );
+    }
+
+    #[test]
+    fn test_fresh_entries_are_not_stale() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("blob"), "data").unwrap();
+
+        assert!(!is_older_than(root.path(), 3600));
+        assert!(is_older_than(root.path(), 0));
+    }
+}
@@ -0,0 +1,264 @@
+//! Relocated Cargo target directories
+//!
+//! Cargo builds into `target/` next to `Cargo.toml` unless the target directory
+//! is moved with `CARGO_TARGET_DIR` (or `CARGO_BUILD_TARGET_DIR`) or with
+//! `build.target-dir` in a `.cargo/config.toml`. Configuration files are looked
+//! up the same way cargo does: in the project directory and each of its parents,
+//! the closest one winning, then in `$CARGO_HOME`.
+//!
+//! Only a target directory inside the project, or one set in the project's own
+//! `.cargo` folder, belongs to the project. One set by the environment, by
+//! `$CARGO_HOME` or by a parent's config is usually shared by many projects:
+//! it is never cleaned with any of them, and with
+//! [`ScanOptions::shared_targets`](crate::ScanOptions::shared_targets) it is
+//! reported once, as a project of its own.
+
+use std::{
+    collections::BTreeSet,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{CustomRule, Project};
+
+/// Names of cargo configuration files inside a `.cargo` directory, newest first
+const CONFIG_FILES: &[&str] = &["config.toml", "config"];
+
+/// Type name of the projects standing for shared target directories
+const SHARED_TARGET_NAME: &str = "Rust, shared target";
+
+/// A relocated target directory, and whether it belongs to one project alone
+#[derive(Debug, PartialEq, Eq)]
+struct Relocated {
+    dir: PathBuf,
+    owned: bool,
+}
+
+/// Returns the project's own target directory if it is not the default `target/`
+pub(crate) fn target_dir(project_dir: &Path) -> Option<PathBuf> {
+    locate(project_dir)
+        .filter(|relocated| relocated.owned)
+        .map(|relocated| relocated.dir)
+}
+
+/// Returns the target directory the project shares with others, if it builds into one
+pub(crate) fn shared_target_dir(project_dir: &Path) -> Option<PathBuf> {
+    locate(project_dir)
+        .filter(|relocated| !relocated.owned)
+        .map(|relocated| relocated.dir)
+}
+
+/// Returns the project listing a shared target directory, sized and cleaned once
+pub(crate) fn shared_target_project(dir: PathBuf) -> Project {
+    let rule = CustomRule {
+        name: SHARED_TARGET_NAME.to_string(),
+        markers: Vec::new(),
+        artifacts: vec![dir.to_string_lossy().into_owned()],
+    };
+    Project::from_rule(rule, dir)
+}
+
+/// Returns true for the projects made by [`shared_target_project`]
+pub(crate) fn is_shared_target(project: &Project) -> bool {
+    project
+        .custom_rule
+        .as_ref()
+        .is_some_and(|rule| rule.name == SHARED_TARGET_NAME && rule.markers.is_empty())
+}
+
+/// Shared target directories of the Rust projects found by one scan
+///
+/// Clones record into the same set, so every scan worker can add to it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedTargets(Arc<Mutex<BTreeSet<PathBuf>>>);
+
+impl SharedTargets {
+    /// Records the shared target directory of `project`, if it has one
+    pub(crate) fn record(&self, project: &Project) {
+        if let Some(dir) = shared_target_dir(&project.path) {
+            if let Ok(mut dirs) = self.0.lock() {
+                dirs.insert(dir);
+            }
+        }
+    }
+
+    /// Returns the recorded directories that exist, each once
+    pub(crate) fn take(&self) -> Vec<PathBuf> {
+        let dirs = self.0.lock().map(|mut dirs| std::mem::take(&mut *dirs));
+        dirs.unwrap_or_default()
+            .into_iter()
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+}
+
+/// Finds the project's target directory if it is not the default `target/`
+fn locate(project_dir: &Path) -> Option<Relocated> {
+    let env_value =
+        env::var_os("CARGO_TARGET_DIR").or_else(|| env::var_os("CARGO_BUILD_TARGET_DIR"));
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+
+    resolve(project_dir, env_value, cargo_home.as_deref())
+}
+
+/// Resolves the target directory from an environment override and config files
+///
+/// Relative paths from the environment are taken relative to the project, those
+/// from a config file relative to the directory holding its `.cargo` folder.
+fn resolve(
+    project_dir: &Path,
+    env_value: Option<OsString>,
+    cargo_home: Option<&Path>,
+) -> Option<Relocated> {
+    let (target, own_config) = match env_value.filter(|value| !value.is_empty()) {
+        Some(value) => (project_dir.join(value), false),
+        None => configured_target_dir(project_dir, cargo_home)?,
+    };
+
+    let target = normalize(&target);
+    if target == normalize(&project_dir.join("target")) {
+        return None;
+    }
+    let owned = own_config || target.starts_with(normalize(project_dir));
+    Some(Relocated { dir: target, owned })
+}
+
+/// Resolves `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Finds `build.target-dir` in the cargo configuration files that apply to `project_dir`
+///
+/// Also returns whether it was set in the project's own `.cargo` folder.
+fn configured_target_dir(project_dir: &Path, cargo_home: Option<&Path>) -> Option<(PathBuf, bool)> {
+    for dir in project_dir.ancestors() {
+        if let Some(target) = read_target_dir(&dir.join(".cargo"), dir) {
+            return Some((target, dir == project_dir));
+        }
+    }
+
+    // `$CARGO_HOME/config.toml` applies to every project; paths in it are
+    // relative to the parent of `$CARGO_HOME`, as for any other `.cargo` folder
+    let home = cargo_home?;
+    read_target_dir(home, home.parent().unwrap_or(home)).map(|target| (target, false))
+}
+
+/// Reads `build.target-dir` from the config file in `cargo_dir`, if set
+fn read_target_dir(cargo_dir: &Path, base: &Path) -> Option<PathBuf> {
+    let contents = CONFIG_FILES
+        .iter()
+        .find_map(|name| fs::read_to_string(cargo_dir.join(name)).ok())?;
+    let config: toml::Table = contents.parse().ok()?;
+    let target = config.get("build")?.get("target-dir")?.as_str()?;
+
+    Some(base.join(target))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_target_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("workspace").join("app");
+        fs::create_dir_all(&project).unwrap();
+        assert_eq!(resolve(&project, None, None), None);
+
+        // A parent's config applies, relative to the folder holding `.cargo`
+        let cargo_dir = root.path().join("workspace").join(".cargo");
+        fs::create_dir_all(&cargo_dir).unwrap();
+        fs::write(
+            cargo_dir.join("config.toml"),
+            "[build]\ntarget-dir = \"../shared-target\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            resolve(&project, None, None),
+            Some(Relocated {
+                dir: root.path().join("shared-target"),
+                owned: false,
+            })
+        );
+
+        // The closest config wins, and the project's own config owns its target
+        fs::create_dir_all(project.join(".cargo")).unwrap();
+        fs::write(
+            project.join(".cargo").join("config"),
+            "[build]\ntarget-dir = \"/tmp/app-target\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            resolve(&project, None, None),
+            Some(Relocated {
+                dir: PathBuf::from("/tmp/app-target"),
+                owned: true,
+            })
+        );
+
+        // Pointing back at `target/` is the default
+        fs::write(
+            project.join(".cargo").join("config"),
+            "[build]\ntarget-dir = \"target\"\n",
+        )
+        .unwrap();
+        assert_eq!(resolve(&project, None, None), None);
+    }
+
+    #[test]
+    fn test_env_target_dir_wins() {
+        let root = tempfile::tempdir().unwrap();
+        let cargo_home = root.path().join(".cargo");
+        fs::create_dir_all(&cargo_home).unwrap();
+        fs::write(
+            cargo_home.join("config.toml"),
+            "[build]\ntarget-dir = \"/from/config\"\n",
+        )
+        .unwrap();
+        let project = root.path().join("app");
+
+        let shared = |dir: &str| {
+            Some(Relocated {
+                dir: PathBuf::from(dir),
+                owned: false,
+            })
+        };
+        assert_eq!(
+            resolve(&project, None, Some(&cargo_home)),
+            shared("/from/config")
+        );
+        assert_eq!(
+            resolve(&project, Some("/from/env".into()), Some(&cargo_home)),
+            shared("/from/env")
+        );
+
+        // A relative override lands inside each project, which then owns it
+        assert_eq!(
+            resolve(&project, Some("build".into()), Some(&cargo_home)),
+            Some(Relocated {
+                dir: project.join("build"),
+                owned: true,
+            })
+        );
+    }
+}
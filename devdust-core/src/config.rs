@@ -0,0 +1,354 @@
+//! Configuration file support
+//!
+//! Settings are layered: the global file (`~/.config/devdust/config.toml`)
+//! is loaded first, then a project-local `.devdust.toml` overrides it.
+//! Command-line flags are applied on top of the merged result by the frontend.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{parse_age, AgeSource, CustomRule, GitPolicy, ProjectType, TypeOverride};
+
+/// File name of the project-local configuration file
+pub const LOCAL_CONFIG_FILE: &str = ".devdust.toml";
+
+// ============================================================================
+// Configuration Structure
+// ============================================================================
+
+/// Settings loaded from devdust configuration files
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Directories to scan when none are given on the command line
+    pub paths: Vec<PathBuf>,
+    /// Glob patterns for directories to skip while scanning and cleaning
+    pub exclude: Vec<String>,
+    /// Glob patterns for projects that are shown but never cleaned
+    pub protected: Vec<String>,
+    /// Only include projects older than this (e.g., "30d")
+    pub older: Option<String>,
+    /// What the age of a project is measured from
+    pub age_source: Option<AgeSource>,
+    /// Whether to follow symbolic links
+    pub follow_symlinks: Option<bool>,
+    /// Whether to stay on the same filesystem
+    pub same_filesystem: Option<bool>,
+    /// Measure artifact sizes by allocated blocks instead of file lengths
+    pub disk_usage: Option<bool>,
+    /// Count hardlinked files once per link instead of once
+    pub count_links: Option<bool>,
+    /// Also report artifact directories whose project sources are gone
+    pub orphans: Option<bool>,
+    /// Number of worker threads used for scanning
+    pub jobs: Option<usize>,
+    /// Move artifacts to the system trash instead of deleting them
+    pub trash: Option<bool>,
+    /// Move artifacts to the quarantine folder so `devdust undo` can restore them
+    pub quarantine: Option<bool>,
+    /// Where `--relocate` moves artifacts to when it is given no directory
+    pub relocate_to: Option<PathBuf>,
+    /// How often a deletion that failed, e.g. on a briefly locked file, is tried again
+    pub retries: Option<u32>,
+    /// Shell command run in each project before it is cleaned; if it fails, the project is skipped
+    ///
+    /// Only honored in the global file (see [`Config::load`]).
+    pub pre_clean: Option<String>,
+    /// Shell command run in each project after it is cleaned
+    ///
+    /// Only honored in the global file (see [`Config::load`]).
+    pub post_clean: Option<String>,
+    /// Whether to honor `.devdustignore` files
+    pub ignore_files: Option<bool>,
+    /// Whether to skip directories excluded by `.gitignore` and git's other ignore rules
+    pub respect_gitignore: Option<bool>,
+    /// Only clean dependency folders that a lockfile can restore
+    pub require_lockfile: Option<bool>,
+    /// What to do with projects that have uncommitted or unpushed work
+    pub git: Option<GitPolicy>,
+    /// Per-project-type overrides, keyed by type identifier (e.g., `[types.node]`)
+    pub types: BTreeMap<String, TypeConfig>,
+    /// User-defined project types (`[[rules]]` tables)
+    pub rules: Vec<CustomRule>,
+}
+
+/// Per-project-type section of the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeConfig {
+    /// Set to false to ignore projects of this type
+    pub enabled: Option<bool>,
+    /// Age filter for this type only (e.g., "90d")
+    pub older: Option<String>,
+}
+
+impl Config {
+    /// Returns the path of the global configuration file
+    ///
+    /// Honors `XDG_CONFIG_HOME` and falls back to `~/.config`.
+    pub fn global_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+
+        Some(base.join("devdust").join("config.toml"))
+    }
+
+    /// Loads the global configuration merged with the local one found in `dir`
+    ///
+    /// Missing files are not an error; an empty configuration is returned instead.
+    /// Hooks in the local file are ignored, so running devdust in a checked-out
+    /// repository can't run commands it brought along.
+    pub fn load(dir: &Path) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+
+        if let Some(global) = Self::global_path() {
+            if global.is_file() {
+                config = config.merge(Self::from_file(&global)?);
+            }
+        }
+
+        let local = dir.join(LOCAL_CONFIG_FILE);
+        if local.is_file() {
+            let local = Self {
+                pre_clean: None,
+                post_clean: None,
+                ..Self::from_file(&local)?
+            };
+            config = config.merge(local);
+        }
+
+        Ok(config)
+    }
+
+    /// Reads and parses a single configuration file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+
+        let mut config: Self =
+            toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+
+        // Allow `~/` in paths so the same file works across machines
+        for path in config.paths.iter_mut().chain(&mut config.relocate_to) {
+            *path = expand_tilde(path);
+        }
+        for pattern in config.exclude.iter_mut().chain(&mut config.protected) {
+            *pattern = expand_tilde(Path::new(pattern))
+                .to_string_lossy()
+                .into_owned();
+        }
+
+        Ok(config)
+    }
+
+    /// Merges another configuration layer on top of this one
+    ///
+    /// Values set in `other` win; exclude and protected lists are combined.
+    pub fn merge(mut self, other: Self) -> Self {
+        if !other.paths.is_empty() {
+            self.paths = other.paths;
+        }
+        self.exclude.extend(other.exclude);
+        self.protected.extend(other.protected);
+        self.older = other.older.or(self.older);
+        self.age_source = other.age_source.or(self.age_source);
+        self.follow_symlinks = other.follow_symlinks.or(self.follow_symlinks);
+        self.same_filesystem = other.same_filesystem.or(self.same_filesystem);
+        self.disk_usage = other.disk_usage.or(self.disk_usage);
+        self.count_links = other.count_links.or(self.count_links);
+        self.orphans = other.orphans.or(self.orphans);
+        self.jobs = other.jobs.or(self.jobs);
+        self.trash = other.trash.or(self.trash);
+        self.quarantine = other.quarantine.or(self.quarantine);
+        self.relocate_to = other.relocate_to.or(self.relocate_to);
+        self.retries = other.retries.or(self.retries);
+        self.pre_clean = other.pre_clean.or(self.pre_clean);
+        self.post_clean = other.post_clean.or(self.post_clean);
+        self.ignore_files = other.ignore_files.or(self.ignore_files);
+        self.respect_gitignore = other.respect_gitignore.or(self.respect_gitignore);
+        self.require_lockfile = other.require_lockfile.or(self.require_lockfile);
+        self.git = other.git.or(self.git);
+
+        self.rules.extend(other.rules);
+
+        for (name, type_config) in other.types {
+            let entry = self.types.entry(name).or_default();
+            entry.enabled = type_config.enabled.or(entry.enabled);
+            entry.older = type_config.older.or(entry.older.take());
+        }
+
+        self
+    }
+
+    /// Resolves the `[types.*]` sections into scan overrides
+    pub fn type_overrides(&self) -> Result<HashMap<ProjectType, TypeOverride>, ConfigError> {
+        let mut overrides = HashMap::new();
+
+        for (name, type_config) in &self.types {
+            let project_type = name
+                .parse::<ProjectType>()
+                .map_err(ConfigError::InvalidValue)?;
+
+            let min_age_seconds = type_config
+                .older
+                .as_deref()
+                .map(parse_age)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidValue(format!("types.{}.older: {}", name, e)))?;
+
+            overrides.insert(
+                project_type,
+                TypeOverride {
+                    enabled: type_config.enabled.unwrap_or(true),
+                    min_age_seconds,
+                },
+            );
+        }
+
+        Ok(overrides)
+    }
+}
+
+/// Replaces a leading `~` with the user's home directory
+fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Errors that can occur while loading configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read
+    Io(PathBuf, std::io::Error),
+    /// The file is not valid TOML or has unexpected keys
+    Parse(PathBuf, toml::de::Error),
+    /// A value could not be interpreted
+    InvalidValue(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Cannot read config {}: {}", path.display(), e),
+            Self::Parse(path, e) => write!(f, "Invalid config {}: {}", path.display(), e),
+            Self::InvalidValue(msg) => write!(f, "Invalid config value: {}", msg),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Config {
+        toml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_merge_layers() {
+        let global = parse(
+            r#"
+            paths = ["~/code"]
+            exclude = ["archive"]
+            older = "30d"
+            pre_clean = "docker compose down"
+
+            [types.node]
+            older = "7d"
+            "#,
+        );
+        let local = parse(
+            r#"
+            exclude = ["vendor-src"]
+            protected = ["~/code/production-app"]
+            follow_symlinks = true
+            git = "skip"
+            age_source = "commit"
+            disk_usage = true
+            orphans = true
+
+            [types.node]
+            enabled = false
+            "#,
+        );
+
+        let merged = global.merge(local);
+        assert_eq!(merged.paths, vec![PathBuf::from("~/code")]);
+        assert_eq!(merged.exclude, vec!["archive", "vendor-src"]);
+        assert_eq!(merged.protected, vec!["~/code/production-app"]);
+        assert_eq!(merged.older.as_deref(), Some("30d"));
+        assert_eq!(merged.follow_symlinks, Some(true));
+        assert_eq!(merged.git, Some(GitPolicy::Skip));
+        assert_eq!(merged.age_source, Some(AgeSource::Commit));
+        assert_eq!(merged.disk_usage, Some(true));
+        assert_eq!(merged.orphans, Some(true));
+        assert_eq!(merged.pre_clean.as_deref(), Some("docker compose down"));
+        assert_eq!(merged.types["node"].enabled, Some(false));
+        assert_eq!(merged.types["node"].older.as_deref(), Some("7d"));
+    }
+
+    #[test]
+    fn test_type_overrides() {
+        let config = parse(
+            r#"
+            [types.rust]
+            older = "2w"
+
+            [types.unity]
+            enabled = false
+            "#,
+        );
+
+        let overrides = config.type_overrides().unwrap();
+        assert_eq!(
+            overrides[&ProjectType::Rust].min_age_seconds,
+            Some(1_209_600)
+        );
+        assert!(!overrides[&ProjectType::Unity].enabled);
+
+        let invalid = parse("[types.cobol]\nenabled = true");
+        assert!(invalid.type_overrides().is_err());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let config = parse(
+            r#"
+            [[rules]]
+            name = "Cabal"
+            markers = ["*.cabal"]
+            artifacts = ["dist-newstyle"]
+            "#,
+        );
+
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].name, "Cabal");
+        assert_eq!(config.rules[0].artifacts, vec!["dist-newstyle"]);
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        assert!(toml::from_str::<Config>("colour = true").is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Docker and Podman disk usage
+//!
+//! Image layers, build caches and stopped containers often take up more space
+//! than all project artifacts together, but live in the engine's own storage
+//! where walking directories is neither possible (root-owned, inside a VM on
+//! macOS and Windows) nor safe. Usage is therefore read from `system df` and
+//! space is reclaimed with the engine's own `prune` commands. Volumes are
+//! reported but never pruned, since they may hold databases and other state.
+
+use std::{
+    fmt, io,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+/// A container engine, driven through its command-line interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+/// A kind of data an engine keeps on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerResource {
+    /// Images; pruning removes only dangling (untagged) ones
+    Images,
+    /// Containers; pruning removes stopped ones
+    Containers,
+    /// Named and anonymous volumes; never pruned
+    Volumes,
+    /// BuildKit cache
+    BuildCache,
+}
+
+/// Space used by one kind of resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContainerUsage {
+    pub resource: ContainerResource,
+    /// Total size in bytes
+    pub size: u64,
+    /// Bytes not used by any running container, as estimated by the engine
+    pub reclaimable: u64,
+}
+
+impl ContainerEngine {
+    /// All supported engines
+    pub const ALL: &'static [ContainerEngine] = &[Self::Docker, Self::Podman];
+
+    /// Returns the short identifier used on the command line
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+
+    /// Returns the human-readable name of the engine
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Docker => "Docker",
+            Self::Podman => "Podman",
+        }
+    }
+
+    /// Reports how much space each kind of resource takes
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the engine's CLI is not
+    /// installed, and with the engine's message if its daemon is not running.
+    pub fn usage(&self) -> io::Result<Vec<ContainerUsage>> {
+        let output = self.run(&[
+            "system",
+            "df",
+            "--format",
+            "{{.Type}}\t{{.Size}}\t{{.Reclaimable}}",
+        ])?;
+        Ok(parse_df(&output))
+    }
+
+    /// Prunes unused data of one kind and returns the bytes freed
+    ///
+    /// Removes the build cache, dangling images or stopped containers; the
+    /// freed space is measured by comparing `system df` before and after.
+    pub fn prune(&self, resource: ContainerResource) -> io::Result<u64> {
+        let args: &[&str] = match resource {
+            ContainerResource::Images => &["image", "prune", "--force"],
+            ContainerResource::Containers => &["container", "prune", "--force"],
+            ContainerResource::BuildCache => &["builder", "prune", "--force"],
+            ContainerResource::Volumes => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "volumes may hold data and are never pruned",
+                ))
+            }
+        };
+
+        let size_of = |usage: Vec<ContainerUsage>| {
+            usage
+                .iter()
+                .find(|u| u.resource == resource)
+                .map_or(0, |u| u.size)
+        };
+        let before = size_of(self.usage()?);
+        self.run(args)?;
+        let after = size_of(self.usage()?);
+        Ok(before.saturating_sub(after))
+    }
+
+    /// Runs the engine's CLI and returns its standard output
+    fn run(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(self.id())
+            .args(args)
+            .stdin(Stdio::null())
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            let subcommand: Vec<&str> = args
+                .iter()
+                .copied()
+                .take_while(|arg| !arg.starts_with('-'))
+                .collect();
+            return Err(io::Error::other(format!(
+                "`{} {}` failed: {}",
+                self.id(),
+                subcommand.join(" "),
+                message.trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl fmt::Display for ContainerEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ContainerEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|e| e.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown container engine: {}", s))
+    }
+}
+
+impl ContainerResource {
+    /// Returns the human-readable name of the resource
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Images => "Images",
+            Self::Containers => "Containers",
+            Self::Volumes => "Volumes",
+            Self::BuildCache => "Build cache",
+        }
+    }
+
+    /// Returns what pruning removes, or `None` for resources that are never pruned
+    pub fn prune_target(&self) -> Option<&'static str> {
+        match self {
+            Self::Images => Some("dangling images"),
+            Self::Containers => Some("stopped containers"),
+            Self::BuildCache => Some("build cache"),
+            Self::Volumes => None,
+        }
+    }
+
+    /// Maps a `Type` column of `system df` to a resource
+    fn from_df_type(name: &str) -> Option<Self> {
+        match name.trim() {
+            "Images" => Some(Self::Images),
+            "Containers" => Some(Self::Containers),
+            "Local Volumes" | "Volumes" => Some(Self::Volumes),
+            "Build Cache" => Some(Self::BuildCache),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ContainerResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parses `Type<TAB>Size<TAB>Reclaimable` lines, skipping unknown types
+fn parse_df(output: &str) -> Vec<ContainerUsage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let resource = ContainerResource::from_df_type(fields.next()?)?;
+            let size = parse_engine_size(fields.next()?)?;
+            // e.g. "1.2GB (52%)"
+            let reclaimable = fields.next()?.split(" (").next()?;
+            Some(ContainerUsage {
+                resource,
+                size,
+                reclaimable: parse_engine_size(reclaimable)?,
+            })
+        })
+        .collect()
+}
+
+/// Parses sizes such as `1.21GB` or `512kB`, which both engines print in SI units
+fn parse_engine_size(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let exponent = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "KB" => 1,
+        "MB" => 2,
+        "GB" => 3,
+        "TB" => 4,
+        "PB" => 5,
+        _ => return None,
+    };
+    Some((number * 1000f64.powi(exponent)) as u64)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df() {
+        let output = "Images\t12.5GB\t8.1GB (64%)\n\
+                      Containers\t340kB\t0B (0%)\n\
+                      Local Volumes\t2.1GB\t1GB (47%)\n\
+                      Build Cache\t4.7GB\t4.7GB\n";
+        let usage = parse_df(output);
+
+        assert_eq!(usage.len(), 4);
+        assert_eq!(usage[0].resource, ContainerResource::Images);
+        assert_eq!(usage[0].size, 12_500_000_000);
+        assert_eq!(usage[0].reclaimable, 8_100_000_000);
+        assert_eq!(usage[1].size, 340_000);
+        assert_eq!(usage[2].resource, ContainerResource::Volumes);
+        assert_eq!(usage[3].reclaimable, 4_700_000_000);
+        assert!(usage[2].resource.prune_target().is_none());
+    }
+
+    #[test]
+    fn test_engine_ids() {
+        assert_eq!("podman".parse(), Ok(ContainerEngine::Podman));
+        assert!("containerd".parse::<ContainerEngine>().is_err());
+    }
+}
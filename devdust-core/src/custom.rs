@@ -0,0 +1,81 @@
+//! User-defined project types
+//!
+//! Custom rules are declared in the configuration file as `[[rules]]` tables
+//! and are checked before the built-in detectors, so they can also be used to
+//! take over directories a built-in type would otherwise claim.
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::matches_wildcard;
+
+/// A project type defined by the user: marker files plus artifact directories
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    /// Display name shown in place of a built-in type name
+    pub name: String,
+    /// File names marking a project root; `*` and `?` wildcards are allowed
+    pub markers: Vec<String>,
+    /// Artifact directories relative to the project root
+    pub artifacts: Vec<String>,
+}
+
+impl CustomRule {
+    /// Returns true if the directory contains a file matching one of the markers
+    pub fn matches(&self, dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+
+        entries.filter_map(|e| e.ok()).any(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            self.markers
+                .iter()
+                .any(|marker| matches_wildcard(marker, &file_name))
+        })
+    }
+}
+
+/// Returns the first rule that matches the directory
+#[cfg_attr(not(feature = "fs"), allow(dead_code))]
+pub(crate) fn detect<'a>(dir: &Path, rules: &'a [CustomRule]) -> Option<&'a CustomRule> {
+    rules.iter().find(|rule| rule.matches(dir))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_matches_marker_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.cabal"), "").unwrap();
+
+        let cabal = CustomRule {
+            name: "Cabal".to_string(),
+            markers: vec!["*.cabal".to_string()],
+            artifacts: vec!["dist-newstyle".to_string()],
+        };
+        let dune = CustomRule {
+            name: "Dune".to_string(),
+            markers: vec!["dune-project".to_string()],
+            artifacts: vec!["_build".to_string()],
+        };
+
+        assert!(cabal.matches(dir.path()));
+        assert!(!dune.matches(dir.path()));
+
+        let rules = [dune, cabal];
+        assert_eq!(
+            detect(dir.path(), &rules).map(|r| r.name.as_str()),
+            Some("Cabal")
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Apparent and on-disk sizes
+//!
+//! Summing file lengths overstates what cleaning frees for sparse files,
+//! compressed NTFS files and APFS clones, and understates it for trees of tiny
+//! files that each occupy a whole block. The on-disk size counts the space
+//! actually allocated instead: `st_blocks` on Unix and the compressed file size
+//! on Windows. Elsewhere it falls back to the file length.
+//!
+//! Files hardlinked several times below a directory (pnpm stores, ccache) are
+//! counted once unless [`ScanOptions::dedupe_hardlinks`] is turned off. Links are
+//! recognized by their `(device, inode)` pair, which is only available on Unix.
+//!
+//! For a quick overview, sizes can also be estimated from a sample: artifact
+//! trees such as `node_modules` hold thousands of similar subdirectories, and a
+//! few hundred of them, spread evenly, predict the total well.
+
+use std::{cell::Cell, collections::HashSet, fs, iter::Sum, ops::Add, path::Path};
+
+use crate::ScanOptions;
+
+/// Directory entries an estimate looks at, give or take the listings on its way down
+const ESTIMATE_BUDGET: usize = 5_000;
+
+/// Fewest entries each sampled subdirectory gets to look at
+const MIN_SAMPLE_BUDGET: usize = 50;
+
+thread_local! {
+    /// Files whose metadata this thread has read to size directories
+    static FILES_STATED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Returns how many files the calling thread has stat'ed for sizes so far
+pub(crate) fn files_stated() -> u64 {
+    FILES_STATED.get()
+}
+
+fn count_stat() {
+    FILES_STATED.set(FILES_STATED.get() + 1);
+}
+
+/// Size of a directory tree, both by file length and by allocated space
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Sum of file lengths, as reported by `ls -l`
+    pub apparent: u64,
+    /// Space allocated on disk, as reported by `du`
+    pub on_disk: u64,
+}
+
+impl DiskUsage {
+    /// Returns the size selected by [`ScanOptions::disk_usage`]
+    pub fn size(&self, options: &ScanOptions) -> u64 {
+        if options.disk_usage {
+            self.on_disk
+        } else {
+            self.apparent
+        }
+    }
+}
+
+impl Add for DiskUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            apparent: self.apparent + other.apparent,
+            on_disk: self.on_disk + other.on_disk,
+        }
+    }
+}
+
+impl Sum for DiskUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Calculates the apparent and on-disk size of a directory in one walk
+pub fn calculate_disk_usage<P: AsRef<Path>>(path: P, options: &ScanOptions) -> DiskUsage {
+    files(path.as_ref(), options)
+        .map(|(entry, metadata)| DiskUsage {
+            apparent: metadata.len(),
+            on_disk: platform::allocated_size(entry.path(), &metadata),
+        })
+        .sum()
+}
+
+/// Returns the space allocated on disk for a file, as counted by [`calculate_disk_usage`]
+pub fn allocated_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    platform::allocated_size(path, metadata)
+}
+
+/// Estimates the size of `path` from evenly spread subdirectories
+///
+/// Honors `options.disk_usage`; hardlinks are counted each time.
+pub(crate) fn estimate(path: &Path, options: &ScanOptions) -> u64 {
+    estimate_within(path, options, ESTIMATE_BUDGET)
+}
+
+/// Counts the files in `dir` and walks as many subdirectories as `budget` allows,
+/// scaling their total up to all of them
+fn estimate_within(dir: &Path, options: &ScanOptions, budget: usize) -> u64 {
+    let size_of = |path: &Path, metadata: &fs::Metadata| {
+        if options.disk_usage {
+            platform::allocated_size(path, metadata)
+        } else {
+            metadata.len()
+        }
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        // Single-file artifacts
+        return fs::symlink_metadata(dir)
+            .ok()
+            .filter(fs::Metadata::is_file)
+            .map_or(0, |metadata| size_of(dir, &metadata));
+    };
+
+    let mut size = 0;
+    let mut listed = 0;
+    let mut subdirs = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        listed += 1;
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => subdirs.push(entry.path()),
+            Ok(file_type) if file_type.is_file() => {
+                count_stat();
+                if let Ok(metadata) = entry.metadata() {
+                    size += size_of(&entry.path(), &metadata);
+                }
+            }
+            _ => {}
+        }
+    }
+    if subdirs.is_empty() {
+        return size;
+    }
+
+    // Sorted, so the same tree always gives the same estimate
+    subdirs.sort();
+    let budget = budget.saturating_sub(listed);
+    let sampled = (budget / MIN_SAMPLE_BUDGET).clamp(1, subdirs.len());
+    let share = budget / sampled;
+    let sampled_size: u64 = (0..sampled)
+        .map(|i| estimate_within(&subdirs[i * subdirs.len() / sampled], options, share))
+        .sum();
+    size + (u128::from(sampled_size) * subdirs.len() as u128 / sampled as u128) as u64
+}
+
+/// Bytes, files and directories below a path, as counted before removing it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tally {
+    /// Sum of file lengths, each hardlinked file counted once
+    pub bytes: u64,
+    /// Files and links
+    pub files: u64,
+    /// Directories, `path` itself included
+    pub dirs: u64,
+}
+
+/// Counts what removing `path` removes, without following links
+pub(crate) fn tally(path: &Path) -> Tally {
+    let mut tally = Tally::default();
+    let mut seen = HashSet::new();
+    for entry in walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            tally.dirs += 1;
+            continue;
+        }
+        tally.files += 1;
+        if file_type.is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                if platform::link_id(&metadata).is_none_or(|id| seen.insert(id)) {
+                    tally.bytes += metadata.len();
+                }
+            }
+        }
+    }
+    tally
+}
+
+/// Yields every file below `path` with its metadata, each hardlinked file only
+/// once if `options.dedupe_hardlinks` is set
+pub(crate) fn files(
+    path: &Path,
+    options: &ScanOptions,
+) -> impl Iterator<Item = (walkdir::DirEntry, fs::Metadata)> {
+    let walker = walkdir::WalkDir::new(path)
+        .follow_links(options.follow_symlinks)
+        .same_file_system(options.same_filesystem);
+    let dedupe = options.dedupe_hardlinks;
+    let mut seen = HashSet::new();
+
+    walker
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            count_stat();
+            let metadata = e.metadata().ok()?;
+            Some((e, metadata))
+        })
+        .filter(move |(_, metadata)| {
+            !dedupe || platform::link_id(metadata).is_none_or(|id| seen.insert(id))
+        })
+}
+
+// ============================================================================
+// Platform Backends
+// ============================================================================
+
+#[cfg(unix)]
+mod platform {
+    use std::{fs, os::unix::fs::MetadataExt, path::Path};
+
+    /// `st_blocks` is always counted in 512-byte units, whatever the block size
+    pub(super) fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+        metadata.blocks() * 512
+    }
+
+    /// Identifies files with more than one link; others can't be seen twice
+    pub(super) fn link_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+        (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{fs, os::windows::ffi::OsStrExt, path::Path};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCompressedFileSizeW(file_name: *const u16, file_size_high: *mut u32) -> u32;
+    }
+
+    /// Asks for the compressed size, which is the allocated size of sparse and
+    /// compressed files and the length of everything else
+    pub(super) fn allocated_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut high = 0u32;
+        // SAFETY: `wide` is NUL-terminated and `high` is a valid out pointer
+        let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+
+        // INVALID_FILE_SIZE; a real size with these low bits is too rare to matter
+        if low == u32::MAX {
+            metadata.len()
+        } else {
+            (u64::from(high) << 32) | u64::from(low)
+        }
+    }
+
+    /// File indexes are not exposed by `std` on stable, so links are counted each time
+    pub(super) fn link_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+        None
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::{fs, path::Path};
+
+    pub(super) fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+        metadata.len()
+    }
+
+    pub(super) fn link_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+        None
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate_samples_subdirectories() {
+        let root = tempfile::tempdir().unwrap();
+        let node_modules = root.path().join("node_modules");
+        for package in 0..40 {
+            let dir = node_modules.join(format!("pkg-{:02}", package));
+            fs::create_dir_all(dir.join("lib")).unwrap();
+            fs::write(dir.join("package.json"), [b' '; 100]).unwrap();
+            fs::write(dir.join("lib").join("index.js"), [b' '; 1000]).unwrap();
+        }
+        fs::write(node_modules.join(".package-lock.json"), [b' '; 10]).unwrap();
+
+        let options = ScanOptions::default();
+        let exact = crate::calculate_directory_size(&node_modules, &options);
+        assert_eq!(exact, 40 * 1100 + 10);
+        // Ten packages are enough for identical ones
+        assert_eq!(estimate_within(&node_modules, &options, 500), exact);
+        assert_eq!(
+            estimate(&node_modules.join(".package-lock.json"), &options),
+            10
+        );
+
+        let options = ScanOptions::builder().estimate_sizes(true).build();
+        assert_eq!(
+            crate::calculate_directory_size(&node_modules, &options),
+            exact
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sparse_file_takes_less_space_on_disk() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("target");
+        fs::create_dir(&target).unwrap();
+        let file = fs::File::create(target.join("sparse.bin")).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+        fs::write(target.join("small.txt"), "x").unwrap();
+
+        let usage = calculate_disk_usage(&target, &ScanOptions::default());
+        assert_eq!(usage.apparent, 64 * 1024 * 1024 + 1);
+        assert!(usage.on_disk < usage.apparent);
+
+        let options = ScanOptions::builder().disk_usage(true).build();
+        assert_eq!(usage.size(&options), usage.on_disk);
+        assert_eq!(
+            crate::calculate_directory_size(&target, &options),
+            usage.on_disk
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hardlinks_counted_once() {
+        let root = tempfile::tempdir().unwrap();
+        let node_modules = root.path().join("node_modules");
+        fs::create_dir_all(node_modules.join(".pnpm")).unwrap();
+        let original = node_modules.join(".pnpm").join("index.js");
+        fs::write(&original, [0u8; 1000]).unwrap();
+        fs::hard_link(&original, node_modules.join("a.js")).unwrap();
+        fs::hard_link(&original, node_modules.join("b.js")).unwrap();
+
+        let options = ScanOptions::default();
+        assert_eq!(
+            crate::calculate_directory_size(&node_modules, &options),
+            1000
+        );
+        assert_eq!(calculate_disk_usage(&node_modules, &options).apparent, 1000);
+
+        let options = ScanOptions::builder().dedupe_hardlinks(false).build();
+        assert_eq!(
+            crate::calculate_directory_size(&node_modules, &options),
+            3000
+        );
+    }
+}
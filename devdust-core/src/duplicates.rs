@@ -0,0 +1,231 @@
+//! npm packages installed identically in several projects
+//!
+//! Every npm or Yarn project keeps its own copy of each dependency, so the same
+//! release of `typescript` or `@babel/core` may sit in dozens of `node_modules`
+//! folders. Copies are grouped by name, version and a fingerprint of their file
+//! names and lengths, so patched or differently built copies are kept apart.
+//! pnpm links packages from a shared store instead; its symlinked packages and
+//! the `.pnpm` folder are not counted.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{Project, ProjectType};
+
+/// A package release found in more than one project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicatePackage {
+    /// Package name, including its scope (`@babel/core`)
+    pub name: String,
+    /// Installed version
+    pub version: String,
+    /// Directories holding a copy, possibly several per project
+    pub copies: Vec<PathBuf>,
+    /// Projects holding at least one copy
+    pub projects: Vec<PathBuf>,
+    /// Size of one copy in bytes (file lengths)
+    pub size: u64,
+}
+
+impl DuplicatePackage {
+    /// Space taken by all copies together
+    pub fn total_size(&self) -> u64 {
+        self.size * self.copies.len() as u64
+    }
+
+    /// Space a shared package store would save, keeping a single copy
+    pub fn reclaimable_size(&self) -> u64 {
+        self.size * (self.copies.len() as u64).saturating_sub(1)
+    }
+}
+
+/// An installed package read from its `package.json`
+struct Installed {
+    name: String,
+    version: String,
+    fingerprint: u64,
+    size: u64,
+    path: PathBuf,
+    project: PathBuf,
+}
+
+/// Finds packages installed identically in the `node_modules` of several projects
+///
+/// Projects other than Node.js ones are skipped. The largest total comes first.
+pub fn find_duplicate_packages(projects: &[Project]) -> Vec<DuplicatePackage> {
+    let installed: Vec<Installed> = projects
+        .par_iter()
+        .filter(|project| project.project_types().contains(&ProjectType::Node))
+        .flat_map_iter(|project| {
+            let mut packages = Vec::new();
+            collect_packages(&project.path.join("node_modules"), &mut packages);
+            packages.into_iter().filter_map(|dir| {
+                let (name, version) = read_manifest(&dir)?;
+                let (fingerprint, size) = fingerprint(&dir);
+                Some(Installed {
+                    name,
+                    version,
+                    fingerprint,
+                    size,
+                    path: dir,
+                    project: project.path.clone(),
+                })
+            })
+        })
+        .collect();
+
+    let mut groups: HashMap<(String, String, u64), DuplicatePackage> = HashMap::new();
+    for package in installed {
+        let key = (package.name, package.version, package.fingerprint);
+        let group = groups
+            .entry(key.clone())
+            .or_insert_with(|| DuplicatePackage {
+                name: key.0,
+                version: key.1,
+                copies: Vec::new(),
+                projects: Vec::new(),
+                size: package.size,
+            });
+        group.copies.push(package.path);
+        if !group.projects.contains(&package.project) {
+            group.projects.push(package.project);
+        }
+    }
+
+    let mut duplicates: Vec<DuplicatePackage> = groups
+        .into_values()
+        .filter(|group| group.projects.len() > 1)
+        .map(|mut group| {
+            group.copies.sort();
+            group.projects.sort();
+            group
+        })
+        .collect();
+    duplicates.sort_by(|a, b| {
+        b.total_size()
+            .cmp(&a.total_size())
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.version.cmp(&b.version))
+    });
+    duplicates
+}
+
+/// Collects the package directories in `node_modules`, including nested ones
+fn collect_packages(node_modules: &Path, packages: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(node_modules) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        // Symlinks are workspace packages or links into pnpm's store
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // .bin, .cache, .pnpm and friends
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            collect_packages(&entry.path(), packages);
+            continue;
+        }
+
+        let dir = entry.path();
+        // Versions that conflict with the top level are installed below their dependent
+        collect_packages(&dir.join("node_modules"), packages);
+        packages.push(dir);
+    }
+}
+
+/// Reads `name` and `version` from a package's `package.json`
+fn read_manifest(dir: &Path) -> Option<(String, String)> {
+    let contents = fs::read_to_string(dir.join("package.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    let field = |key: &str| manifest.get(key)?.as_str().map(String::from);
+    Some((field("name")?, field("version")?))
+}
+
+/// Hashes the relative paths and lengths of a package's files, and sums the lengths
+///
+/// Nested `node_modules` are left out; they are packages of their own.
+fn fingerprint(dir: &Path) -> (u64, u64) {
+    let mut files: Vec<(PathBuf, u64)> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || e.file_name() != "node_modules")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let len = e.metadata().ok()?.len();
+            let relative = e.path().strip_prefix(dir).ok()?.to_path_buf();
+            Some((relative, len))
+        })
+        .collect();
+    files.sort();
+
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    let size = files.iter().map(|(_, len)| len).sum();
+    (hasher.finish(), size)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Installs a package with one file of `len` bytes into `project`
+    fn install(project: &Path, name: &str, version: &str, len: usize) {
+        let dir = project.join("node_modules").join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            format!(r#"{{"name": "{name}", "version": "{version}"}}"#),
+        )
+        .unwrap();
+        fs::write(dir.join("index.js"), vec![b'x'; len]).unwrap();
+    }
+
+    #[test]
+    fn test_find_duplicate_packages() {
+        let root = tempfile::tempdir().unwrap();
+        let projects: Vec<Project> = ["web", "docs", "admin"]
+            .iter()
+            .map(|name| {
+                let dir = root.path().join(name);
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(dir.join("package.json"), "{}").unwrap();
+                install(&dir, "typescript", "5.4.5", 1000);
+                Project::detect(&dir, &[]).unwrap()
+            })
+            .collect();
+        install(&projects[0].path, "@babel/core", "7.24.0", 100);
+        install(&projects[1].path, "@babel/core", "7.24.0", 100);
+        // Same release, patched locally
+        install(&projects[2].path, "@babel/core", "7.24.0", 120);
+        install(&projects[2].path, "left-pad", "1.3.0", 10);
+
+        let duplicates = find_duplicate_packages(&projects);
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].name, "typescript");
+        assert_eq!(duplicates[0].projects.len(), 3);
+        let typescript_size = duplicates[0].size;
+        assert_eq!(duplicates[0].total_size(), typescript_size * 3);
+        assert_eq!(duplicates[0].reclaimable_size(), typescript_size * 2);
+        assert_eq!(duplicates[1].name, "@babel/core");
+        assert_eq!(
+            duplicates[1].projects,
+            vec![projects[1].path.clone(), projects[0].path.clone()]
+        );
+    }
+}
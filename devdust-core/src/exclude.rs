@@ -0,0 +1,109 @@
+//! Exclude patterns
+//!
+//! Patterns without a path separator match directory names anywhere in the
+//! tree (`node_modules`, `*-backup`). Patterns with a separator match full
+//! paths: absolute ones and ones starting with `**` are used as-is, other
+//! relative ones are anchored at the current directory.
+
+use std::path::{self, Path};
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// Compiled form of `ScanOptions::exclude` or `ScanOptions::protected`
+#[derive(Debug, Clone)]
+pub(crate) struct ExcludeSet {
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl ExcludeSet {
+    /// Compiles the patterns, failing on the first invalid one
+    pub(crate) fn new(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            if pattern.contains('/') || pattern.contains(path::MAIN_SEPARATOR) {
+                let anchored = anchor(pattern);
+                paths.add(
+                    GlobBuilder::new(&anchored)
+                        .literal_separator(true)
+                        .build()?,
+                );
+            } else {
+                names.add(Glob::new(pattern)?);
+            }
+        }
+
+        Ok(Self {
+            names: names.build()?,
+            paths: paths.build()?,
+        })
+    }
+
+    /// Returns true if the directory matches any pattern
+    pub(crate) fn is_match(&self, path: &Path) -> bool {
+        if path
+            .file_name()
+            .is_some_and(|name| self.names.is_match(name))
+        {
+            return true;
+        }
+        if self.paths.is_empty() {
+            return false;
+        }
+        path::absolute(path).is_ok_and(|abs| self.paths.is_match(abs))
+    }
+
+    /// Returns true if no patterns were given
+    pub(crate) fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.paths.is_empty()
+    }
+}
+
+/// Anchors a relative path pattern at the current directory
+fn anchor(pattern: &str) -> String {
+    if pattern.starts_with("**") || Path::new(pattern).is_absolute() {
+        return pattern.to_string();
+    }
+    match path::absolute(pattern) {
+        Ok(abs) => abs.to_string_lossy().into_owned(),
+        Err(_) => pattern.to_string(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(patterns: &[&str]) -> ExcludeSet {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        ExcludeSet::new(&patterns).unwrap()
+    }
+
+    #[test]
+    fn test_name_patterns() {
+        let exclude = set(&["node_modules", "*-backup"]);
+        assert!(exclude.is_match(Path::new("/code/web/node_modules")));
+        assert!(exclude.is_match(Path::new("/code/site-backup")));
+        assert!(!exclude.is_match(Path::new("/code/site")));
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        let exclude = set(&["/srv/**", "**/clients/*/legacy"]);
+        assert!(exclude.is_match(Path::new("/srv/app")));
+        assert!(exclude.is_match(Path::new("/home/me/clients/acme/legacy")));
+        assert!(!exclude.is_match(Path::new("/home/me/clients/acme/nested/legacy")));
+        assert!(!exclude.is_match(Path::new("/home/me/srv")));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(ExcludeSet::new(&["[unclosed".to_string()]).is_err());
+    }
+}
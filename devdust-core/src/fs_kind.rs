@@ -0,0 +1,245 @@
+//! Recognizing network and FUSE filesystems
+//!
+//! Deleting a large `node_modules` over NFS or SMB is slow, and the share may
+//! be used by other machines that still need the artifacts. Frontends use
+//! [`fs_kind`] to warn before scanning or cleaning there. The mount table is
+//! read from `/proc/self/mountinfo` on Linux and from `mount` on macOS;
+//! elsewhere the kind is [`FsKind::Unknown`].
+
+use std::{fmt, path::Path};
+
+/// What kind of filesystem a path is stored on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsKind {
+    /// A local disk (or an in-memory filesystem)
+    Local,
+    /// A network share such as NFS or SMB
+    Network,
+    /// A filesystem in userspace, often backed by a remote server (sshfs, rclone)
+    Fuse,
+    /// The mount table could not be read
+    Unknown,
+}
+
+impl FsKind {
+    /// Returns true for filesystems where deleting may be slow or affect shared storage
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Self::Network | Self::Fuse)
+    }
+}
+
+impl fmt::Display for FsKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Local => "local",
+            Self::Network => "network",
+            Self::Fuse => "FUSE",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// Returns the kind of filesystem `path` is stored on
+pub fn fs_kind(path: &Path) -> FsKind {
+    let Ok(path) = path.canonicalize() else {
+        return FsKind::Unknown;
+    };
+
+    match platform::mount_type(&path) {
+        Some(fs_type) => classify(&fs_type),
+        None => FsKind::Unknown,
+    }
+}
+
+/// Returns the ID of the device `path` is stored on, following links
+///
+/// Only Unix platforms expose one cheaply; elsewhere this is always `None`.
+#[cfg(unix)]
+pub(crate) fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Classifies a filesystem type name as reported by the mount table
+fn classify(fs_type: &str) -> FsKind {
+    const NETWORK: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb",
+        "smb3",
+        "smbfs",
+        "afpfs",
+        "webdav",
+        "ncpfs",
+        "afs",
+        "9p",
+        "ceph",
+        "glusterfs",
+        "lustre",
+        "gpfs",
+    ];
+
+    // `fuseblk` is a FUSE driver for a local block device (e.g. ntfs-3g)
+    if fs_type == "fuseblk" {
+        FsKind::Local
+    } else if fs_type.contains("fuse") {
+        FsKind::Fuse
+    } else if NETWORK.contains(&fs_type) {
+        FsKind::Network
+    } else {
+        FsKind::Local
+    }
+}
+
+/// Picks the type of the innermost mount containing `path` from `(mount point, type)` pairs
+///
+/// Later entries win ties, as they are mounted over earlier ones.
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn innermost_mount<'a>(
+    path: &Path,
+    mounts: impl Iterator<Item = (String, &'a str)>,
+) -> Option<&'a str> {
+    let mut best: Option<(usize, &str)> = None;
+    for (mount_point, fs_type) in mounts {
+        if path.starts_with(&mount_point) && best.is_none_or(|(len, _)| mount_point.len() >= len) {
+            best = Some((mount_point.len(), fs_type));
+        }
+    }
+    best.map(|(_, fs_type)| fs_type)
+}
+
+// ============================================================================
+// Platform Backends
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, path::Path};
+
+    use super::innermost_mount;
+
+    pub(super) fn mount_type(path: &Path) -> Option<String> {
+        let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+        innermost_mount(path, parse_mountinfo(&mountinfo)).map(String::from)
+    }
+
+    /// Yields `(mount point, type)` for each line of `/proc/self/mountinfo`
+    ///
+    /// Lines look like `36 35 98:0 / /mnt rw - ext4 /dev/sda1 rw`: the mount
+    /// point is the fifth field and the type follows the ` - ` separator.
+    pub(super) fn parse_mountinfo(mountinfo: &str) -> impl Iterator<Item = (String, &str)> {
+        mountinfo.lines().filter_map(|line| {
+            let (fields, rest) = line.split_once(" - ")?;
+            let mount_point = fields.split(' ').nth(4)?;
+            let fs_type = rest.split(' ').next()?;
+            Some((unescape(mount_point), fs_type))
+        })
+    }
+
+    /// Decodes the octal escapes (`\040` for a space) used in mount points
+    fn unescape(field: &str) -> String {
+        let bytes = field.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let octal = bytes.get(i + 1..i + 4).and_then(|digits| {
+                let digits = std::str::from_utf8(digits).ok()?;
+                u8::from_str_radix(digits, 8).ok()
+            });
+            match (bytes[i], octal) {
+                (b'\\', Some(byte)) => {
+                    decoded.push(byte);
+                    i += 4;
+                }
+                (byte, _) => {
+                    decoded.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{path::Path, process::Command};
+
+    use super::innermost_mount;
+
+    /// Reads the mount table from `mount`, whose lines look like
+    /// `//user@server/share on /Volumes/share (smbfs, nodev, nosuid)`
+    pub(super) fn mount_type(path: &Path) -> Option<String> {
+        let output = Command::new("mount").output().ok()?;
+        let table = String::from_utf8_lossy(&output.stdout);
+
+        let mounts = table.lines().filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?;
+            Some((mount_point.to_string(), fs_type))
+        });
+        innermost_mount(path, mounts).map(String::from)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod platform {
+    use std::path::Path;
+
+    pub(super) fn mount_type(_path: &Path) -> Option<String> {
+        None
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("ext4"), FsKind::Local);
+        assert_eq!(classify("apfs"), FsKind::Local);
+        assert_eq!(classify("fuseblk"), FsKind::Local);
+        assert_eq!(classify("nfs4"), FsKind::Network);
+        assert_eq!(classify("cifs"), FsKind::Network);
+        assert_eq!(classify("smbfs"), FsKind::Network);
+        assert_eq!(classify("fuse.sshfs"), FsKind::Fuse);
+        assert_eq!(classify("macfuse"), FsKind::Fuse);
+        assert!(FsKind::Fuse.is_remote() && !FsKind::Unknown.is_remote());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_mountinfo() {
+        let mountinfo = "\
+22 1 8:1 / / rw,relatime - ext4 /dev/sda1 rw
+40 22 0:35 / /home/dev/shared rw - nfs4 server:/export rw
+41 40 0:36 / /home/dev/shared/my\\040mount rw - fuse.sshfs dev@host: rw
+";
+        let mounts: Vec<_> = platform::parse_mountinfo(mountinfo).collect();
+        assert_eq!(
+            mounts[2],
+            ("/home/dev/shared/my mount".to_string(), "fuse.sshfs")
+        );
+
+        let kind = |path: &str| {
+            innermost_mount(Path::new(path), platform::parse_mountinfo(mountinfo)).map(classify)
+        };
+        assert_eq!(kind("/home/dev/code"), Some(FsKind::Local));
+        assert_eq!(kind("/home/dev/shared/app"), Some(FsKind::Network));
+        assert_eq!(kind("/home/dev/shared/my mount/app"), Some(FsKind::Fuse));
+        // Path components are compared, not string prefixes
+        assert_eq!(kind("/home/dev/shared2"), Some(FsKind::Local));
+    }
+}
@@ -0,0 +1,103 @@
+//! Godot import caches and exported builds
+//!
+//! Both major versions mark a project with `project.godot`, but Godot 4 keeps
+//! its import cache in `.godot/` while Godot 3 uses `.import/`. The file's
+//! `config_version` tells them apart: 5 for Godot 4, 4 or lower for Godot 3.
+//! Exports configured in `export_presets.cfg` are build outputs as well; only
+//! the exported files inside the project are included, never their folders,
+//! which may hold anything.
+
+use std::{
+    fs,
+    path::{Component, Path},
+};
+
+/// First `config_version` written by Godot 4
+const GODOT_4_CONFIG_VERSION: u32 = 5;
+
+/// Returns the project's import cache and exported files
+///
+/// Returns `None` if `project.godot` can't be read.
+pub(crate) fn artifact_directories(project_dir: &Path) -> Option<Vec<String>> {
+    let project = fs::read_to_string(project_dir.join("project.godot")).ok()?;
+    let config_version = setting(&project, "config_version").and_then(|v| v.parse::<u32>().ok());
+    let import_cache = match config_version {
+        Some(version) if version < GODOT_4_CONFIG_VERSION => ".import",
+        _ => ".godot",
+    };
+
+    let mut dirs = vec![import_cache.to_string()];
+    if let Ok(presets) = fs::read_to_string(project_dir.join("export_presets.cfg")) {
+        for line in presets.lines() {
+            let Some(path) = setting(line, "export_path") else {
+                continue;
+            };
+            let path = Path::new(path.trim_start_matches("res://"));
+            let inside = path.components().all(|c| matches!(c, Component::Normal(_)));
+            if !inside || path.as_os_str().is_empty() {
+                continue;
+            }
+
+            // Windows and Linux exports put the game data in a .pck next to the binary
+            for file in [path.to_path_buf(), path.with_extension("pck")] {
+                let name = file.to_string_lossy().replace('\\', "/");
+                if project_dir.join(&file).is_file() && !dirs.contains(&name) {
+                    dirs.push(name);
+                }
+            }
+        }
+    }
+    Some(dirs)
+}
+
+/// Reads `key=value` from the first line that sets `key`, without quotes
+fn setting<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    contents.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix(key)?
+            .trim_start()
+            .strip_prefix('=')?;
+        Some(value.trim().trim_matches('"'))
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_godot_versions_and_exports() {
+        let root = tempfile::tempdir().unwrap();
+        let game = root.path();
+        fs::write(
+            game.join("project.godot"),
+            "config_version=4\n\n[application]\nconfig/name=\"Pong\"\n",
+        )
+        .unwrap();
+        assert_eq!(artifact_directories(game).unwrap(), vec![".import"]);
+
+        fs::write(game.join("project.godot"), "config_version=5\n").unwrap();
+        fs::create_dir_all(game.join("export").join("windows")).unwrap();
+        fs::write(game.join("export").join("windows").join("pong.exe"), "").unwrap();
+        fs::write(game.join("export").join("windows").join("pong.pck"), "").unwrap();
+        fs::write(
+            game.join("export_presets.cfg"),
+            "[preset.0]\nname=\"Windows\"\nexport_path=\"export/windows/pong.exe\"\n\n\
+             [preset.1]\nname=\"Linux\"\nexport_path=\"../builds/pong.x86_64\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            artifact_directories(game).unwrap(),
+            vec![
+                ".godot",
+                "export/windows/pong.exe",
+                "export/windows/pong.pck"
+            ]
+        );
+    }
+}
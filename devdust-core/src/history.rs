@@ -0,0 +1,300 @@
+//! Clean history
+//!
+//! Every successful clean is appended to a history file
+//! (`$XDG_DATA_HOME/devdust/history.tsv`) with its time, project path, project
+//! type and the number of bytes freed. The history is summarized into totals
+//! per project type and per month by [`HistoryStats`].
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{civil_from_days, Project};
+
+/// File name of the history inside the devdust data directory
+const HISTORY_FILE_NAME: &str = "history.tsv";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The file recording past cleans
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+/// A single recorded clean
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// When the project was cleaned
+    pub time: SystemTime,
+    /// Name of the project type (or custom rule)
+    pub project_type: String,
+    /// Number of bytes freed
+    pub bytes: u64,
+    /// Root path of the cleaned project
+    pub path: PathBuf,
+}
+
+/// Totals over the clean history
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryStats {
+    /// Number of recorded cleans
+    pub cleans: usize,
+    /// Total number of bytes freed
+    pub bytes: u64,
+    /// Time of the oldest recorded clean
+    pub since: Option<SystemTime>,
+    /// Totals per project type, largest first
+    pub by_type: Vec<StatsRow>,
+    /// Totals per calendar month (`YYYY-MM`, UTC), oldest first
+    pub by_month: Vec<StatsRow>,
+}
+
+/// Number of cleans and bytes freed for one project type or month
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsRow {
+    /// Project type name or month
+    pub label: String,
+    /// Number of cleans
+    pub cleans: usize,
+    /// Bytes freed
+    pub bytes: u64,
+}
+
+impl History {
+    /// Returns the default history location (`$XDG_DATA_HOME/devdust/history.tsv`)
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))?;
+
+        Some(base.join("devdust").join(HISTORY_FILE_NAME))
+    }
+
+    /// Opens the history at `path`; the file is created when the first clean is recorded
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Opens the history at the default location
+    pub fn open_default() -> io::Result<Self> {
+        Self::default_path().map(Self::new).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "cannot determine data directory")
+        })
+    }
+
+    /// Returns the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a clean to the history
+    pub fn record(&self, entry: &HistoryEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", format_entry(entry))
+    }
+
+    /// Reads all recorded cleans, oldest first
+    ///
+    /// A missing file yields an empty history; malformed lines are skipped.
+    pub fn entries(&self) -> io::Result<Vec<HistoryEntry>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries: Vec<HistoryEntry> = contents.lines().filter_map(parse_entry).collect();
+        entries.sort_by_key(|e| e.time);
+        Ok(entries)
+    }
+
+    /// Summarizes the recorded cleans
+    pub fn stats(&self) -> io::Result<HistoryStats> {
+        Ok(HistoryStats::from_entries(&self.entries()?))
+    }
+}
+
+impl HistoryEntry {
+    /// Creates an entry for a project cleaned just now
+    pub fn new(project: &Project, bytes: u64) -> Self {
+        Self {
+            time: SystemTime::now(),
+            project_type: project.type_name().to_string(),
+            bytes,
+            path: std::path::absolute(&project.path).unwrap_or_else(|_| project.path.clone()),
+        }
+    }
+}
+
+impl HistoryStats {
+    /// Adds up the given cleans per project type and per month
+    pub fn from_entries(entries: &[HistoryEntry]) -> Self {
+        let mut by_type: BTreeMap<&str, StatsRow> = BTreeMap::new();
+        let mut by_month: BTreeMap<String, StatsRow> = BTreeMap::new();
+
+        for entry in entries {
+            add(by_type.entry(&entry.project_type).or_default(), entry);
+            add(by_month.entry(month(entry.time)).or_default(), entry);
+        }
+
+        let mut by_type: Vec<StatsRow> = by_type
+            .into_iter()
+            .map(|(label, row)| StatsRow {
+                label: label.to_string(),
+                ..row
+            })
+            .collect();
+        by_type.sort_by_key(|row| std::cmp::Reverse(row.bytes));
+
+        Self {
+            cleans: entries.len(),
+            bytes: entries.iter().map(|e| e.bytes).sum(),
+            since: entries.iter().map(|e| e.time).min(),
+            by_type,
+            by_month: by_month
+                .into_iter()
+                .map(|(label, row)| StatsRow { label, ..row })
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn add(row: &mut StatsRow, entry: &HistoryEntry) {
+    row.cleans += 1;
+    row.bytes += entry.bytes;
+}
+
+/// Formats a history line: `seconds<TAB>bytes<TAB>type<TAB>path`
+fn format_entry(entry: &HistoryEntry) -> String {
+    let secs = entry
+        .time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!(
+        "{}\t{}\t{}\t{}",
+        secs,
+        entry.bytes,
+        entry.project_type,
+        entry.path.display()
+    )
+}
+
+/// Parses a history line written by `format_entry`
+fn parse_entry(line: &str) -> Option<HistoryEntry> {
+    let mut fields = line.splitn(4, '\t');
+    let secs: u64 = fields.next()?.parse().ok()?;
+    Some(HistoryEntry {
+        time: UNIX_EPOCH + Duration::from_secs(secs),
+        bytes: fields.next()?.parse().ok()?,
+        project_type: fields.next()?.to_string(),
+        path: PathBuf::from(fields.next()?),
+    })
+}
+
+/// Returns the calendar month (`YYYY-MM`, UTC) of a point in time
+fn month(time: SystemTime) -> String {
+    let days = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) / SECONDS_PER_DAY;
+    let (year, month, _) = civil_from_days(days);
+    format!("{:04}-{:02}", year, month)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(secs: u64, project_type: &str, bytes: u64) -> HistoryEntry {
+        HistoryEntry {
+            time: UNIX_EPOCH + Duration::from_secs(secs),
+            project_type: project_type.to_string(),
+            bytes,
+            path: PathBuf::from("/home/dev/app"),
+        }
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let root = tempfile::tempdir().unwrap();
+        let history = History::new(root.path().join("devdust").join(HISTORY_FILE_NAME));
+        assert!(history.entries().unwrap().is_empty());
+
+        let cleans = [entry(2_000, "Rust", 10), entry(1_000, "Node.js", 5)];
+        for clean in &cleans {
+            history.record(clean).unwrap();
+        }
+        fs::OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap()
+            .write_all(b"not a history line\n")
+            .unwrap();
+
+        assert_eq!(
+            history.entries().unwrap(),
+            vec![cleans[1].clone(), cleans[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        // 2024-01-31, 2024-02-01 and 2024-02-29 (UTC)
+        let stats = HistoryStats::from_entries(&[
+            entry(1_706_659_200, "Rust", 100),
+            entry(1_706_745_600, "Node.js", 300),
+            entry(1_709_164_800, "Rust", 50),
+        ]);
+
+        assert_eq!(stats.cleans, 3);
+        assert_eq!(stats.bytes, 450);
+        assert_eq!(
+            stats.since,
+            Some(UNIX_EPOCH + Duration::from_secs(1_706_659_200))
+        );
+        let by_type: Vec<_> = stats
+            .by_type
+            .iter()
+            .map(|r| (r.label.as_str(), r.cleans, r.bytes))
+            .collect();
+        assert_eq!(by_type, [("Node.js", 1, 300), ("Rust", 2, 150)]);
+        let by_month: Vec<_> = stats
+            .by_month
+            .iter()
+            .map(|r| (r.label.as_str(), r.cleans, r.bytes))
+            .collect();
+        assert_eq!(by_month, [("2024-01", 1, 100), ("2024-02", 2, 350)]);
+    }
+
+    #[test]
+    fn test_month() {
+        assert_eq!(month(UNIX_EPOCH), "1970-01");
+        // 2000-02-29 and 2000-03-01
+        assert_eq!(
+            month(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02"
+        );
+        assert_eq!(
+            month(UNIX_EPOCH + Duration::from_secs(951_868_800)),
+            "2000-03"
+        );
+    }
+}
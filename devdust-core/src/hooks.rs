@@ -0,0 +1,157 @@
+//! Commands run before and after cleaning a project
+//!
+//! Some projects need preparing before their artifacts go: running containers
+//! hold `node_modules` mounts, and work in progress may be worth stashing. A
+//! pre-clean hook runs first and a project whose hook fails is left alone; a
+//! post-clean hook runs after each clean attempt, e.g. to send a notification.
+//!
+//! Hooks run through the shell (`sh -c`, or `cmd /C` on Windows) in the project
+//! directory, with the project described in `DEVDUST_*` environment variables.
+
+use std::process::{Command, Output, Stdio};
+
+use tracing::debug;
+
+use crate::{CleanError, CleanReport, Project};
+
+/// Shell commands run around each project's clean
+///
+/// Both get `DEVDUST_PROJECT_PATH`, `DEVDUST_PROJECT_NAME` and
+/// `DEVDUST_PROJECT_TYPE` (the type identifier, or the custom rule's name).
+/// The post-clean hook also gets `DEVDUST_CLEAN_STATUS` (`cleaned` or `failed`)
+/// and `DEVDUST_BYTES_FREED`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanHooks {
+    pub pre_clean: Option<String>,
+    pub post_clean: Option<String>,
+}
+
+impl CleanHooks {
+    /// Runs the pre-clean hook, if any; on error the project must not be cleaned
+    pub fn before(&self, project: &Project) -> Result<(), CleanError> {
+        match &self.pre_clean {
+            Some(command) => run(command, project, &[]),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the post-clean hook, if any, telling it how the clean went
+    ///
+    /// A failure doesn't undo the clean; it is returned only to be reported.
+    pub fn after(
+        &self,
+        project: &Project,
+        outcome: &Result<CleanReport, CleanError>,
+    ) -> Result<(), CleanError> {
+        let Some(command) = &self.post_clean else {
+            return Ok(());
+        };
+        let (status, bytes_freed) = match outcome {
+            Ok(report) => ("cleaned", report.bytes_freed),
+            Err(CleanError::PartialFailure { deleted, .. }) => ("failed", *deleted),
+            Err(_) => ("failed", 0),
+        };
+        run(
+            command,
+            project,
+            &[
+                ("DEVDUST_CLEAN_STATUS", status.to_string()),
+                ("DEVDUST_BYTES_FREED", bytes_freed.to_string()),
+            ],
+        )
+    }
+}
+
+/// Runs `command` for `project`, failing unless it exits successfully
+///
+/// The hook's output is captured rather than shown, so it can't mix with
+/// machine-readable output or the full-screen mode; on failure, the last line
+/// it wrote to stderr becomes part of the error.
+fn run(command: &str, project: &Project, env: &[(&str, String)]) -> Result<(), CleanError> {
+    let type_name = match &project.custom_rule {
+        Some(rule) => rule.name.clone(),
+        None => project.project_type.id().to_string(),
+    };
+    let failed = |reason: String| CleanError::Hook {
+        path: project.path.clone(),
+        command: command.to_string(),
+        reason,
+    };
+
+    debug!(path = %project.path.display(), command, "running hook");
+    let Output { status, stderr, .. } = shell(command)
+        .current_dir(&project.path)
+        .env("DEVDUST_PROJECT_PATH", &project.path)
+        .env("DEVDUST_PROJECT_NAME", project.display_name())
+        .env("DEVDUST_PROJECT_TYPE", type_name)
+        .envs(env.iter().cloned())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| failed(e.to_string()))?;
+
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&stderr);
+    match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => Err(failed(format!("{} ({})", line.trim(), status))),
+        None => Err(failed(status.to_string())),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::ProjectType;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        fs::create_dir_all(app.join("target")).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        let project = Project::new(ProjectType::Rust, app.clone());
+
+        let hooks = CleanHooks {
+            pre_clean: Some("test \"$DEVDUST_PROJECT_TYPE\" = rust && touch stashed".to_string()),
+            post_clean: Some(
+                "echo \"$DEVDUST_CLEAN_STATUS $DEVDUST_BYTES_FREED\" > done".to_string(),
+            ),
+        };
+        hooks.before(&project).unwrap();
+        assert!(app.join("stashed").is_file());
+
+        let report = project.clean().unwrap();
+        hooks.after(&project, &Ok(report)).unwrap();
+        assert_eq!(fs::read_to_string(app.join("done")).unwrap(), "cleaned 0\n");
+
+        let hooks = CleanHooks {
+            pre_clean: Some("echo 'containers still running' >&2; exit 3".to_string()),
+            post_clean: None,
+        };
+        let error = hooks.before(&project).unwrap_err();
+        assert!(matches!(error, CleanError::Hook { .. }));
+        assert!(error.to_string().contains("containers still running"));
+    }
+}
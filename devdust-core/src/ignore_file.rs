@@ -0,0 +1,112 @@
+//! `.devdustignore` support
+//!
+//! Ignore files use gitignore syntax and are read from each scan root and all
+//! of its parent directories. Patterns are relative to the directory holding
+//! the file, and files closer to the scanned path take precedence.
+
+use std::path::{self, Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// File name of devdust ignore files
+pub const IGNORE_FILE_NAME: &str = ".devdustignore";
+
+/// Matches paths against every `.devdustignore` that applies to a scan root
+#[derive(Debug, Default)]
+pub(crate) struct IgnoreMatcher {
+    /// Parsed ignore files, closest to the scan root first
+    matchers: Vec<Gitignore>,
+}
+
+impl IgnoreMatcher {
+    /// Collects the ignore files in `root` and its ancestors
+    pub(crate) fn for_root(root: &Path) -> Self {
+        let Some(root) = absolute(root) else {
+            return Self::default();
+        };
+
+        let matchers = root
+            .ancestors()
+            .filter_map(|dir| {
+                let file = dir.join(IGNORE_FILE_NAME);
+                if !file.is_file() {
+                    return None;
+                }
+
+                let mut builder = GitignoreBuilder::new(dir);
+                // Invalid lines are skipped; the rest of the file still applies
+                let _ = builder.add(&file);
+                builder.build().ok()
+            })
+            .collect();
+
+        Self { matchers }
+    }
+
+    /// Returns true if the path is excluded by the closest matching ignore file
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.matchers.is_empty() {
+            return false;
+        }
+        let Some(path) = absolute(path) else {
+            return false;
+        };
+
+        for matcher in &self.matchers {
+            let matched = matcher.matched(&path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+
+        false
+    }
+}
+
+/// Makes a path absolute without touching the filesystem
+fn absolute(path: &Path) -> Option<PathBuf> {
+    path::absolute(path).ok()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_root_and_parent_ignore_files() {
+        let parent = tempfile::tempdir().unwrap();
+        let root = parent.path().join("code");
+        fs::create_dir_all(root.join("archive")).unwrap();
+        fs::create_dir_all(root.join("app").join("legacy")).unwrap();
+        fs::create_dir_all(root.join("app").join("src")).unwrap();
+
+        fs::write(parent.path().join(IGNORE_FILE_NAME), "**/legacy\n").unwrap();
+        fs::write(root.join(IGNORE_FILE_NAME), "archive/\n").unwrap();
+
+        let matcher = IgnoreMatcher::for_root(&root);
+        assert!(matcher.is_ignored(&root.join("archive"), true));
+        assert!(matcher.is_ignored(&root.join("app").join("legacy"), true));
+        assert!(!matcher.is_ignored(&root.join("app").join("src"), true));
+    }
+
+    #[test]
+    fn test_closer_file_can_whitelist() {
+        let parent = tempfile::tempdir().unwrap();
+        let root = parent.path().join("code");
+        fs::create_dir_all(&root).unwrap();
+
+        fs::write(parent.path().join(IGNORE_FILE_NAME), "code/keep\n").unwrap();
+        fs::write(root.join(IGNORE_FILE_NAME), "!keep\n").unwrap();
+
+        let matcher = IgnoreMatcher::for_root(&root);
+        assert!(!matcher.is_ignored(&root.join("keep"), true));
+    }
+}
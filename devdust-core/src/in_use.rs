@@ -0,0 +1,126 @@
+//! Detecting artifact directories that running processes are using
+//!
+//! Deleting a `target/` directory while a build or a freshly built binary is
+//! running leaves both half-broken. Before cleaning, we look for processes with
+//! files open (or their working directory or executable) inside the artifact
+//! directories: through `/proc` on Linux and `lsof` on macOS. Elsewhere, and
+//! whenever the check itself fails, directories are assumed to be unused.
+
+use std::path::{Path, PathBuf};
+
+/// Returns true if any process has a file open inside one of `dirs`
+pub(crate) fn any_in_use(dirs: &[PathBuf]) -> bool {
+    // Process tables report resolved paths, so compare against those
+    let dirs: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect();
+    if dirs.is_empty() {
+        return false;
+    }
+
+    platform::any_in_use(&dirs)
+}
+
+/// Returns true if `path` is one of `dirs` or lies inside one of them
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_inside(path: &Path, dirs: &[PathBuf]) -> bool {
+    dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+// ============================================================================
+// Platform Backends
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, path::PathBuf};
+
+    use super::is_inside;
+
+    /// Walks `/proc`, checking each process's working directory, executable and open files
+    ///
+    /// Processes of other users are skipped, as their entries cannot be read.
+    pub(super) fn any_in_use(dirs: &[PathBuf]) -> bool {
+        let Ok(processes) = fs::read_dir("/proc") else {
+            return false;
+        };
+
+        processes
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str().is_some_and(is_pid))
+            .any(|entry| {
+                let proc_dir = entry.path();
+                let uses = |link: PathBuf| {
+                    fs::read_link(link).is_ok_and(|target| is_inside(&target, dirs))
+                };
+
+                uses(proc_dir.join("cwd"))
+                    || uses(proc_dir.join("exe"))
+                    || fs::read_dir(proc_dir.join("fd"))
+                        .is_ok_and(|fds| fds.filter_map(|fd| fd.ok()).any(|fd| uses(fd.path())))
+            })
+    }
+
+    fn is_pid(name: &str) -> bool {
+        !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{path::PathBuf, process::Command};
+
+    /// Asks `lsof` for processes with files open below each directory
+    pub(super) fn any_in_use(dirs: &[PathBuf]) -> bool {
+        dirs.iter().any(|dir| {
+            Command::new("lsof")
+                .args(["-t", "+D"])
+                .arg(dir)
+                .output()
+                .is_ok_and(|output| !output.stdout.is_empty())
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod platform {
+    use std::path::PathBuf;
+
+    pub(super) fn any_in_use(_dirs: &[PathBuf]) -> bool {
+        false
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_inside() {
+        let dirs = [PathBuf::from("/code/app/target")];
+        assert!(is_inside(Path::new("/code/app/target"), &dirs));
+        assert!(is_inside(Path::new("/code/app/target/debug/app"), &dirs));
+        assert!(!is_inside(Path::new("/code/app/target2/x"), &dirs));
+        assert!(!is_inside(Path::new("/code/app/src/main.rs"), &dirs));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_file_marks_directory_in_use() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("target");
+        std::fs::create_dir(&target).unwrap();
+        let dirs = [target.clone()];
+        assert!(!any_in_use(&dirs));
+
+        let file = std::fs::File::create(target.join("build.lock")).unwrap();
+        assert!(any_in_use(&dirs));
+        drop(file);
+        assert!(!any_in_use(&dirs));
+    }
+}
@@ -0,0 +1,119 @@
+//! What takes up the space inside artifact directories
+//!
+//! A single size for `node_modules` or `target/` doesn't tell which package or
+//! build profile is responsible. [`entries`] breaks the artifact directories
+//! down a given number of levels deep, sizing each subdirectory as a whole.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{calculate_directory_size, Project, ScanOptions};
+
+/// A file or directory inside an artifact directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArtifactEntry {
+    /// Relative to the project root, or absolute if the artifact lies outside it
+    pub path: PathBuf,
+    /// Size in bytes; for directories, of everything below them
+    pub size: u64,
+    /// True for directories
+    pub is_dir: bool,
+}
+
+/// Lists the entries `depth` levels below each artifact directory, largest first
+///
+/// Files found above that depth are listed as well, so every byte of the
+/// artifacts is accounted for exactly once.
+pub(crate) fn entries(
+    project: &Project,
+    options: &ScanOptions,
+    depth: usize,
+) -> Vec<ArtifactEntry> {
+    let depth = depth.max(1);
+    let mut entries = Vec::new();
+
+    for artifact in project.artifacts(options) {
+        let root = project.path.join(&artifact.path);
+        let walker = walkdir::WalkDir::new(&root)
+            .min_depth(1)
+            .max_depth(depth)
+            .follow_links(options.follow_symlinks)
+            .same_file_system(options.same_filesystem);
+
+        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            let is_dir = entry.file_type().is_dir();
+            if is_dir && entry.depth() < depth {
+                continue;
+            }
+
+            let size = if is_dir {
+                calculate_directory_size(entry.path(), options)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            };
+            entries.push(ArtifactEntry {
+                path: relative_to(entry.path(), &project.path),
+                size,
+                is_dir,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries
+}
+
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    path.strip_prefix(base)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_entries_by_depth() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        let target = app.join("target");
+        fs::create_dir_all(target.join("debug").join("deps")).unwrap();
+        fs::create_dir_all(target.join("release")).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        fs::write(
+            target.join("debug").join("deps").join("libfoo.rlib"),
+            [0u8; 300],
+        )
+        .unwrap();
+        fs::write(target.join("debug").join("app"), [0u8; 100]).unwrap();
+        fs::write(target.join("release").join("app"), [0u8; 50]).unwrap();
+        fs::write(target.join(".rustc_info.json"), [0u8; 10]).unwrap();
+
+        let project = Project::detect(&app, &[]).unwrap();
+        let options = ScanOptions::default();
+
+        let top = entries(&project, &options, 1);
+        let listed: Vec<_> = top.iter().map(|e| (e.path.clone(), e.size)).collect();
+        assert_eq!(
+            listed,
+            vec![
+                (PathBuf::from("target/debug"), 400),
+                (PathBuf::from("target/release"), 50),
+                (PathBuf::from("target/.rustc_info.json"), 10),
+            ]
+        );
+        assert!(top[0].is_dir && !top[2].is_dir);
+
+        let deeper = entries(&project, &options, 2);
+        assert_eq!(deeper[0].path, PathBuf::from("target/debug/deps"));
+        assert_eq!(deeper.iter().map(|e| e.size).sum::<u64>(), 460);
+    }
+}
@@ -12,12 +12,134 @@
 //! - Unity
 //! - Unreal Engine
 //! - And many more...
-
-use std::{
-    error::Error,
-    fmt, fs,
-    path::{Path, PathBuf},
-    time::SystemTime,
+//!
+//! Walking, sizing and cleaning need the default `fs` feature. With
+//! `default-features = false` only project detection
+//! ([`ProjectType::detect_from_file_names`]) and the formatting and parsing
+//! helpers remain, so the crate builds for `wasm32-unknown-unknown`.
+
+use std::{fs, path::Path, str::FromStr, time::SystemTime};
+
+use serde::Serialize;
+
+#[cfg(feature = "fs")]
+mod android;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "fs")]
+mod artifacts;
+#[cfg(feature = "async")]
+mod async_scan;
+#[cfg(feature = "fs")]
+mod background;
+#[cfg(feature = "fs")]
+mod bazel;
+#[cfg(feature = "fs")]
+mod cachedir;
+#[cfg(feature = "fs")]
+mod caches;
+#[cfg(feature = "fs")]
+mod cargo_target;
+#[cfg(feature = "fs")]
+mod config;
+#[cfg(feature = "fs")]
+mod containers;
+mod custom;
+#[cfg(feature = "fs")]
+mod disk_usage;
+#[cfg(feature = "fs")]
+mod duplicates;
+#[cfg(feature = "fs")]
+mod exclude;
+#[cfg(feature = "fs")]
+mod fs_kind;
+#[cfg(feature = "fs")]
+mod godot;
+#[cfg(feature = "fs")]
+mod history;
+#[cfg(feature = "fs")]
+mod hooks;
+#[cfg(feature = "fs")]
+mod ignore_file;
+#[cfg(feature = "fs")]
+mod in_use;
+#[cfg(feature = "fs")]
+mod inspect;
+#[cfg(feature = "fs")]
+mod metadata;
+#[cfg(feature = "fs")]
+mod native;
+#[cfg(feature = "fs")]
+mod orphan;
+#[cfg(feature = "fs")]
+mod partial;
+#[cfg(feature = "fs")]
+mod pins;
+#[cfg(feature = "fs")]
+mod progress;
+#[cfg(feature = "fs")]
+mod quarantine;
+#[cfg(feature = "fs")]
+mod ranking;
+#[cfg(feature = "fs")]
+mod relocate;
+#[cfg(feature = "fs")]
+mod remove;
+#[cfg(feature = "fs")]
+mod scan;
+#[cfg(feature = "fs")]
+mod size_cache;
+#[cfg(feature = "fs")]
+mod stale;
+#[cfg(feature = "fs")]
+mod timings;
+#[cfg(feature = "fs")]
+mod unknown;
+#[cfg(feature = "fs")]
+mod unreal;
+#[cfg(feature = "fs")]
+mod vcs;
+#[cfg(feature = "fs")]
+mod xcode;
+
+#[cfg(feature = "archive")]
+pub use archive::{Archive, ArchiveBatch};
+#[cfg(feature = "async")]
+pub use async_scan::scan_directory_async;
+pub use custom::CustomRule;
+
+#[cfg(feature = "fs")]
+pub use {
+    background::{remove_staged, BackgroundRemover, BackgroundReport},
+    caches::GlobalCache,
+    config::{Config, ConfigError, TypeConfig, LOCAL_CONFIG_FILE},
+    containers::{ContainerEngine, ContainerResource, ContainerUsage},
+    disk_usage::{allocated_size, calculate_disk_usage, DiskUsage},
+    duplicates::{find_duplicate_packages, DuplicatePackage},
+    fs_kind::{fs_kind, FsKind},
+    history::{History, HistoryEntry, HistoryStats, StatsRow},
+    hooks::CleanHooks,
+    ignore_file::IGNORE_FILE_NAME,
+    inspect::ArtifactEntry,
+    metadata::ProjectMetadata,
+    partial::PartialClean,
+    pins::{is_pinned, Pins},
+    progress::{ProgressCallback, ProgressEvent, ScanObserver, SkipReason},
+    quarantine::{BatchInfo, Quarantine, QuarantineBatch, QuarantinedEntry, RestoreReport},
+    ranking::{
+        cleanup_score, group_by_type, rank_for_cleanup, select_until_freed, sort_projects,
+        SortFields, SortKey, TypeGroup,
+    },
+    remove::RetryPolicy,
+    scan::{
+        calculate_directory_size, scan_directory, scan_many, scan_with_sizes, AgeSource, Artifact,
+        CleanError, CleanReport, Project, ScanError, ScanOptions, ScanOptionsBuilder, TypeOverride,
+        KEEP_FILE_NAME,
+    },
+    size_cache::SizeCache,
+    timings::{ProjectTimings, RootTimings, ScanTimings},
+    unknown::{find_unknown_artifacts, UnknownArtifact},
+    vcs::{GitPolicy, GitStatus, VcsInfo},
 };
 
 // ============================================================================
@@ -25,7 +147,10 @@ use std::{
 // ============================================================================
 
 /// Represents different types of development projects we can detect
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serializes as the identifier returned by [`ProjectType::id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ProjectType {
     /// Rust projects (Cargo.toml)
     Rust,
@@ -34,6 +159,7 @@ pub enum ProjectType {
     /// Python projects (.py files with common artifacts)
     Python,
     /// .NET projects (.csproj, .fsproj)
+    #[serde(rename = "dotnet")]
     DotNet,
     /// Unity game engine projects
     Unity,
@@ -44,10 +170,16 @@ pub enum ProjectType {
     /// Java/Kotlin Gradle projects (build.gradle)
     Gradle,
     /// CMake projects (CMakeLists.txt)
+    #[serde(rename = "cmake")]
     CMake,
+    /// Meson projects (meson.build)
+    Meson,
+    /// GNU Autotools projects (configure.ac)
+    Autotools,
     /// Haskell Stack projects (stack.yaml)
     HaskellStack,
     /// Scala SBT projects (build.sbt)
+    #[serde(rename = "scala-sbt")]
     ScalaSBT,
     /// PHP Composer projects (composer.json)
     Composer,
@@ -59,7 +191,7 @@ pub enum ProjectType {
     Swift,
     /// Zig projects (build.zig)
     Zig,
-    /// Godot 4.x projects (project.godot)
+    /// Godot 3.x and 4.x projects (project.godot)
     Godot,
     /// Jupyter notebooks (.ipynb)
     Jupyter,
@@ -71,11 +203,105 @@ pub enum ProjectType {
     Terraform,
     /// Docker projects (Dockerfile)
     Docker,
-    /// Bazel projects (WORKSPACE, BUILD)
+    /// Bazel projects (MODULE.bazel, WORKSPACE, BUILD)
     Bazel,
+    /// OCaml projects built with dune (dune-project)
+    #[serde(rename = "ocaml")]
+    OCaml,
+    /// Nim projects (*.nimble)
+    Nim,
+    /// Julia projects (Project.toml)
+    Julia,
+    /// R projects using renv (renv.lock)
+    R,
+    /// Machine learning experiments (wandb, mlruns, lightning_logs, .dvc)
+    #[serde(rename = "ml")]
+    MachineLearning,
+    /// Xcode projects (.xcodeproj, .xcworkspace)
+    Xcode,
+    /// LaTeX documents (.tex files with build leftovers)
+    #[serde(rename = "latex")]
+    LaTeX,
+    /// User-defined projects matched by a `CustomRule`
+    Custom,
 }
 
 impl ProjectType {
+    /// All built-in project types, in detection order
+    pub const ALL: &'static [ProjectType] = &[
+        Self::Rust,
+        Self::Node,
+        Self::Python,
+        Self::DotNet,
+        Self::Unity,
+        Self::Unreal,
+        Self::Maven,
+        Self::Gradle,
+        Self::CMake,
+        Self::Meson,
+        Self::Autotools,
+        Self::HaskellStack,
+        Self::ScalaSBT,
+        Self::Composer,
+        Self::Dart,
+        Self::Elixir,
+        Self::Swift,
+        Self::Zig,
+        Self::Godot,
+        Self::Jupyter,
+        Self::Go,
+        Self::Ruby,
+        Self::Terraform,
+        Self::Docker,
+        Self::Bazel,
+        Self::OCaml,
+        Self::Nim,
+        Self::Julia,
+        Self::R,
+        Self::MachineLearning,
+        Self::Xcode,
+        Self::LaTeX,
+    ];
+
+    /// Returns the short lowercase identifier used in config files and CLI flags
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Node => "node",
+            Self::Python => "python",
+            Self::DotNet => "dotnet",
+            Self::Unity => "unity",
+            Self::Unreal => "unreal",
+            Self::Maven => "maven",
+            Self::Gradle => "gradle",
+            Self::CMake => "cmake",
+            Self::Meson => "meson",
+            Self::Autotools => "autotools",
+            Self::HaskellStack => "haskell-stack",
+            Self::ScalaSBT => "scala-sbt",
+            Self::Composer => "composer",
+            Self::Dart => "dart",
+            Self::Elixir => "elixir",
+            Self::Swift => "swift",
+            Self::Zig => "zig",
+            Self::Godot => "godot",
+            Self::Jupyter => "jupyter",
+            Self::Go => "go",
+            Self::Ruby => "ruby",
+            Self::Terraform => "terraform",
+            Self::Docker => "docker",
+            Self::Bazel => "bazel",
+            Self::OCaml => "ocaml",
+            Self::Nim => "nim",
+            Self::Julia => "julia",
+            Self::R => "r",
+            Self::MachineLearning => "ml",
+            Self::Xcode => "xcode",
+            Self::LaTeX => "latex",
+            Self::Custom => "custom",
+        }
+    }
+
     /// Returns the human-readable name of the project type
     pub fn name(&self) -> &'static str {
         match self {
@@ -88,6 +314,8 @@ impl ProjectType {
             Self::Maven => "Maven",
             Self::Gradle => "Gradle",
             Self::CMake => "CMake",
+            Self::Meson => "Meson",
+            Self::Autotools => "Autotools",
             Self::HaskellStack => "Haskell Stack",
             Self::ScalaSBT => "Scala SBT",
             Self::Composer => "PHP Composer",
@@ -102,11 +330,19 @@ impl ProjectType {
             Self::Terraform => "Terraform",
             Self::Docker => "Docker",
             Self::Bazel => "Bazel",
+            Self::OCaml => "OCaml",
+            Self::Nim => "Nim",
+            Self::Julia => "Julia",
+            Self::R => "R",
+            Self::MachineLearning => "Machine Learning",
+            Self::Xcode => "Xcode",
+            Self::LaTeX => "LaTeX",
+            Self::Custom => "Custom",
         }
     }
 
     /// Returns the directories that contain build artifacts for this project type
-    pub fn artifact_directories(&self) -> &[&str] {
+    pub fn artifact_directories(&self) -> &'static [&'static str] {
         match self {
             Self::Rust => &["target", ".xwin-cache"],
             Self::Node => &[
@@ -116,9 +352,19 @@ impl ProjectType {
                 "dist",
                 "build",
                 ".angular",
+                ".turbo",
+                ".nx/cache",
+                "node_modules/.cache",
+                "node_modules/.vite",
+                // Expo and React Native
+                ".expo",
+                "android/app/build",
+                "android/.gradle",
+                "ios/Pods",
+                "ios/build",
             ],
             Self::Python => &[
-                "__pycache__",
+                "**/__pycache__",
                 ".pytest_cache",
                 ".mypy_cache",
                 ".ruff_cache",
@@ -128,7 +374,7 @@ impl ProjectType {
                 "venv",
                 ".hypothesis",
                 "__pypackages__",
-                "*.egg-info",
+                "**/*.egg-info",
             ],
             Self::DotNet => &["bin", "obj"],
             Self::Unity => &[
@@ -136,6 +382,7 @@ impl ProjectType {
                 "Temp",
                 "Obj",
                 "Logs",
+                "UserSettings",
                 "MemoryCaptures",
                 "Build",
                 "Builds",
@@ -148,8 +395,11 @@ impl ProjectType {
                 "DerivedDataCache",
             ],
             Self::Maven => &["target"],
-            Self::Gradle => &["build", ".gradle"],
-            Self::CMake => &["build", "cmake-build-debug", "cmake-build-release"],
+            Self::Gradle => &["build", ".gradle", ".cxx"],
+            Self::CMake => &["build", "cmake-build-*"],
+            Self::Meson => &["builddir", "build"],
+            // Plus out-of-tree build directories
+            Self::Autotools => &["autom4te.cache", "build", "_build"],
             Self::HaskellStack => &[".stack-work"],
             Self::ScalaSBT => &["target", "project/target"],
             Self::Composer => &["vendor"],
@@ -157,33 +407,186 @@ impl ProjectType {
             Self::Elixir => &["_build", ".elixir-tools", ".elixir_ls", ".lexical"],
             Self::Swift => &[".build", ".swiftpm"],
             Self::Zig => &["zig-cache", "zig-out"],
-            Self::Godot => &[".godot"],
+            // Narrowed down by engine version, see `Project::artifact_directories`
+            Self::Godot => &[".godot", ".import"],
             Self::Jupyter => &[".ipynb_checkpoints"],
             Self::Go => &["vendor", "bin"],
-            Self::Ruby => &["vendor/bundle"],
+            Self::Ruby => &["vendor/bundle", ".bundle", "tmp/cache"],
             Self::Terraform => &[".terraform", ".terraform.lock.hcl"],
             Self::Docker => &[".docker"],
             Self::Bazel => &["bazel-bin", "bazel-out", "bazel-testlogs", "bazel-*"],
+            Self::OCaml => &["_build"],
+            Self::Nim => &["nimcache"],
+            // A project-local depot; its packages are left alone, Manifest.toml is the lockfile
+            Self::Julia => &[".julia/compiled", ".julia/scratchspaces"],
+            Self::R => &["renv/library", "renv/staging"],
+            Self::MachineLearning => &[
+                "wandb",
+                "mlruns",
+                "lightning_logs",
+                "checkpoints",
+                ".dvc/cache",
+            ],
+            // Plus the project's folders in Xcode's DerivedData, see `Project::artifact_directories`
+            Self::Xcode => &["build", "DerivedData"],
+            // Files rather than directories, next to the .tex sources; only
+            // `<stem>.<ext>` of a source counts, see `is_latex_leftover`
+            Self::LaTeX => &[
+                "*.aux",
+                "*.log",
+                "*.out",
+                "*.toc",
+                "*.lof",
+                "*.lot",
+                "*.synctex.gz",
+                "*.fls",
+                "*.fdb_latexmk",
+                "*.bbl",
+                "*.blg",
+                "*.bcf",
+                "*.run.xml",
+                "*.nav",
+                "*.snm",
+                "*.xdv",
+                // `_minted` or `_minted-<jobname>`
+                "_minted*",
+            ],
+            // Defined by the matching rule, see `Project::artifact_directories`
+            Self::Custom => &[],
+        }
+    }
+
+    /// Returns the category of one of this type's artifact directories
+    pub fn artifact_category(&self, artifact_dir: &str) -> ArtifactCategory {
+        match (self, artifact_dir) {
+            // `**` patterns resolve to nested paths such as `pkg/__pycache__`
+            (Self::Python, nested) if nested.contains('/') => {
+                self.artifact_category(nested.rsplit('/').next().unwrap_or(nested))
+            }
+            (
+                Self::Python,
+                "__pycache__" | ".pytest_cache" | ".mypy_cache" | ".ruff_cache" | ".hypothesis",
+            ) => ArtifactCategory::Cache,
+            (Self::Python, ".venv" | "venv" | ".tox" | ".nox" | "__pypackages__") => {
+                ArtifactCategory::Environment
+            }
+            (Self::Node, ".turbo" | ".nx/cache" | "node_modules/.cache" | "node_modules/.vite") => {
+                ArtifactCategory::Cache
+            }
+            (Self::Ruby, "tmp/cache") => ArtifactCategory::Cache,
+            (Self::Julia, _) => ArtifactCategory::Cache,
+            (Self::R, "renv/library") => ArtifactCategory::Environment,
+            (Self::MachineLearning, _) => ArtifactCategory::Experiment,
+            _ => ArtifactCategory::Build,
+        }
+    }
+
+    /// Returns the lockfiles that make an artifact directory reproducible
+    ///
+    /// Under the `require_lockfile` policy, the directory is only cleaned if one of
+    /// them exists in the project root. Most directories need none.
+    pub fn required_lockfiles(&self, artifact_dir: &str) -> &[&str] {
+        match (self, artifact_dir) {
+            (Self::Node, "node_modules") => &[
+                "package-lock.json",
+                "npm-shrinkwrap.json",
+                "yarn.lock",
+                "pnpm-lock.yaml",
+                "bun.lock",
+                "bun.lockb",
+            ],
+            (Self::Composer, "vendor") => &["composer.lock"],
+            (Self::Ruby, "vendor/bundle") => &["Gemfile.lock"],
+            _ => &[],
+        }
+    }
+
+    /// Returns the lockfiles an artifact directory is installed or built from
+    ///
+    /// A directory older than all of them predates the last dependency change
+    /// and will be rebuilt anyway (see `Project::stale_artifacts`).
+    pub fn source_lockfiles(&self, artifact_dir: &str) -> &[&str] {
+        match (self, artifact_dir) {
+            (Self::Node, "node_modules")
+            | (Self::Composer, "vendor")
+            | (Self::Ruby, "vendor/bundle") => self.required_lockfiles(artifact_dir),
+            (Self::Rust, "target") => &["Cargo.lock"],
+            (Self::Python, ".venv" | "venv") => {
+                &["uv.lock", "poetry.lock", "pdm.lock", "Pipfile.lock"]
+            }
+            (Self::Dart, ".dart_tool") => &["pubspec.lock"],
+            (Self::Elixir, "_build") => &["mix.lock"],
+            (Self::R, "renv/library") => &["renv.lock"],
+            _ => &[],
         }
     }
 
     /// Detects project type from a directory by checking for marker files
+    ///
+    /// Returns the primary type of a directory that matches several, see
+    /// [`ProjectType::detect_all_from_directory`].
     pub fn detect_from_directory(path: &Path) -> Option<Self> {
-        // Read directory entries
-        let entries: Vec<_> = fs::read_dir(path).ok()?.filter_map(|e| e.ok()).collect();
+        Self::detect_all_from_directory(path).into_iter().next()
+    }
+
+    /// Detects every project type whose markers a directory holds
+    ///
+    /// A Tauri app, for example, is both a Node.js and a Rust project. The primary
+    /// type comes first; the others are only listed if one of their artifact
+    /// directories exists.
+    pub fn detect_all_from_directory(path: &Path) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(path) else {
+            return Vec::new();
+        };
+        let names: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+
+        Self::detect_all_from_file_names(&names)
+    }
 
-        // Check for specific marker files
-        for entry in &entries {
-            let file_name = entry.file_name();
-            let file_name_str = file_name.to_string_lossy();
+    /// Detects project type from the names of the entries in a project root
+    ///
+    /// Does not touch the filesystem, so callers without one (e.g. a browser
+    /// showing an uploaded listing) get the same answer as
+    /// [`ProjectType::detect_from_directory`].
+    pub fn detect_from_file_names<S: AsRef<str>>(names: &[S]) -> Option<Self> {
+        Self::detect_all_from_file_names(names).into_iter().next()
+    }
+
+    /// Detects every project type from the names of the entries in a project root
+    ///
+    /// The listing counterpart of [`ProjectType::detect_all_from_directory`].
+    pub fn detect_all_from_file_names<S: AsRef<str>>(names: &[S]) -> Vec<Self> {
+        let has_file = |file_name: &str| names.iter().any(|n| n.as_ref() == file_name);
+        let has_ml_marker = || ML_MARKERS.iter().any(|marker| has_file(marker));
+        // The editor writes `ProjectSettings/ProjectVersion.txt` next to `Assets/`
+        // on creation, long before an IDE generates any .csproj
+        let is_unity = || has_file("ProjectSettings") && has_file("Assets");
+        // Source files alone are not worth reporting, only next to their artifacts
+        let has_artifact_of = |project_type: Self| {
+            names.iter().any(|n| {
+                project_type.artifact_directories().iter().any(|artifact| {
+                    // Listed by their top directory, e.g. `vendor` for `vendor/bundle`
+                    let top = artifact.split('/').find(|c| *c != "**").unwrap_or(artifact);
+                    matches_wildcard(top, n.as_ref())
+                })
+            })
+        };
 
+        let detect_name = |file_name_str: &str| -> Option<Self> {
             // Check exact file names
-            match file_name_str.as_ref() {
+            match file_name_str {
                 "Cargo.toml" => return Some(Self::Rust),
                 "package.json" => return Some(Self::Node),
                 "pom.xml" => return Some(Self::Maven),
                 "build.gradle" | "build.gradle.kts" => return Some(Self::Gradle),
                 "CMakeLists.txt" => return Some(Self::CMake),
+                "meson.build" => return Some(Self::Meson),
+                "configure.ac" | "configure.in" => return Some(Self::Autotools),
+                // A release tarball ships the generated script without its source
+                "configure" if has_file("Makefile.in") => return Some(Self::Autotools),
                 "stack.yaml" => return Some(Self::HaskellStack),
                 "build.sbt" => return Some(Self::ScalaSBT),
                 "composer.json" => return Some(Self::Composer),
@@ -193,11 +596,15 @@ impl ProjectType {
                 "build.zig" => return Some(Self::Zig),
                 "project.godot" => return Some(Self::Godot),
                 "Assembly-CSharp.csproj" => return Some(Self::Unity),
+                "ProjectSettings" | "Assets" if is_unity() => return Some(Self::Unity),
                 "go.mod" => return Some(Self::Go),
                 "Gemfile" => return Some(Self::Ruby),
                 "Dockerfile" => return Some(Self::Docker),
-                "WORKSPACE" | "WORKSPACE.bazel" => return Some(Self::Bazel),
+                "MODULE.bazel" | "WORKSPACE" | "WORKSPACE.bazel" => return Some(Self::Bazel),
                 "BUILD" | "BUILD.bazel" => return Some(Self::Bazel),
+                "dune-project" => return Some(Self::OCaml),
+                "Project.toml" => return Some(Self::Julia),
+                "renv.lock" => return Some(Self::R),
                 _ => {}
             }
 
@@ -207,9 +614,9 @@ impl ProjectType {
             }
             if file_name_str.ends_with(".csproj") || file_name_str.ends_with(".fsproj") {
                 // Distinguish between Unity, Godot, and regular .NET
-                if Self::has_file(path, "project.godot") {
+                if has_file("project.godot") {
                     return Some(Self::Godot);
-                } else if Self::has_file(path, "Assembly-CSharp.csproj") {
+                } else if has_file("Assembly-CSharp.csproj") || is_unity() {
                     return Some(Self::Unity);
                 } else {
                     return Some(Self::DotNet);
@@ -218,233 +625,87 @@ impl ProjectType {
             if file_name_str.ends_with(".ipynb") {
                 return Some(Self::Jupyter);
             }
+            if file_name_str.ends_with(".xcodeproj") || file_name_str.ends_with(".xcworkspace") {
+                return Some(Self::Xcode);
+            }
+            if file_name_str.ends_with(".nimble") {
+                return Some(Self::Nim);
+            }
             if file_name_str.ends_with(".tf") {
                 return Some(Self::Terraform);
             }
+            if file_name_str.ends_with(".tex")
+                && names.iter().any(|n| is_latex_leftover(names, n.as_ref()))
+            {
+                return Some(Self::LaTeX);
+            }
             if file_name_str.ends_with(".py") {
+                // Training code next to its runs is an experiment first
+                if has_ml_marker() {
+                    return Some(Self::MachineLearning);
+                }
+
                 // Check if there are Python artifacts
-                if Self::has_any_artifact(path, Self::Python.artifact_directories()) {
+                if has_artifact_of(Self::Python) {
                     return Some(Self::Python);
                 }
             }
-        }
-
-        None
-    }
-
-    /// Helper: Check if a directory contains a specific file
-    fn has_file(dir: &Path, file_name: &str) -> bool {
-        dir.join(file_name).exists()
-    }
-
-    /// Helper: Check if a directory contains any of the specified artifacts
-    fn has_any_artifact(dir: &Path, artifacts: &[&str]) -> bool {
-        artifacts.iter().any(|artifact| {
-            let artifact_path = dir.join(artifact);
-            artifact_path.exists()
-        })
-    }
-}
-
-// ============================================================================
-// Project Structure
-// ============================================================================
-
-/// Represents a detected development project
-#[derive(Debug, Clone)]
-pub struct Project {
-    /// The type of project detected
-    pub project_type: ProjectType,
-    /// The root path of the project
-    pub path: PathBuf,
-}
-
-impl Project {
-    /// Creates a new Project instance
-    pub fn new(project_type: ProjectType, path: PathBuf) -> Self {
-        Self { project_type, path }
-    }
-
-    /// Returns the display name of the project (usually the directory name)
-    pub fn display_name(&self) -> String {
-        self.path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Unknown")
-            .to_string()
-    }
-
-    /// Calculates the total size of artifact directories in bytes
-    pub fn calculate_artifact_size(&self, options: &ScanOptions) -> u64 {
-        let mut total_size = 0u64;
-
-        for artifact_dir in self.project_type.artifact_directories() {
-            let artifact_path = self.path.join(artifact_dir);
-            if artifact_path.exists() {
-                total_size += calculate_directory_size(&artifact_path, options);
-            }
-        }
-
-        total_size
-    }
+            None
+        };
 
-    /// Gets the last modified time of the project
-    pub fn last_modified(&self, options: &ScanOptions) -> Result<SystemTime, std::io::Error> {
-        let metadata = fs::metadata(&self.path)?;
-        let mut most_recent = metadata.modified()?;
-
-        // Walk through the project to find the most recent modification
-        let walker = walkdir::WalkDir::new(&self.path)
-            .follow_links(options.follow_symlinks)
-            .same_file_system(options.same_filesystem);
-
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
-            if let Ok(metadata) = entry.metadata() {
-                if let Ok(modified) = metadata.modified() {
-                    if modified > most_recent {
-                        most_recent = modified;
-                    }
+        let mut types: Vec<Self> = Vec::new();
+        for file_name in names {
+            if let Some(project_type) = detect_name(file_name.as_ref()) {
+                // Further types only count next to their artifacts, so a stray
+                // Dockerfile doesn't turn every project into two
+                if !types.contains(&project_type)
+                    && (types.is_empty() || has_artifact_of(project_type))
+                {
+                    types.push(project_type);
                 }
             }
         }
 
-        Ok(most_recent)
-    }
-
-    /// Cleans (deletes) all artifact directories for this project
-    pub fn clean(&self) -> Result<u64, CleanError> {
-        let mut total_deleted = 0u64;
-        let mut errors = Vec::new();
-
-        for artifact_dir in self.project_type.artifact_directories() {
-            let artifact_path = self.path.join(artifact_dir);
-
-            if !artifact_path.exists() {
-                continue;
-            }
-
-            // Calculate size before deletion
-            let size = calculate_directory_size(&artifact_path, &ScanOptions::default());
-
-            // Attempt to delete the directory
-            match fs::remove_dir_all(&artifact_path) {
-                Ok(_) => {
-                    total_deleted += size;
-                }
-                Err(e) => {
-                    errors.push((artifact_path.clone(), e));
-                }
-            }
+        if types.is_empty() && has_ml_marker() {
+            types.push(Self::MachineLearning);
         }
 
-        if errors.is_empty() {
-            Ok(total_deleted)
-        } else {
-            Err(CleanError::PartialFailure {
-                deleted: total_deleted,
-                errors,
-            })
-        }
+        types
     }
 }
 
-// ============================================================================
-// Scanning Configuration
-// ============================================================================
-
-/// Options for scanning directories
-#[derive(Debug, Clone)]
-pub struct ScanOptions {
-    /// Whether to follow symbolic links
-    pub follow_symlinks: bool,
-    /// Whether to stay on the same filesystem
-    pub same_filesystem: bool,
-    /// Minimum age in seconds for projects to be included
-    pub min_age_seconds: u64,
-}
-
-impl Default for ScanOptions {
-    fn default() -> Self {
-        Self {
-            follow_symlinks: false,
-            same_filesystem: true,
-            min_age_seconds: 0,
-        }
+/// Directories left behind by experiment trackers and DVC
+///
+/// `checkpoints` is too common a name to mark a project on its own.
+const ML_MARKERS: &[&str] = &["wandb", "mlruns", "lightning_logs", ".dvc"];
+
+impl FromStr for ProjectType {
+    type Err = String;
+
+    /// Parses a project type from its identifier or display name (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|t| t.id().eq_ignore_ascii_case(s) || t.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown project type: {}", s))
     }
 }
 
-// ============================================================================
-// Scanning Functions
-// ============================================================================
-
-/// Scans a directory recursively to find development projects
-pub fn scan_directory<P: AsRef<Path>>(
-    path: P,
-    options: &ScanOptions,
-) -> impl Iterator<Item = Result<Project, ScanError>> {
-    let path = path.as_ref().to_path_buf();
-    let options = options.clone();
-
-    // Create a walkdir iterator with the specified options
-    let walker = walkdir::WalkDir::new(&path)
-        .follow_links(options.follow_symlinks)
-        .same_file_system(options.same_filesystem)
-        .into_iter();
-
-    // Filter and map entries to projects
-    walker.filter_map(move |entry| {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => return Some(Err(ScanError::WalkError(e))),
-        };
-
-        // Only process directories
-        if !entry.file_type().is_dir() {
-            return None;
-        }
-
-        // Skip hidden directories (starting with .)
-        if entry.file_name().to_string_lossy().starts_with('.') {
-            return None;
-        }
-
-        let dir_path = entry.path();
-
-        // Try to detect project type
-        if let Some(project_type) = ProjectType::detect_from_directory(dir_path) {
-            let project = Project::new(project_type, dir_path.to_path_buf());
-
-            // Check age filter if specified
-            if options.min_age_seconds > 0 {
-                if let Ok(last_modified) = project.last_modified(&options) {
-                    if let Ok(elapsed) = last_modified.elapsed() {
-                        if elapsed.as_secs() < options.min_age_seconds {
-                            return None; // Too recent, skip
-                        }
-                    }
-                }
-            }
-
-            return Some(Ok(project));
-        }
-
-        None
-    })
-}
-
-/// Calculates the total size of a directory in bytes
-pub fn calculate_directory_size<P: AsRef<Path>>(path: P, options: &ScanOptions) -> u64 {
-    let walker = walkdir::WalkDir::new(path.as_ref())
-        .follow_links(options.follow_symlinks)
-        .same_file_system(options.same_filesystem);
-
-    walker
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter_map(|e| e.metadata().ok())
-        .map(|m| m.len())
-        .sum()
+/// What kind of artifacts a directory holds
+///
+/// Environments are expensive to recreate and experiment results may not be
+/// reproducible at all, so both are only cleaned on request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtifactCategory {
+    /// Build outputs and dependencies (the default)
+    Build,
+    /// Tool caches that are cheap to regenerate (e.g., `__pycache__`)
+    Cache,
+    /// Installed environments (e.g., Python virtualenvs)
+    Environment,
+    /// Experiment runs and model checkpoints (e.g., `wandb`, `mlruns`)
+    Experiment,
 }
 
 // ============================================================================
@@ -467,8 +728,8 @@ pub fn format_size(bytes: u64) -> String {
     format!("{:.1} {}", size, UNITS[unit_index])
 }
 
-/// Formats elapsed time into a human-readable string (e.g., "2 days ago")
-pub fn format_elapsed_time(seconds: u64) -> String {
+/// Parses an age string (e.g., "30d", "2w", "6M") into seconds
+pub fn parse_age(input: &str) -> Result<u64, String> {
     const MINUTE: u64 = 60;
     const HOUR: u64 = MINUTE * 60;
     const DAY: u64 = HOUR * 24;
@@ -476,90 +737,196 @@ pub fn format_elapsed_time(seconds: u64) -> String {
     const MONTH: u64 = DAY * 30;
     const YEAR: u64 = DAY * 365;
 
-    let (value, unit) = match seconds {
-        s if s < MINUTE => (s, "second"),
-        s if s < HOUR => (s / MINUTE, "minute"),
-        s if s < DAY => (s / HOUR, "hour"),
-        s if s < WEEK => (s / DAY, "day"),
-        s if s < MONTH => (s / WEEK, "week"),
-        s if s < YEAR => (s / MONTH, "month"),
-        s => (s / YEAR, "year"),
+    if input.is_empty() {
+        return Err("Age filter cannot be empty".to_string());
+    }
+
+    // Split into number and unit; the unit is the last character, which need not be ASCII
+    let split = input.char_indices().next_back().map_or(0, |(i, _)| i);
+    let (num_str, unit) = input.split_at(split);
+
+    let number: u64 = num_str
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", num_str))?;
+
+    let multiplier = match unit {
+        "m" => MINUTE,
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => WEEK,
+        "M" => MONTH,
+        "y" => YEAR,
+        _ => return Err(format!("Invalid unit: {}. Use m, h, d, w, M, or y", unit)),
     };
 
-    let plural = if value == 1 { "" } else { "s" };
-    format!("{} {}{} ago", value, unit, plural)
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Age too large: {}", input))
 }
 
-// ============================================================================
-// Error Types
-// ============================================================================
+/// Parses a size string (e.g., "500M", "50G", "1.5TB") into bytes
+///
+/// Units are binary (1K = 1024 bytes) to match `format_size`; a bare number is bytes.
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Size cannot be empty".to_string());
+    }
 
-/// Errors that can occur during scanning
-#[derive(Debug)]
-pub enum ScanError {
-    /// Error from walkdir
-    WalkError(walkdir::Error),
-    /// IO error
-    IoError(std::io::Error),
-}
+    // Split into number and unit
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (num_str, unit) = input.split_at(split);
+
+    let number: f64 = num_str
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", num_str))?;
+
+    let exponent = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 1,
+        "M" | "MB" | "MIB" => 2,
+        "G" | "GB" | "GIB" => 3,
+        "T" | "TB" | "TIB" => 4,
+        _ => return Err(format!("Invalid unit: {}. Use B, K, M, G, or T", unit)),
+    };
 
-impl fmt::Display for ScanError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::WalkError(e) => write!(f, "Walk error: {}", e),
-            Self::IoError(e) => write!(f, "IO error: {}", e),
-        }
-    }
+    Ok((number * 1024f64.powi(exponent)) as u64)
 }
 
-impl Error for ScanError {}
-
-impl From<walkdir::Error> for ScanError {
-    fn from(e: walkdir::Error) -> Self {
-        Self::WalkError(e)
-    }
+/// Matches a relative path against an artifact pattern
+///
+/// Each component is matched with [`matches_wildcard`], except `**`, which
+/// matches any number of directories, including none.
+#[cfg(feature = "fs")]
+pub(crate) fn matches_artifact(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let path: Vec<&str> = path.split(['/', '\\']).filter(|c| !c.is_empty()).collect();
+    matches_components(&pattern, &path)
 }
 
-impl From<std::io::Error> for ScanError {
-    fn from(e: std::io::Error) -> Self {
-        Self::IoError(e)
+#[cfg(feature = "fs")]
+fn matches_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_components(rest, &path[skip..])),
+        Some((component, rest)) => path.split_first().is_some_and(|(name, path)| {
+            matches_wildcard(component, name) && matches_components(rest, path)
+        }),
     }
 }
 
-/// Errors that can occur during cleaning
-#[derive(Debug)]
-pub enum CleanError {
-    /// Complete failure to clean
-    IoError(std::io::Error),
-    /// Some directories were cleaned, but others failed
-    PartialFailure {
-        deleted: u64,
-        errors: Vec<(PathBuf, std::io::Error)>,
-    },
+/// Returns true if `name` is a build leftover of one of the `.tex` sources in `names`
+///
+/// Like `latexmk -c`, an extension pattern such as `*.log` only matches the
+/// source's own `<stem>.log`, so a `server.log` next to `notes.tex` is no
+/// leftover. A `.bbl` only counts when a `.bib` file can regenerate it, as
+/// arXiv submissions keep theirs on purpose. Other patterns (`_minted*`) match
+/// as usual.
+pub(crate) fn is_latex_leftover<S: AsRef<str>>(names: &[S], name: &str) -> bool {
+    let stems: Vec<&str> = names
+        .iter()
+        .filter_map(|n| n.as_ref().strip_suffix(".tex"))
+        .filter(|stem| !stem.is_empty())
+        .collect();
+    let has_bib = names.iter().any(|n| n.as_ref().ends_with(".bib"));
+
+    ProjectType::LaTeX
+        .artifact_directories()
+        .iter()
+        .any(|pattern| match pattern.strip_prefix('*') {
+            Some(".bbl") if !has_bib => false,
+            Some(suffix) => stems
+                .iter()
+                .any(|stem| name.strip_prefix(*stem) == Some(suffix)),
+            None => matches_wildcard(pattern, name),
+        })
 }
 
-impl fmt::Display for CleanError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::IoError(e) => write!(f, "Clean error: {}", e),
-            Self::PartialFailure { deleted, errors } => {
-                write!(
-                    f,
-                    "Partially cleaned ({} bytes), {} errors occurred",
-                    deleted,
-                    errors.len()
-                )
-            }
+/// Matches a file name against a pattern with `*` and `?` wildcards
+pub(crate) fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Iterative matcher with backtracking to the most recent `*`
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
         }
     }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Formats elapsed time into a human-readable string (e.g., "2 days ago")
+pub fn format_elapsed_time(seconds: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = MINUTE * 60;
+    const DAY: u64 = HOUR * 24;
+    const WEEK: u64 = DAY * 7;
+    const MONTH: u64 = DAY * 30;
+    const YEAR: u64 = DAY * 365;
+
+    let (value, unit) = match seconds {
+        s if s < MINUTE => (s, "second"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < WEEK => (s / DAY, "day"),
+        s if s < MONTH => (s / WEEK, "week"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s => (s / YEAR, "year"),
+    };
+
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{} {}{} ago", value, unit, plural)
 }
 
-impl Error for CleanError {}
+/// Formats a point in time as an RFC 3339 timestamp in UTC (e.g., "2025-10-16T09:30:00Z")
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
 
-impl From<std::io::Error> for CleanError {
-    fn from(e: std::io::Error) -> Self {
-        Self::IoError(e)
-    }
+/// Converts days since the Unix epoch to a (year, month, day) date
+///
+/// Howard Hinnant's `civil_from_days` algorithm, restricted to dates after 1970.
+pub(crate) fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
 }
 
 // ============================================================================
@@ -590,6 +957,79 @@ mod tests {
         assert_eq!(format_elapsed_time(86400), "1 day ago");
     }
 
+    #[test]
+    fn test_format_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_760_607_000)),
+            "2025-10-16T09:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_matches_wildcard() {
+        assert!(matches_wildcard("*.egg-info", "pkg.egg-info"));
+        assert!(matches_wildcard("cmake-build-*", "cmake-build-debug"));
+        assert!(matches_wildcard("bazel-?ut", "bazel-out"));
+        assert!(matches_wildcard("target", "target"));
+        assert!(!matches_wildcard("*.egg-info", "egg-info.txt"));
+        assert!(!matches_wildcard("target", "targets"));
+    }
+
+    #[test]
+    fn test_matches_artifact() {
+        assert!(matches_artifact("**/__pycache__", "__pycache__"));
+        assert!(matches_artifact("**/__pycache__", "pkg/sub/__pycache__"));
+        assert!(matches_artifact("**/*.egg-info", "src/pkg.egg-info"));
+        assert!(matches_artifact("android/.gradle", "android/.gradle"));
+        assert!(!matches_artifact("android/.gradle", ".gradle"));
+        assert!(!matches_artifact("**/__pycache__", "__pycache__/cache"));
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30m"), Ok(1800));
+        assert_eq!(parse_age("2d"), Ok(172_800));
+        assert_eq!(parse_age("1w"), Ok(604_800));
+        assert!(parse_age("").is_err());
+        assert!(parse_age("10x").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("5é").is_err());
+        assert!(parse_age("99999999999999999y").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("100M"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("1.5GB"), Ok(1536 * 1024 * 1024));
+        assert_eq!(parse_size("2 TiB"), Ok(2 << 40));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("G").is_err());
+    }
+
+    #[test]
+    fn test_project_type_from_str() {
+        assert_eq!("rust".parse(), Ok(ProjectType::Rust));
+        assert_eq!("Node.js".parse(), Ok(ProjectType::Node));
+        assert_eq!("haskell-stack".parse(), Ok(ProjectType::HaskellStack));
+        assert!("cobol".parse::<ProjectType>().is_err());
+        for project_type in ProjectType::ALL {
+            assert_eq!(project_type.id().parse(), Ok(*project_type));
+        }
+    }
+
+    #[test]
+    fn test_project_type_serializes_as_id() {
+        for project_type in ProjectType::ALL {
+            let json = serde_json::to_string(project_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", project_type.id()));
+        }
+    }
+
     #[test]
     fn test_project_type_names() {
         assert_eq!(ProjectType::Rust.name(), "Rust");
@@ -600,5 +1040,73 @@ mod tests {
         assert_eq!(ProjectType::Terraform.name(), "Terraform");
         assert_eq!(ProjectType::Docker.name(), "Docker");
         assert_eq!(ProjectType::Bazel.name(), "Bazel");
+        assert_eq!(ProjectType::OCaml.name(), "OCaml");
+        assert_eq!(ProjectType::Nim.name(), "Nim");
+        assert_eq!(ProjectType::Julia.name(), "Julia");
+        assert_eq!(ProjectType::R.name(), "R");
+        assert_eq!(ProjectType::MachineLearning.name(), "Machine Learning");
+        assert_eq!(ProjectType::Xcode.name(), "Xcode");
+        assert_eq!(ProjectType::LaTeX.name(), "LaTeX");
+        assert_eq!(ProjectType::Meson.name(), "Meson");
+        assert_eq!(ProjectType::Autotools.name(), "Autotools");
+    }
+
+    #[test]
+    fn test_detect_from_file_names() {
+        let detect = |names: &[&str]| ProjectType::detect_from_file_names(names);
+
+        assert_eq!(detect(&["src", "Cargo.toml"]), Some(ProjectType::Rust));
+        assert_eq!(detect(&["App.csproj"]), Some(ProjectType::DotNet));
+        assert_eq!(
+            detect(&["App.csproj", "project.godot"]),
+            Some(ProjectType::Godot)
+        );
+        assert_eq!(
+            detect(&["main.py", "pkg.egg-info"]),
+            Some(ProjectType::Python)
+        );
+        assert_eq!(
+            detect(&["Assets", "Packages", "ProjectSettings"]),
+            Some(ProjectType::Unity)
+        );
+        assert_eq!(
+            detect(&["Game.csproj", "Assets", "ProjectSettings"]),
+            Some(ProjectType::Unity)
+        );
+        assert_eq!(detect(&["ProjectSettings"]), None);
+        assert_eq!(
+            ProjectType::detect_all_from_file_names(&[
+                "package.json",
+                "Cargo.toml",
+                "target",
+                "Dockerfile"
+            ]),
+            vec![ProjectType::Node, ProjectType::Rust]
+        );
+        assert_eq!(detect(&["dune-project", "bin"]), Some(ProjectType::OCaml));
+        assert_eq!(detect(&["src", "tool.nimble"]), Some(ProjectType::Nim));
+        assert_eq!(
+            detect(&["train.py", "__pycache__", "wandb"]),
+            Some(ProjectType::MachineLearning)
+        );
+        assert_eq!(detect(&["notes", "checkpoints"]), None);
+        assert_eq!(
+            detect(&["Weather", "Weather.xcodeproj"]),
+            Some(ProjectType::Xcode)
+        );
+        assert_eq!(
+            detect(&["thesis.tex", "thesis.aux", "thesis.pdf"]),
+            Some(ProjectType::LaTeX)
+        );
+        assert_eq!(detect(&["thesis.tex", "figures"]), None);
+        assert_eq!(detect(&["notes.tex", "server.log", "slurm-42.out"]), None);
+        assert_eq!(
+            detect(&["configure", "Makefile.in"]),
+            Some(ProjectType::Autotools)
+        );
+        assert_eq!(detect(&["configure", "README"]), None);
+        // Python needs artifacts to count as a project
+        assert_eq!(detect(&["main.py", "README.md"]), None);
+        assert_eq!(detect(&[]), None);
     }
 }
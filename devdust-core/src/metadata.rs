@@ -0,0 +1,225 @@
+//! Project names and versions from manifests
+//!
+//! Folder names such as `app` or `backend` say little about what a project is,
+//! so frontends show the name and version declared in its manifest next to
+//! them. TOML and JSON manifests are parsed properly; for the others
+//! (`pubspec.yaml`, `mix.exs`, `go.mod`, `project.godot`) the few top-level
+//! lines needed are matched directly, which is enough for the common layouts.
+
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+use crate::ProjectType;
+
+/// Name and version declared in a project's manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProjectMetadata {
+    /// Package name, e.g. `name` in `Cargo.toml` or `package.json`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Package version as written in the manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl ProjectMetadata {
+    /// Returns true if the manifest declared neither a name nor a version
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.version.is_none()
+    }
+}
+
+/// Reads the metadata of a project of the given type
+///
+/// Returns empty metadata for types without a manifest that names the
+/// project, and when the manifest is missing or can't be parsed.
+pub(crate) fn read(path: &Path, project_type: ProjectType) -> ProjectMetadata {
+    let (file_name, parse): (&str, fn(&str) -> Option<ProjectMetadata>) = match project_type {
+        ProjectType::Rust => ("Cargo.toml", cargo),
+        ProjectType::Node => ("package.json", json),
+        ProjectType::Composer => ("composer.json", json),
+        ProjectType::Python => ("pyproject.toml", pyproject),
+        ProjectType::Dart => ("pubspec.yaml", pubspec),
+        ProjectType::Elixir => ("mix.exs", mix),
+        ProjectType::Go => ("go.mod", go_mod),
+        ProjectType::Godot => ("project.godot", godot),
+        _ => return ProjectMetadata::default(),
+    };
+
+    fs::read_to_string(path.join(file_name))
+        .ok()
+        .and_then(|contents| parse(&contents))
+        .unwrap_or_default()
+}
+
+/// Returns a TOML string value, ignoring tables such as `version.workspace = true`
+fn string(value: Option<&toml::Value>) -> Option<String> {
+    value.and_then(toml::Value::as_str).map(String::from)
+}
+
+/// `[package]` in `Cargo.toml`; workspace roots without one have no name
+fn cargo(contents: &str) -> Option<ProjectMetadata> {
+    let manifest: toml::Table = contents.parse().ok()?;
+    let package = manifest.get("package")?;
+    Some(ProjectMetadata {
+        name: string(package.get("name")),
+        version: string(package.get("version")),
+    })
+}
+
+/// `[project]` (PEP 621), falling back to `[tool.poetry]`
+fn pyproject(contents: &str) -> Option<ProjectMetadata> {
+    let manifest: toml::Table = contents.parse().ok()?;
+    let project = manifest
+        .get("project")
+        .or_else(|| manifest.get("tool")?.get("poetry"))?;
+    Some(ProjectMetadata {
+        name: string(project.get("name")),
+        version: string(project.get("version")),
+    })
+}
+
+/// Top-level `name` and `version` of `package.json` or `composer.json`
+fn json(contents: &str) -> Option<ProjectMetadata> {
+    let manifest: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let field = |key: &str| manifest.get(key)?.as_str().map(String::from);
+    Some(ProjectMetadata {
+        name: field("name"),
+        version: field("version"),
+    })
+}
+
+/// Top-level `name:` and `version:` keys of `pubspec.yaml`
+fn pubspec(contents: &str) -> Option<ProjectMetadata> {
+    let field = |key: &str| {
+        contents.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix(':')?;
+            unquote(value.split(" #").next()?)
+        })
+    };
+    Some(ProjectMetadata {
+        name: field("name"),
+        version: field("version"),
+    })
+}
+
+/// `app: :name` and `version: "x.y.z"` in the `project` keyword list of `mix.exs`
+///
+/// A version taken from a module attribute (`version: @version`) is not resolved.
+fn mix(contents: &str) -> Option<ProjectMetadata> {
+    let field = |key: &str| {
+        contents.lines().find_map(|line| {
+            let value = line.trim().strip_prefix(key)?.strip_prefix(':')?;
+            let value = value.trim().trim_end_matches(',');
+            if value.starts_with('@') {
+                return None;
+            }
+            unquote(value.trim_start_matches(':'))
+        })
+    };
+    Some(ProjectMetadata {
+        name: field("app"),
+        version: field("version"),
+    })
+}
+
+/// The module path of `go.mod`; Go modules are versioned by tags, not in the file
+fn go_mod(contents: &str) -> Option<ProjectMetadata> {
+    let module = contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))?;
+    Some(ProjectMetadata {
+        name: unquote(module),
+        version: None,
+    })
+}
+
+/// `config/name` and `config/version` in `project.godot`
+fn godot(contents: &str) -> Option<ProjectMetadata> {
+    let field = |key: &str| {
+        contents
+            .lines()
+            .find_map(|line| unquote(line.strip_prefix(key)?.strip_prefix('=')?))
+    };
+    Some(ProjectMetadata {
+        name: field("config/name"),
+        version: field("config/version"),
+    })
+}
+
+/// Trims whitespace and surrounding quotes; empty values count as missing
+fn unquote(value: &str) -> Option<String> {
+    let value = value.trim().trim_matches(['"', '\'']).trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(name: &str, version: Option<&str>) -> Option<ProjectMetadata> {
+        Some(ProjectMetadata {
+            name: Some(name.to_string()),
+            version: version.map(String::from),
+        })
+    }
+
+    #[test]
+    fn test_parse_manifests() {
+        assert_eq!(
+            cargo("[package]\nname = \"api\"\nversion = \"0.3.1\"\n"),
+            metadata("api", Some("0.3.1"))
+        );
+        assert_eq!(
+            cargo("[package]\nname = \"api\"\nversion.workspace = true\n"),
+            metadata("api", None)
+        );
+        assert_eq!(cargo("[workspace]\nmembers = [\"api\"]\n"), None);
+        assert_eq!(
+            json(r#"{"name": "@acme/web", "version": "2.0.0", "private": true}"#),
+            metadata("@acme/web", Some("2.0.0"))
+        );
+        assert_eq!(
+            pyproject("[tool.poetry]\nname = \"etl\"\nversion = \"1.0\"\n"),
+            metadata("etl", Some("1.0"))
+        );
+        assert_eq!(
+            pubspec("name: mobile_app # the app\ndescription: x\nversion: 1.2.0+3\n"),
+            metadata("mobile_app", Some("1.2.0+3"))
+        );
+        assert_eq!(
+            mix("  def project do\n    [\n      app: :billing,\n      version: \"0.1.0\",\n"),
+            metadata("billing", Some("0.1.0"))
+        );
+        assert_eq!(
+            go_mod("module github.com/acme/cli\n\ngo 1.22\n"),
+            metadata("github.com/acme/cli", None)
+        );
+        assert_eq!(
+            godot("[application]\n\nconfig/name=\"Space Game\"\n"),
+            metadata("Space Game", None)
+        );
+    }
+
+    #[test]
+    fn test_read_from_project() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("package.json"),
+            r#"{"name": "backend", "version": "4.1.0"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(read(root.path(), ProjectType::Node)),
+            metadata("backend", Some("4.1.0"))
+        );
+        assert!(read(root.path(), ProjectType::Rust).is_empty());
+        assert!(read(root.path(), ProjectType::Unity).is_empty());
+    }
+}
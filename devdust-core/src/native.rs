@@ -0,0 +1,140 @@
+//! Ecosystem clean commands
+//!
+//! Some build tools know best what they produced: `cargo clean` also handles a
+//! relocated target directory, `dotnet clean` only removes outputs of the
+//! current configuration and `bazel clean --expunge` shuts down the Bazel
+//! server before deleting its output base. For those types the tool's own
+//! cleaner can be run instead of deleting artifact directories; if the tool is
+//! not installed, fails or runs into the timeout, the directories are deleted
+//! as usual.
+
+use std::{
+    io,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::ProjectType;
+
+/// How often to check whether the clean command has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl ProjectType {
+    /// Returns the ecosystem's own clean command for this type, if it has one
+    pub fn native_clean_command(&self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Rust => Some(&["cargo", "clean"]),
+            Self::Gradle => Some(&["gradle", "clean"]),
+            Self::DotNet => Some(&["dotnet", "clean"]),
+            Self::Dart => Some(&["flutter", "clean"]),
+            Self::Bazel => Some(&["bazel", "clean", "--expunge"]),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the native clean command for a project in `dir`
+///
+/// Prefers a Gradle wrapper in the project over a global `gradle`. Fails if the
+/// command cannot be started, exits unsuccessfully or runs longer than `timeout`.
+pub(crate) fn run(project_type: ProjectType, dir: &Path, timeout: Duration) -> io::Result<()> {
+    let Some(command) = project_type.native_clean_command() else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no native clean command for this project type",
+        ));
+    };
+
+    let program = match gradle_wrapper(project_type, dir) {
+        Some(wrapper) => wrapper,
+        None => command[0].to_string(),
+    };
+    run_command(&program, command, dir, timeout)
+}
+
+/// Runs `command` in `dir` with `program` as its executable
+///
+/// Fails if the command cannot be started, exits unsuccessfully or runs longer
+/// than `timeout`.
+pub(crate) fn run_command(
+    program: &str,
+    command: &[&str],
+    dir: &Path,
+    timeout: Duration,
+) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(&command[1..])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other(format!(
+                    "`{}` failed ({})",
+                    command.join(" "),
+                    status
+                )))
+            };
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("`{}` timed out", command.join(" ")),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Returns the project's Gradle wrapper script, if it has one
+fn gradle_wrapper(project_type: ProjectType, dir: &Path) -> Option<String> {
+    if project_type != ProjectType::Gradle {
+        return None;
+    }
+
+    let name = if cfg!(windows) {
+        "gradlew.bat"
+    } else {
+        "gradlew"
+    };
+    let wrapper = dir.join(name);
+    wrapper
+        .is_file()
+        .then(|| wrapper.to_string_lossy().into_owned())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_clean_commands() {
+        assert_eq!(
+            ProjectType::Rust.native_clean_command(),
+            Some(&["cargo", "clean"][..])
+        );
+        assert_eq!(ProjectType::Node.native_clean_command(), None);
+    }
+
+    #[test]
+    fn test_unsupported_type_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run(ProjectType::Node, dir.path(), Duration::from_secs(1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,160 @@
+//! Artifact directories whose project is gone
+//!
+//! Deleting a project's sources often leaves its `target/` or `node_modules`
+//! behind. Without a marker file next to them no project is detected, so they
+//! are never offered for cleaning. With [`ScanOptions::orphans`] set, a
+//! directory that is not a project but contains one of a few unmistakable
+//! artifact directories is reported as an orphaned project owning just those.
+//! Directories holding a valid `CACHEDIR.TAG` count as such artifacts too.
+//!
+//! [`ScanOptions::orphans`]: crate::ScanOptions::orphans
+
+use std::{fs, path::Path};
+
+use crate::{cachedir, CustomRule, Project, ProjectType};
+
+/// An artifact directory name distinctive enough to recognize on its own
+struct Signature {
+    name: &'static str,
+    project_type: ProjectType,
+    /// Entries one of which must exist inside, for names that are common words
+    contents: &'static [&'static str],
+}
+
+/// Generic names such as `build` or `dist` are left out on purpose: outside a
+/// project there is no telling whether they hold anything disposable.
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        name: "node_modules",
+        project_type: ProjectType::Node,
+        contents: &[],
+    },
+    Signature {
+        name: "target",
+        project_type: ProjectType::Rust,
+        contents: &["CACHEDIR.TAG", ".rustc_info.json"],
+    },
+    Signature {
+        name: ".dart_tool",
+        project_type: ProjectType::Dart,
+        contents: &[],
+    },
+    Signature {
+        name: ".stack-work",
+        project_type: ProjectType::HaskellStack,
+        contents: &[],
+    },
+    Signature {
+        name: ".zig-cache",
+        project_type: ProjectType::Zig,
+        contents: &[],
+    },
+    Signature {
+        name: "zig-cache",
+        project_type: ProjectType::Zig,
+        contents: &[],
+    },
+    Signature {
+        name: ".terraform",
+        project_type: ProjectType::Terraform,
+        contents: &["providers", "modules"],
+    },
+];
+
+/// Returns the signature `dir` matches, if it looks like an artifact directory
+fn signature(dir: &Path) -> Option<&'static Signature> {
+    let name = dir.file_name()?.to_str()?;
+    SIGNATURES.iter().find(|signature| {
+        signature.name == name
+            && (signature.contents.is_empty()
+                || signature
+                    .contents
+                    .iter()
+                    .any(|entry| dir.join(entry).exists()))
+    })
+}
+
+/// Returns true if `dir` looks like an artifact directory, owned or not
+///
+/// The walk doesn't descend into these, so packages inside an orphaned
+/// `node_modules` are not reported as projects of their own.
+pub(crate) fn is_artifact_like(dir: &Path) -> bool {
+    signature(dir).is_some() || cachedir::is_tagged(dir)
+}
+
+/// Returns an orphaned project for `dir` if it holds artifact directories
+///
+/// Must only be called for directories that are not projects themselves.
+pub(crate) fn detect(dir: &Path) -> Option<Project> {
+    let mut found: Vec<(String, &str)> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| signature(&e.path()))
+        .map(|signature| (signature.name.to_string(), signature.project_type.name()))
+        .collect();
+    for name in cachedir::tagged_children(dir) {
+        if !found.iter().any(|(found_name, _)| *found_name == name) {
+            found.push((name, "Tagged cache"));
+        }
+    }
+    if found.is_empty() {
+        return None;
+    }
+    found.sort();
+
+    let mut type_names: Vec<&str> = found.iter().map(|(_, name)| *name).collect();
+    type_names.sort();
+    type_names.dedup();
+    let rule = CustomRule {
+        name: format!("{}, orphaned", type_names.join(", ")),
+        markers: Vec::new(),
+        artifacts: found.into_iter().map(|(name, _)| name).collect(),
+    };
+
+    let mut project = Project::from_rule(rule, dir.to_path_buf());
+    project.orphaned = true;
+    Some(project)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_orphaned_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let old = root.path().join("old-api");
+        fs::create_dir_all(old.join("node_modules").join("left-pad")).unwrap();
+        fs::create_dir_all(old.join("target").join("debug")).unwrap();
+        fs::write(old.join("target").join("CACHEDIR.TAG"), "").unwrap();
+        fs::create_dir_all(old.join("dist")).unwrap();
+
+        let project = detect(&old).unwrap();
+        assert!(project.orphaned);
+        assert_eq!(project.type_name(), "Node.js, Rust, orphaned");
+        assert_eq!(
+            project.artifact_directories(),
+            vec!["node_modules", "target"]
+        );
+
+        // A plain `target` folder is not taken for a Rust build directory
+        let notes = root.path().join("notes");
+        fs::create_dir_all(notes.join("target")).unwrap();
+        assert!(detect(&notes).is_none());
+        assert!(!is_artifact_like(&notes.join("target")));
+
+        // Any tagged cache qualifies
+        fs::create_dir_all(notes.join("renders")).unwrap();
+        cachedir::tag(&notes.join("renders")).unwrap();
+        assert!(is_artifact_like(&notes.join("renders")));
+        assert_eq!(
+            detect(&notes).unwrap().type_name(),
+            "Tagged cache, orphaned"
+        );
+    }
+}
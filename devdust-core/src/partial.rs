@@ -0,0 +1,396 @@
+//! Partial cleaning strategies
+//!
+//! Deleting a whole artifact directory is simple but throws away everything a
+//! build tool could have reused. Some project types know enough about their
+//! artifact layout to remove only the stale parts; a strategy returns the
+//! paths to remove for a project, and types without one fall back to a full
+//! clean.
+//!
+//! Rust is the only type with a strategy so far. It works like `cargo sweep`:
+//! cargo keeps one fingerprint directory per build unit in every profile
+//! directory (`target/debug/.fingerprint/<crate>-<hash>`), and all files that
+//! belong to the unit in `deps/`, `build/` and `.fingerprint/` carry the same
+//! `-<hash>` suffix. A unit is stale if it has not been used for a while or
+//! was built by a different compiler than the most recently used one.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{cargo_target, Project, ProjectType};
+
+/// Which stale artifacts a partial clean removes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartialClean {
+    /// Remove build units that have not been used for this many seconds
+    pub older_than_seconds: Option<u64>,
+    /// Remove build units made by a compiler other than the most recently used one
+    pub stale_toolchains: bool,
+    /// Remove debug builds entirely, keeping release builds
+    pub debug_only: bool,
+}
+
+impl PartialClean {
+    /// Returns true if no criterion is set, so nothing would be removed
+    pub fn is_empty(&self) -> bool {
+        self.older_than_seconds.is_none() && !self.stale_toolchains && !self.debug_only
+    }
+}
+
+impl ProjectType {
+    /// Returns true if this type has a partial cleaning strategy
+    pub fn supports_partial_clean(&self) -> bool {
+        matches!(self, Self::Rust)
+    }
+}
+
+/// Returns the files and directories a partial clean of `project` removes
+///
+/// Returns `None` if the project type has no partial strategy.
+pub(crate) fn plan(project: &Project, partial: &PartialClean) -> Option<Vec<PathBuf>> {
+    if cargo_target::is_shared_target(project) {
+        return Some(rust_plan(&project.path, partial));
+    }
+
+    // The other types' artifacts would be left behind
+    if project.custom_rule.is_some() || !project.additional_types.is_empty() {
+        return None;
+    }
+
+    match project.project_type {
+        ProjectType::Rust => Some(
+            project
+                .artifact_directories()
+                .into_iter()
+                // A relocated target directory is listed with its absolute path
+                .filter(|dir| *dir == "target" || Path::new(dir).is_absolute())
+                .flat_map(|dir| rust_plan(&project.path.join(dir), partial))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Rust
+// ============================================================================
+
+/// Subdirectories of a profile directory whose entries belong to build units
+const UNIT_DIRS: &[&str] = &["deps", "build", ".fingerprint"];
+
+/// A build unit found in a profile's `.fingerprint` directory
+struct Unit {
+    hash: String,
+    last_used: SystemTime,
+    /// Hash of the compiler that built the unit, as recorded by cargo
+    rustc: Option<u64>,
+}
+
+fn rust_plan(target: &Path, partial: &PartialClean) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut profiles = Vec::new();
+
+    for profile in profile_dirs(target) {
+        if partial.debug_only && profile.file_name().is_some_and(|n| n == "debug") {
+            paths.push(profile);
+        } else {
+            let mut units = read_units(&profile);
+            mark_linked_units(&profile, &mut units);
+            profiles.push((profile, units));
+        }
+    }
+
+    // The compiler used most recently is the one worth keeping artifacts for
+    let current_rustc = profiles
+        .iter()
+        .flat_map(|(_, units)| units)
+        .filter(|unit| unit.rustc.is_some())
+        .max_by_key(|unit| unit.last_used)
+        .and_then(|unit| unit.rustc);
+    let cutoff = partial
+        .older_than_seconds
+        .and_then(|secs| SystemTime::now().checked_sub(Duration::from_secs(secs)));
+
+    for (profile, units) in profiles {
+        let stale: HashSet<&str> = units
+            .iter()
+            .filter(|unit| {
+                cutoff.is_some_and(|cutoff| unit.last_used < cutoff)
+                    || (partial.stale_toolchains
+                        && unit.rustc.is_some()
+                        && unit.rustc != current_rustc)
+            })
+            .map(|unit| unit.hash.as_str())
+            .collect();
+
+        for dir in UNIT_DIRS {
+            let Ok(entries) = fs::read_dir(profile.join(dir)) else {
+                continue;
+            };
+            paths.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| {
+                        unit_hash(&entry.file_name().to_string_lossy())
+                            .is_some_and(|hash| stale.contains(hash))
+                    })
+                    .map(|entry| entry.path()),
+            );
+        }
+
+        // Incremental caches are named differently, so they go by age alone
+        if let (Some(cutoff), Ok(entries)) = (cutoff, fs::read_dir(profile.join("incremental"))) {
+            paths.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| last_used(&entry.path(), true) < cutoff)
+                    .map(|entry| entry.path()),
+            );
+        }
+    }
+
+    paths
+}
+
+/// Finds profile directories (`target/debug`, `target/<triple>/release`, ...)
+fn profile_dirs(target: &Path) -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .map(|entry| entry.path())
+            .collect()
+    };
+    let is_profile = |dir: &Path| dir.join(".fingerprint").is_dir();
+
+    let mut profiles = Vec::new();
+    for dir in subdirs(target) {
+        if is_profile(&dir) {
+            profiles.push(dir);
+        } else {
+            // Cross-compiled builds live one level deeper, below the target triple
+            profiles.extend(subdirs(&dir).into_iter().filter(|d| is_profile(d)));
+        }
+    }
+    profiles
+}
+
+/// Reads the build units of a profile directory
+fn read_units(profile: &Path) -> Vec<Unit> {
+    let Ok(entries) = fs::read_dir(profile.join(".fingerprint")) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let hash = unit_hash(&entry.file_name().to_string_lossy())?.to_string();
+            Some(Unit {
+                hash,
+                // Only modification times: reading the fingerprint below updates access times
+                last_used: last_used(&path, false),
+                rustc: read_rustc_hash(&path),
+            })
+        })
+        .collect()
+}
+
+/// Updates each unit's last use with the access times of its files in `deps/`
+///
+/// Linking a crate reads its `.rlib`/`.rmeta`, so this catches units that are
+/// still used but have not needed a rebuild in a while.
+fn mark_linked_units(profile: &Path, units: &mut [Unit]) {
+    let Ok(entries) = fs::read_dir(profile.join("deps")) else {
+        return;
+    };
+    let mut accessed: HashMap<String, SystemTime> = HashMap::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let (Some(hash), Ok(time)) = (
+            unit_hash(&name),
+            entry.metadata().and_then(|m| m.accessed()),
+        ) else {
+            continue;
+        };
+        let newest = accessed.entry(hash.to_string()).or_insert(time);
+        *newest = (*newest).max(time);
+    }
+
+    for unit in units {
+        if let Some(time) = accessed.get(&unit.hash) {
+            unit.last_used = unit.last_used.max(*time);
+        }
+    }
+}
+
+/// Extracts the unit hash from names like `libserde-1a2b3c4d.rlib` or `serde-1a2b3c4d`
+fn unit_hash(name: &str) -> Option<&str> {
+    let stem = name.split('.').next()?;
+    let (_, hash) = stem.rsplit_once('-')?;
+    (!hash.is_empty() && hash.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// Reads the compiler hash from the JSON fingerprints in a unit directory
+fn read_rustc_hash(unit_dir: &Path) -> Option<u64> {
+    fs::read_dir(unit_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .find_map(|entry| {
+            let contents = fs::read_to_string(entry.path()).ok()?;
+            let start = contents.find("\"rustc\":")? + "\"rustc\":".len();
+            let digits: String = contents[start..]
+                .chars()
+                .skip_while(|c| c.is_whitespace())
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse().ok()
+        })
+}
+
+/// Returns the most recent modification (and optionally access) time of the files below `path`
+///
+/// Directory times are ignored, since listing a directory updates its access time.
+fn last_used(path: &Path, include_access: bool) -> SystemTime {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| entry.metadata().ok())
+        .flat_map(|metadata| {
+            let accessed = include_access.then(|| metadata.accessed().ok()).flatten();
+            [metadata.modified().ok(), accessed]
+        })
+        .flatten()
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, FileTimes};
+
+    /// Creates a build unit in `profile` built by compiler `rustc`, last used `age` ago
+    fn make_unit(profile: &Path, name: &str, hash: &str, rustc: u64, age: Duration) {
+        let fingerprint = profile.join(".fingerprint").join(format!("{name}-{hash}"));
+        let build = profile.join("build").join(format!("{name}-{hash}"));
+        fs::create_dir_all(&fingerprint).unwrap();
+        fs::create_dir_all(&build).unwrap();
+        fs::create_dir_all(profile.join("deps")).unwrap();
+
+        let time = SystemTime::now() - age;
+        let times = FileTimes::new().set_accessed(time).set_modified(time);
+        for file in [
+            fingerprint.join(format!("lib-{name}.json")),
+            build.join("output"),
+            profile.join("deps").join(format!("lib{name}-{hash}.rlib")),
+        ] {
+            fs::write(&file, format!("{{\"rustc\":{rustc},\"features\":\"[]\"}}")).unwrap();
+            File::options()
+                .write(true)
+                .open(&file)
+                .unwrap()
+                .set_times(times)
+                .unwrap();
+        }
+    }
+
+    fn rust_project(root: &Path) -> Project {
+        let project = Project::new(ProjectType::Rust, root.to_path_buf());
+        let debug = root.join("target").join("debug");
+        let release = root.join("target").join("release");
+        make_unit(
+            &debug,
+            "old",
+            "aaaa1111",
+            1,
+            Duration::from_secs(40 * 86_400),
+        );
+        make_unit(&debug, "new", "bbbb2222", 2, Duration::ZERO);
+        make_unit(&release, "new", "cccc3333", 2, Duration::from_secs(3600));
+        project
+    }
+
+    fn names(paths: Vec<PathBuf>) -> Vec<String> {
+        let mut names: Vec<String> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_stale_toolchain_units() {
+        let root = tempfile::tempdir().unwrap();
+        let project = rust_project(root.path());
+
+        let partial = PartialClean {
+            stale_toolchains: true,
+            ..PartialClean::default()
+        };
+        assert_eq!(
+            names(plan(&project, &partial).unwrap()),
+            ["libold-aaaa1111.rlib", "old-aaaa1111", "old-aaaa1111"]
+        );
+    }
+
+    #[test]
+    fn test_units_older_than() {
+        let root = tempfile::tempdir().unwrap();
+        let project = rust_project(root.path());
+
+        let partial = PartialClean {
+            older_than_seconds: Some(30 * 86_400),
+            ..PartialClean::default()
+        };
+        assert_eq!(plan(&project, &partial).unwrap().len(), 3);
+
+        let partial = PartialClean {
+            older_than_seconds: Some(60),
+            ..PartialClean::default()
+        };
+        assert_eq!(plan(&project, &partial).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_debug_only() {
+        let root = tempfile::tempdir().unwrap();
+        let project = rust_project(root.path());
+
+        let partial = PartialClean {
+            debug_only: true,
+            ..PartialClean::default()
+        };
+        assert_eq!(
+            plan(&project, &partial).unwrap(),
+            vec![root.path().join("target").join("debug")]
+        );
+    }
+
+    #[test]
+    fn test_unit_hash() {
+        assert_eq!(unit_hash("libserde-1a2b3c4d.rlib"), Some("1a2b3c4d"));
+        assert_eq!(unit_hash("serde_json-0f0f"), Some("0f0f"));
+        assert_eq!(unit_hash("app"), None);
+        assert_eq!(unit_hash("build-script-build"), None);
+    }
+
+    #[test]
+    fn test_types_without_strategy() {
+        let project = Project::new(ProjectType::Node, PathBuf::from("web"));
+        assert_eq!(plan(&project, &PartialClean::default()), None);
+        assert!(!ProjectType::Node.supports_partial_clean());
+    }
+}
@@ -0,0 +1,135 @@
+//! Pinned projects
+//!
+//! Pinning a project at the clean prompt makes later runs skip it. Pins are
+//! stored as absolute project paths, one per line, in
+//! `$XDG_DATA_HOME/devdust/pins.txt`.
+
+use std::{
+    collections::BTreeSet,
+    env, fs, io,
+    path::{self, Path, PathBuf},
+};
+
+/// File name of the pin list inside the devdust data directory
+const PINS_FILE_NAME: &str = "pins.txt";
+
+/// The list of projects that are never offered for cleaning
+#[derive(Debug, Clone)]
+pub struct Pins {
+    path: PathBuf,
+}
+
+impl Pins {
+    /// Returns the default pin list location (`$XDG_DATA_HOME/devdust/pins.txt`)
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))?;
+
+        Some(base.join("devdust").join(PINS_FILE_NAME))
+    }
+
+    /// Opens the pin list at `path`; the file is created when the first project is pinned
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Opens the pin list at the default location
+    pub fn open_default() -> io::Result<Self> {
+        Self::default_path().map(Self::new).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "cannot determine data directory")
+        })
+    }
+
+    /// Returns the pin list file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the pinned project paths, sorted
+    ///
+    /// A missing file yields an empty list; blank lines are skipped.
+    pub fn list(&self) -> io::Result<BTreeSet<PathBuf>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(PathBuf::from)
+                .collect()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Pins a project; returns false if it was already pinned
+    pub fn add(&self, project: &Path) -> io::Result<bool> {
+        let mut pins = self.list()?;
+        let added = pins.insert(absolute(project));
+        if added {
+            self.save(&pins)?;
+        }
+        Ok(added)
+    }
+
+    /// Unpins a project; returns false if it was not pinned
+    pub fn remove(&self, project: &Path) -> io::Result<bool> {
+        let mut pins = self.list()?;
+        let removed = pins.remove(&absolute(project));
+        if removed {
+            self.save(&pins)?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self, pins: &BTreeSet<PathBuf>) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let contents: String = pins
+            .iter()
+            .map(|pin| format!("{}\n", pin.display()))
+            .collect();
+        fs::write(&self.path, contents)
+    }
+}
+
+/// Returns true if `project` is one of the pinned paths
+pub fn is_pinned(pins: &BTreeSet<PathBuf>, project: &Path) -> bool {
+    !pins.is_empty() && pins.contains(&absolute(project))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let root = tempfile::tempdir().unwrap();
+        let pins = Pins::new(root.path().join("devdust").join(PINS_FILE_NAME));
+        let app = root.path().join("app");
+        assert!(pins.list().unwrap().is_empty());
+
+        assert!(pins.add(&app).unwrap());
+        assert!(!pins.add(&app).unwrap());
+        assert!(pins.add(&root.path().join("web")).unwrap());
+
+        let list = pins.list().unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(is_pinned(&list, &app));
+        assert!(!is_pinned(&list, root.path()));
+
+        assert!(pins.remove(&app).unwrap());
+        assert!(!pins.remove(&app).unwrap());
+        assert!(!is_pinned(&pins.list().unwrap(), &app));
+    }
+}
@@ -0,0 +1,144 @@
+//! Progress reporting hooks
+//!
+//! Scans over large trees can take minutes. A `ProgressCallback` set on
+//! `ScanOptions` is invoked as directories are walked, projects are found and
+//! artifact sizes are computed, so front ends can show live feedback.
+//!
+//! Front ends that need more than that implement [`ScanObserver`], which is
+//! also told about deleted artifact directories, errors, and directories and
+//! projects the scan passed over along with a [`SkipReason`].
+
+use std::{error::Error, fmt, path::Path, sync::Arc};
+
+use crate::Project;
+
+/// Receives notifications as a scan or clean makes progress
+///
+/// Every method does nothing by default, so implementors only override what
+/// they render. Methods may be called from several worker threads at once.
+pub trait ScanObserver: Send + Sync {
+    /// A directory was visited by the walker
+    fn directory_entered(&self, _path: &Path) {}
+
+    /// A project passed all filters and is about to be yielded
+    fn project_found(&self, _project: &Project) {}
+
+    /// The artifact size of a project was calculated
+    fn size_computed(&self, _project: &Project, _bytes: u64) {}
+
+    /// An artifact directory holding this many bytes was removed
+    fn artifact_deleted(&self, _path: &Path, _bytes: u64) {}
+
+    /// Walking or cleaning failed, at `path` if it is known
+    fn error(&self, _path: Option<&Path>, _error: &dyn Error) {}
+
+    /// A directory was not walked, or a project was left out, for `reason`
+    fn skipped(&self, _path: &Path, _reason: SkipReason) {}
+}
+
+impl fmt::Debug for dyn ScanObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ScanObserver")
+    }
+}
+
+/// Why a scan passed over a directory or project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The directory name starts with a dot; it is walked, but is no project itself
+    Hidden,
+    /// The directory matches an exclude pattern
+    Excluded,
+    /// A `.devdustignore` file or git's ignore rules exclude the directory
+    Ignored,
+    /// The directory is on another filesystem than the scan root (Unix only)
+    OtherFilesystem,
+    /// The directory could not be read
+    PermissionDenied,
+    /// The project's type is disabled or was not asked for
+    TypeFiltered,
+    /// The project was modified more recently than the age filter allows
+    TooNew,
+    /// Only projects with stale artifacts were asked for, and it has none
+    NothingStale,
+    /// The project has uncommitted work and the git policy skips such projects
+    UncommittedWork,
+    /// The project has no artifacts on disk
+    NoArtifacts,
+    /// The project's artifacts take up less than the size limit
+    TooSmall,
+    /// The project matches a protected pattern; it is listed but never cleaned
+    Protected,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hidden => "hidden",
+            Self::Excluded => "excluded",
+            Self::Ignored => "ignored by an ignore file",
+            Self::OtherFilesystem => "on another filesystem",
+            Self::PermissionDenied => "permission denied",
+            Self::TypeFiltered => "project type filtered out",
+            Self::TooNew => "too new",
+            Self::NothingStale => "nothing stale",
+            Self::UncommittedWork => "uncommitted work",
+            Self::NoArtifacts => "no artifacts",
+            Self::TooSmall => "artifacts below the size limit",
+            Self::Protected => "protected",
+        })
+    }
+}
+
+/// Something that happened while scanning
+#[derive(Debug, Clone, Copy)]
+pub enum ProgressEvent<'a> {
+    /// A directory was visited by the walker
+    DirectoryScanned(&'a Path),
+    /// A project passed all filters and is about to be yielded
+    ProjectFound(&'a Project),
+    /// The artifact size of a project was calculated
+    ArtifactsSized(&'a Project, u64),
+}
+
+/// Shared callback invoked for every `ProgressEvent`
+///
+/// The callback may be called from several worker threads at once.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(ProgressEvent<'_>) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wraps a closure as a progress callback
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(ProgressEvent<'_>) + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    /// Invokes the callback
+    pub(crate) fn emit(&self, event: ProgressEvent<'_>) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+impl ScanObserver for ProgressCallback {
+    fn directory_entered(&self, path: &Path) {
+        self.emit(ProgressEvent::DirectoryScanned(path));
+    }
+
+    fn project_found(&self, project: &Project) {
+        self.emit(ProgressEvent::ProjectFound(project));
+    }
+
+    fn size_computed(&self, project: &Project, bytes: u64) {
+        self.emit(ProgressEvent::ArtifactsSized(project, bytes));
+    }
+}
@@ -0,0 +1,454 @@
+//! Quarantine for cleaned artifacts
+//!
+//! Instead of deleting artifact directories, a clean can move them into a
+//! quarantine folder (`$XDG_DATA_HOME/devdust/quarantine`). Every clean run
+//! gets its own batch directory holding the moved directories plus a manifest
+//! of where they came from, so the most recent run can be undone and old
+//! batches purged once they are no longer needed.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing::warn;
+
+use crate::{
+    remove::{self, STAGING_PREFIX},
+    CleanError,
+};
+
+/// Name of the manifest file inside each batch directory
+pub(crate) const MANIFEST_FILE_NAME: &str = "manifest.tsv";
+
+/// Start of the name a directory is copied to before it takes its final name
+const COPYING_PREFIX: &str = ".devdust-copying-";
+
+/// The quarantine folder holding all batches
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    root: PathBuf,
+}
+
+/// One clean run's worth of quarantined artifact directories
+///
+/// The batch directory is only created once the first artifact is stashed.
+#[derive(Debug)]
+pub struct QuarantineBatch {
+    dir: PathBuf,
+    /// Number of items stashed so far, used to name them
+    count: Mutex<usize>,
+}
+
+/// A batch found in the quarantine folder or an archive
+#[derive(Debug, Clone)]
+pub struct BatchInfo {
+    /// Directory holding the batch
+    pub path: PathBuf,
+    /// When the clean run started
+    pub created: SystemTime,
+    /// Quarantined directories with their original locations and sizes
+    pub entries: Vec<QuarantinedEntry>,
+}
+
+/// A single quarantined or archived artifact directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedEntry {
+    /// Name of the item (directory or archive file) inside the batch directory
+    pub name: String,
+    /// Size in bytes when it was quarantined or archived
+    pub size: u64,
+    /// Where the directory was moved from
+    pub original: PathBuf,
+}
+
+/// Result of restoring a batch
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// Directories moved back to their original locations
+    pub restored: Vec<PathBuf>,
+    /// Total size of the restored directories
+    pub bytes: u64,
+    /// Directories that could not be restored; they stay in the batch
+    pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl Quarantine {
+    /// Returns the default quarantine location (`$XDG_DATA_HOME/devdust/quarantine`)
+    pub fn default_root() -> Option<PathBuf> {
+        let base = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))?;
+
+        Some(base.join("devdust").join("quarantine"))
+    }
+
+    /// Opens the quarantine at `root`; nothing is created until something is stashed
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Opens the quarantine at the default location
+    pub fn open_default() -> io::Result<Self> {
+        Self::default_root().map(Self::new).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "cannot determine data directory")
+        })
+    }
+
+    /// Returns the quarantine folder
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Starts a new batch for one clean run
+    pub fn begin(&self) -> QuarantineBatch {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        QuarantineBatch {
+            dir: self.root.join(format!("{}-{}", secs, process::id())),
+            count: Mutex::new(0),
+        }
+    }
+
+    /// Lists all batches, oldest first
+    pub fn batches(&self) -> io::Result<Vec<BatchInfo>> {
+        let read_dir = match fs::read_dir(&self.root) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut batches: Vec<BatchInfo> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| BatchInfo::read(&entry.path()))
+            .collect();
+        batches.sort_by_key(|b| (b.created, b.path.clone()));
+        Ok(batches)
+    }
+
+    /// Moves the directories of the most recent batch back to where they came from
+    ///
+    /// Returns `None` if the quarantine is empty. Entries whose original location
+    /// exists again are left in the quarantine and reported as errors.
+    pub fn restore_last(&self) -> io::Result<Option<RestoreReport>> {
+        let Some(batch) = self.batches()?.pop() else {
+            return Ok(None);
+        };
+
+        let mut report = RestoreReport::default();
+        let mut remaining = Vec::new();
+
+        for entry in batch.entries {
+            let item = batch.path.join(&entry.name);
+            let result = if entry.original.exists() {
+                Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "original location exists again",
+                ))
+            } else {
+                entry
+                    .original
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| move_dir(&item, &entry.original))
+            };
+
+            match result {
+                Ok(()) => {
+                    report.bytes += entry.size;
+                    report.restored.push(entry.original);
+                }
+                Err(e) => {
+                    report.errors.push((entry.original.clone(), e));
+                    remaining.push(entry);
+                }
+            }
+        }
+
+        if remaining.is_empty() {
+            fs::remove_dir_all(&batch.path)?;
+        } else {
+            write_manifest(&batch.path, &remaining)?;
+        }
+
+        Ok(Some(report))
+    }
+
+    /// Permanently deletes batches older than `min_age_seconds`
+    ///
+    /// An age of zero empties the whole quarantine. Returns the number of bytes freed.
+    pub fn purge(&self, min_age_seconds: u64) -> Result<u64, CleanError> {
+        let mut deleted = 0u64;
+        let mut errors = Vec::new();
+
+        for batch in self.batches()? {
+            let age = batch.created.elapsed().map_or(0, |d| d.as_secs());
+            if age < min_age_seconds {
+                continue;
+            }
+
+            match fs::remove_dir_all(&batch.path) {
+                Ok(()) => deleted += batch.size(),
+                Err(e) => errors.push(CleanError::at(batch.path, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(deleted)
+        } else {
+            Err(CleanError::PartialFailure { deleted, errors })
+        }
+    }
+}
+
+impl QuarantineBatch {
+    /// Returns the batch directory
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Moves a directory into the batch and records it in the manifest
+    pub fn stash(&self, path: &Path, size: u64) -> io::Result<()> {
+        let original = std::path::absolute(path)?;
+
+        // Serialize stashing so item names and manifest lines stay in step
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        fs::create_dir_all(&self.dir)?;
+
+        let entry = QuarantinedEntry {
+            name: count.to_string(),
+            size,
+            original,
+        };
+        move_dir(path, &self.dir.join(&entry.name))?;
+
+        let mut manifest = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(MANIFEST_FILE_NAME))?;
+        writeln!(manifest, "{}", format_entry(&entry))?;
+
+        *count += 1;
+        Ok(())
+    }
+}
+
+impl BatchInfo {
+    /// Reads a batch directory, returning `None` for anything that is not a batch
+    pub(crate) fn read(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let secs: u64 = name.split('-').next()?.parse().ok()?;
+        let manifest = fs::read_to_string(path.join(MANIFEST_FILE_NAME)).ok()?;
+
+        Some(Self {
+            path: path.to_path_buf(),
+            created: UNIX_EPOCH + std::time::Duration::from_secs(secs),
+            entries: manifest.lines().filter_map(parse_entry).collect(),
+        })
+    }
+
+    /// Total size of the directories in the batch
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Formats a manifest line: `name<TAB>size<TAB>original path`
+pub(crate) fn format_entry(entry: &QuarantinedEntry) -> String {
+    format!(
+        "{}\t{}\t{}",
+        entry.name,
+        entry.size,
+        entry.original.display()
+    )
+}
+
+/// Parses a manifest line written by `format_entry`
+fn parse_entry(line: &str) -> Option<QuarantinedEntry> {
+    let mut fields = line.splitn(3, '\t');
+    Some(QuarantinedEntry {
+        name: fields.next()?.to_string(),
+        size: fields.next()?.parse().ok()?,
+        original: PathBuf::from(fields.next()?),
+    })
+}
+
+/// Rewrites a batch manifest with the given entries
+pub(crate) fn write_manifest(dir: &Path, entries: &[QuarantinedEntry]) -> io::Result<()> {
+    let contents: String = entries.iter().map(|e| format_entry(e) + "\n").collect();
+    fs::write(dir.join(MANIFEST_FILE_NAME), contents)
+}
+
+/// Moves a directory, copying it when source and target are on different filesystems
+pub(crate) fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    move_dir_then(from, to, || Ok(()))
+}
+
+/// Moves a directory like [`move_dir`], then runs `then` while `from` is free
+///
+/// If `then` fails, the move is undone. Relocating uses this to put a link
+/// where the directory was.
+pub(crate) fn move_dir_then(
+    from: &Path,
+    to: &Path,
+    then: impl FnOnce() -> io::Result<()>,
+) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => then().or_else(|e| {
+            fs::rename(to, from)?;
+            Err(e)
+        }),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => copy_dir_then(from, to, then),
+        Err(e) => Err(e),
+    }
+}
+
+/// The cross-device half of [`move_dir_then`]
+///
+/// The copy is made under a temporary name next to `to`, and the original is
+/// renamed aside on its own device rather than deleted until `then` succeeds.
+/// A failure at any step puts the original back and removes the partial copy.
+pub(crate) fn copy_dir_then(
+    from: &Path,
+    to: &Path,
+    then: impl FnOnce() -> io::Result<()>,
+) -> io::Result<()> {
+    let copy = hidden_sibling(to, COPYING_PREFIX)?;
+    let aside = hidden_sibling(from, STAGING_PREFIX)?;
+
+    if let Err(e) = copy_dir(from, &copy).and_then(|()| fs::rename(&copy, to)) {
+        let _ = remove::remove_path(&copy);
+        return Err(e);
+    }
+    if let Err(e) = fs::rename(from, &aside) {
+        let _ = remove::remove_path(to);
+        return Err(e);
+    }
+    if let Err(e) = then() {
+        fs::rename(&aside, from)?;
+        let _ = remove::remove_path(to);
+        return Err(e);
+    }
+
+    // The move is complete; a leftover can go with `devdust remove-staged`
+    if let Err(e) = remove::remove_path(&aside) {
+        warn!(path = %aside.display(), error = %e, "could not remove moved directory");
+    }
+    Ok(())
+}
+
+/// Returns a hidden name next to `path` for it to take during a move
+fn hidden_sibling(path: &Path, prefix: &str) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no name"))?;
+    let mut hidden = OsString::from(format!("{}{}-", prefix, process::id()));
+    hidden.push(name);
+    Ok(path.with_file_name(hidden))
+}
+
+/// Recursively copies a directory, recreating symlinks rather than following them
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    // Windows needs to know the link kind; copying the target keeps things simple
+    fs::copy(from, to).map(|_| ())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stash_and_restore() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("app").join("target");
+        fs::create_dir_all(target.join("debug")).unwrap();
+        fs::write(target.join("debug").join("app"), [0u8; 32]).unwrap();
+
+        let quarantine = Quarantine::new(root.path().join("quarantine"));
+        let batch = quarantine.begin();
+        batch.stash(&target, 32).unwrap();
+        assert!(!target.exists());
+
+        let batches = quarantine.batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].size(), 32);
+
+        let report = quarantine.restore_last().unwrap().unwrap();
+        assert_eq!(report.restored, vec![target.clone()]);
+        assert!(report.errors.is_empty());
+        assert!(target.join("debug").join("app").is_file());
+        assert!(quarantine.batches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_keeps_conflicting_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("target");
+        fs::create_dir(&target).unwrap();
+
+        let quarantine = Quarantine::new(root.path().join("quarantine"));
+        quarantine.begin().stash(&target, 0).unwrap();
+        fs::create_dir(&target).unwrap();
+
+        let report = quarantine.restore_last().unwrap().unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(quarantine.batches().unwrap().len(), 1);
+
+        assert_eq!(quarantine.purge(0).unwrap(), 0);
+        assert!(quarantine.batches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_manifest_lines() {
+        let entry = QuarantinedEntry {
+            name: "3".to_string(),
+            size: 42,
+            original: PathBuf::from("/code/app/node_modules"),
+        };
+        assert_eq!(parse_entry(&format_entry(&entry)), Some(entry));
+        assert_eq!(parse_entry("broken"), None);
+    }
+}
@@ -0,0 +1,314 @@
+//! Ordering projects for display and cleanup
+//!
+//! `sort_projects` orders a listing by a user-chosen key. When only part of the found artifacts should go (e.g. "free 50 GB"), the
+//! best candidates are big projects nobody has touched in a while. The score
+//! multiplies the artifact size by the square root of the age in days, so a
+//! project idle for 100 days counts ten times as much per byte as one used
+//! today, without letting tiny ancient projects outrank huge recent ones.
+//!
+//! `group_by_type` gathers a sorted listing by ecosystem, with subtotals, to
+//! show which toolchain takes up the most space.
+
+use std::{cmp::Ordering, fmt, str::FromStr, time::SystemTime};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{Project, ScanOptions};
+
+const DAY: f64 = 86_400.0;
+
+/// Key used to order a project listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SortKey {
+    /// Largest artifacts first
+    #[default]
+    Size,
+    /// Least recently modified first
+    Age,
+    /// Alphabetical by directory name
+    Name,
+    /// Alphabetical by project type
+    Type,
+}
+
+/// The values a project is sorted by
+#[derive(Debug, Clone, Copy)]
+pub struct SortFields<'a> {
+    pub project: &'a Project,
+    pub artifact_size: u64,
+    /// Last modification time, only needed for `SortKey::Age`
+    pub modified: Option<SystemTime>,
+}
+
+impl SortKey {
+    /// All sort keys
+    pub const ALL: &'static [SortKey] = &[Self::Size, Self::Age, Self::Name, Self::Type];
+
+    /// Returns the identifier used on the command line
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Size => "size",
+            Self::Age => "age",
+            Self::Name => "name",
+            Self::Type => "type",
+        }
+    }
+
+    /// Returns the key after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            Self::Size => Self::Age,
+            Self::Age => Self::Name,
+            Self::Name => Self::Type,
+            Self::Type => Self::Size,
+        }
+    }
+
+    /// Compares two projects in this key's natural order
+    ///
+    /// Ties are broken by size (largest first) and then by path, so the order is stable
+    /// across runs. Projects with an unknown modification time sort last by age.
+    pub fn compare(self, a: &SortFields, b: &SortFields) -> Ordering {
+        let primary = match self {
+            Self::Size => Ordering::Equal,
+            Self::Age => {
+                (a.modified.is_none(), a.modified).cmp(&(b.modified.is_none(), b.modified))
+            }
+            Self::Name => compare_lowercase(&a.project.display_name(), &b.project.display_name()),
+            Self::Type => compare_lowercase(&a.project.type_name(), &b.project.type_name()),
+        };
+
+        primary
+            .then_with(|| b.artifact_size.cmp(&a.artifact_size))
+            .then_with(|| a.project.path.cmp(&b.project.path))
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|k| k.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown sort key: {}. Use size, age, name, or type", s))
+    }
+}
+
+/// Sorts projects by `key`, reversing the natural order if `reverse` is set
+pub fn sort_projects(
+    projects: &mut Vec<(Project, u64)>,
+    key: SortKey,
+    reverse: bool,
+    options: &ScanOptions,
+) {
+    // Modification times need a walk of each project, so only collect them when needed
+    let modified: Vec<Option<SystemTime>> = if key == SortKey::Age {
+        projects
+            .par_iter()
+            .map(|(project, _)| project.last_modified(options).ok())
+            .collect()
+    } else {
+        vec![None; projects.len()]
+    };
+
+    let mut keyed: Vec<(Option<SystemTime>, (Project, u64))> =
+        modified.into_iter().zip(std::mem::take(projects)).collect();
+    keyed.sort_by(|(a_modified, a), (b_modified, b)| {
+        let ordering = key.compare(
+            &SortFields {
+                project: &a.0,
+                artifact_size: a.1,
+                modified: *a_modified,
+            },
+            &SortFields {
+                project: &b.0,
+                artifact_size: b.1,
+                modified: *b_modified,
+            },
+        );
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    projects.extend(keyed.into_iter().map(|(_, entry)| entry));
+}
+
+/// Projects of one type and the artifacts they hold together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeGroup {
+    /// As returned by [`Project::type_name`]
+    pub type_name: String,
+    pub projects: usize,
+    pub artifact_size: u64,
+}
+
+/// Orders projects by type and returns the totals of each type
+///
+/// Types holding the most come first; within a type, projects keep their order.
+/// Projects are grouped by [`Project::type_name`], so a project of several types
+/// or matched by a custom rule forms a group of its own.
+pub fn group_by_type(projects: &mut [(Project, u64)]) -> Vec<TypeGroup> {
+    let mut groups: Vec<TypeGroup> = Vec::new();
+    for (project, size) in projects.iter() {
+        let type_name = project.type_name();
+        match groups.iter_mut().find(|group| group.type_name == type_name) {
+            Some(group) => {
+                group.projects += 1;
+                group.artifact_size += size;
+            }
+            None => groups.push(TypeGroup {
+                type_name: type_name.into_owned(),
+                projects: 1,
+                artifact_size: *size,
+            }),
+        }
+    }
+    groups.sort_by(|a, b| {
+        b.artifact_size
+            .cmp(&a.artifact_size)
+            .then_with(|| b.projects.cmp(&a.projects))
+            .then_with(|| compare_lowercase(&a.type_name, &b.type_name))
+    });
+
+    projects.sort_by_cached_key(|(project, _)| {
+        let type_name = project.type_name();
+        groups.iter().position(|group| group.type_name == type_name)
+    });
+    groups
+}
+
+/// Case-insensitive string comparison
+fn compare_lowercase(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Returns how good a cleanup candidate a project is; higher is better
+pub fn cleanup_score(artifact_size: u64, age_seconds: u64) -> f64 {
+    artifact_size as f64 * (1.0 + age_seconds as f64 / DAY).sqrt()
+}
+
+/// Orders projects so the best cleanup candidates come first
+///
+/// Projects whose age cannot be determined are treated as modified just now.
+pub fn rank_for_cleanup(
+    projects: Vec<(Project, u64)>,
+    options: &ScanOptions,
+) -> Vec<(Project, u64)> {
+    let mut scored: Vec<(f64, (Project, u64))> = projects
+        .into_par_iter()
+        .map(|(project, size)| {
+            let age = project
+                .last_modified(options)
+                .ok()
+                .and_then(|m| m.elapsed().ok())
+                .map_or(0, |d| d.as_secs());
+            (cleanup_score(size, age), (project, size))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Returns the shortest prefix of `projects` whose artifacts add up to `target` bytes
+///
+/// If all projects together hold less than `target`, all of them are returned.
+pub fn select_until_freed(projects: Vec<(Project, u64)>, target: u64) -> Vec<(Project, u64)> {
+    let mut total = 0u64;
+    projects
+        .into_iter()
+        .take_while(|(_, size)| {
+            let needed = total < target;
+            total += size;
+            needed
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProjectType;
+    use std::path::PathBuf;
+
+    fn project(name: &str, size: u64) -> (Project, u64) {
+        (Project::new(ProjectType::Rust, PathBuf::from(name)), size)
+    }
+
+    #[test]
+    fn test_sort_projects() {
+        let options = ScanOptions::default();
+        let mut projects = vec![
+            project("beta", 10),
+            project("alpha", 30),
+            project("gamma", 20),
+        ];
+        let names = |projects: &[(Project, u64)]| -> Vec<String> {
+            projects.iter().map(|(p, _)| p.display_name()).collect()
+        };
+
+        sort_projects(&mut projects, SortKey::Size, false, &options);
+        assert_eq!(names(&projects), ["alpha", "gamma", "beta"]);
+
+        sort_projects(&mut projects, SortKey::Name, false, &options);
+        assert_eq!(names(&projects), ["alpha", "beta", "gamma"]);
+
+        sort_projects(&mut projects, SortKey::Name, true, &options);
+        assert_eq!(names(&projects), ["gamma", "beta", "alpha"]);
+
+        assert_eq!("AGE".parse(), Ok(SortKey::Age));
+        assert!("color".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn test_cleanup_score_prefers_old_and_large() {
+        assert!(cleanup_score(100, 30 * 86_400) > cleanup_score(100, 0));
+        assert!(cleanup_score(200, 0) > cleanup_score(100, 0));
+        assert_eq!(cleanup_score(100, 0), 100.0);
+    }
+
+    #[test]
+    fn test_group_by_type() {
+        let mut projects = vec![
+            project("api", 10),
+            (Project::new(ProjectType::Node, PathBuf::from("web")), 40),
+            project("cli", 20),
+            (Project::new(ProjectType::Node, PathBuf::from("docs")), 5),
+            (Project::new(ProjectType::Go, PathBuf::from("svc")), 30),
+        ];
+
+        let groups = group_by_type(&mut projects);
+        let totals: Vec<_> = groups
+            .iter()
+            .map(|g| (g.type_name.as_str(), g.projects, g.artifact_size))
+            .collect();
+        assert_eq!(totals, [("Node.js", 2, 45), ("Rust", 2, 30), ("Go", 1, 30)]);
+        let names: Vec<String> = projects.iter().map(|(p, _)| p.display_name()).collect();
+        assert_eq!(names, ["web", "docs", "api", "cli", "svc"]);
+    }
+
+    #[test]
+    fn test_select_until_freed() {
+        let projects = vec![project("a", 50), project("b", 30), project("c", 20)];
+        assert_eq!(select_until_freed(projects.clone(), 60).len(), 2);
+        assert_eq!(select_until_freed(projects.clone(), 50).len(), 1);
+        assert_eq!(select_until_freed(projects.clone(), 500).len(), 3);
+        assert!(select_until_freed(projects, 0).is_empty());
+    }
+}
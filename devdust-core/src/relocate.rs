@@ -0,0 +1,194 @@
+//! Relocating artifacts to another volume
+//!
+//! For a small SSD, deleting build state is not the only way to free space: an
+//! artifact directory can move to a bigger, slower disk and a symbolic link take
+//! its place, so builds carry on where they left off. The destination mirrors
+//! the original absolute path (`/code/app/target` becomes
+//! `<destination>/code/app/target`), which keeps projects apart and lets a
+//! relocated directory be recognized from its link alone when it is cleaned.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::quarantine::move_dir_then;
+
+/// How a directory is moved: [`move_dir_then`], or a forced copy in tests
+type Mover = fn(&Path, &Path, &dyn Fn() -> io::Result<()>) -> io::Result<()>;
+
+/// Moves the directory at `path` below `destination` and links it back
+pub(crate) fn relocate(path: &Path, destination: &Path) -> io::Result<()> {
+    relocate_with(path, destination, |from, to, then| {
+        move_dir_then(from, to, then)
+    })
+}
+
+/// Relocates with the given way of moving the directory
+///
+/// A failure to create the link undoes the move, so the project keeps its
+/// build state either way.
+fn relocate_with(path: &Path, destination: &Path, mover: Mover) -> io::Result<()> {
+    let original = std::path::absolute(path)?;
+    let target = std::path::absolute(destination)?.join(mirror(&original));
+    if fs::symlink_metadata(&target).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists already", target.display()),
+        ));
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    mover(&original, &target, &|| symlink_dir(&target, &original))
+}
+
+/// Returns the directory a relocated artifact's link points to
+///
+/// Links made by anything else, such as pnpm or a user, return `None`.
+pub(crate) fn relocated_target(link: &Path) -> Option<PathBuf> {
+    let target = fs::read_link(link).ok()?;
+    let original = std::path::absolute(link).ok()?;
+    let mirrored = mirror(&original);
+    let is_relocated = target.is_absolute()
+        && target.ends_with(&mirrored)
+        && target.components().count() > mirrored.components().count();
+    is_relocated.then_some(target)
+}
+
+/// Turns an absolute path into a relative one (`C:\code` becomes `C\code`)
+fn mirror(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => {
+                let drive = prefix.as_os_str().to_string_lossy();
+                let drive: String = drive
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '.')
+                    .collect();
+                Some(OsString::from(drive))
+            }
+            Component::Normal(name) => Some(name.to_os_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn symlink_dir(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(target: &Path, link: &Path) -> io::Result<()> {
+    // Needs Developer Mode or the symlink privilege
+    std::os::windows::fs::symlink_dir(target, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_dir(_target: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "symbolic links are not supported on this platform",
+    ))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_relocate_and_recognize() {
+        let root = tempfile::tempdir().unwrap();
+        let target_dir = root.path().join("app").join("target");
+        let volume = root.path().join("volume");
+        fs::create_dir_all(target_dir.join("debug")).unwrap();
+        fs::write(target_dir.join("debug").join("app"), [0u8; 32]).unwrap();
+
+        relocate(&target_dir, &volume).unwrap();
+        let moved = volume.join(mirror(&target_dir));
+        assert!(fs::symlink_metadata(&target_dir)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(moved.join("debug").join("app").is_file());
+        // Still usable through the link
+        assert!(target_dir.join("debug").join("app").is_file());
+        assert_eq!(relocated_target(&target_dir), Some(moved.clone()));
+
+        // A link devdust didn't make
+        let node_modules = root.path().join("app").join("node_modules");
+        std::os::unix::fs::symlink(root.path().join("store"), &node_modules).unwrap();
+        assert_eq!(relocated_target(&node_modules), None);
+
+        // Relocating twice would clobber the first copy
+        fs::remove_file(&target_dir).unwrap();
+        fs::create_dir(&target_dir).unwrap();
+        assert!(relocate(&target_dir, &volume).is_err());
+        assert!(target_dir.is_dir());
+
+        // Cleaning removes the moved tree along with its link
+        fs::remove_dir(&target_dir).unwrap();
+        std::os::unix::fs::symlink(&moved, &target_dir).unwrap();
+        crate::remove::remove_dir_all(&target_dir).unwrap();
+        assert!(!moved.exists());
+        assert!(fs::symlink_metadata(&target_dir).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_relocate_across_devices() {
+        use crate::quarantine::copy_dir_then;
+
+        let copy: Mover = |from, to, then| copy_dir_then(from, to, then);
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        let target_dir = app.join("target");
+        let volume = root.path().join("volume");
+        fs::create_dir_all(target_dir.join("debug")).unwrap();
+        fs::write(target_dir.join("debug").join("app"), [0u8; 32]).unwrap();
+
+        relocate_with(&target_dir, &volume, copy).unwrap();
+        let moved = volume.join(mirror(&target_dir));
+        assert_eq!(relocated_target(&target_dir), Some(moved.clone()));
+        assert!(target_dir.join("debug").join("app").is_file());
+        // Neither the original nor a partial copy is left behind
+        let names = |dir: &Path| -> Vec<OsString> {
+            fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect()
+        };
+        assert_eq!(names(&app), vec![OsString::from("target")]);
+        assert_eq!(
+            names(moved.parent().unwrap()),
+            vec![OsString::from("target")]
+        );
+
+        // Without a link, the original stays where it was and the copy goes
+        let node_modules = app.join("node_modules");
+        fs::create_dir_all(node_modules.join("left-pad")).unwrap();
+        let failing: Mover =
+            |from, to, _| copy_dir_then(from, to, || Err(io::Error::other("no symlinks here")));
+        assert!(relocate_with(&node_modules, &volume, failing).is_err());
+        assert!(node_modules.join("left-pad").is_dir());
+        assert!(!volume.join(mirror(&node_modules)).exists());
+        assert_eq!(
+            names(moved.parent().unwrap()),
+            vec![OsString::from("target")]
+        );
+        let mut left = names(&app);
+        left.sort();
+        assert_eq!(
+            left,
+            vec![OsString::from("node_modules"), OsString::from("target")]
+        );
+    }
+}
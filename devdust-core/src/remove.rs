@@ -0,0 +1,467 @@
+//! Deleting artifact directories
+//!
+//! Deep `node_modules` trees regularly defeat `fs::remove_dir_all` on Windows:
+//! paths grow past `MAX_PATH` (260 characters) and packages ship files with the
+//! read-only attribute set. Paths are therefore passed in their `\\?\` verbatim
+//! form there, which lifts the length limit, and when removal fails for lack of
+//! permissions, read-only flags below the directory are cleared before trying
+//! once more. On Unix, the same retry makes read-only directories writable.
+//!
+//! The tree is removed entry by entry so links are never followed: symbolic
+//! links, NTFS junctions and other reparse points (pnpm and Yarn create plenty
+//! of them) are unlinked themselves, leaving their targets untouched, even when
+//! the artifact directory itself is one. The exception is an artifact directory
+//! devdust relocated to another volume: its link and the moved tree go together.
+//!
+//! A `node_modules` holds hundreds of thousands of small files, and unlinking
+//! them one at a time leaves the disk idle between system calls. Directories
+//! are therefore emptied in chunks across a thread pool sized for the platform,
+//! and sibling subdirectories are removed side by side.
+//!
+//! Antivirus scanners and search indexers on Windows briefly hold freshly
+//! written files open, so a deletion failing there is often worth another try
+//! a moment later; see [`RetryPolicy`].
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use rayon::prelude::*;
+
+use crate::relocate;
+
+/// Entries removed per task; enough to outweigh the cost of scheduling it
+const CHUNK_SIZE: usize = 64;
+
+/// Start of the names directories are renamed to while they await removal
+pub(crate) const STAGING_PREFIX: &str = ".devdust-removing-";
+
+/// How often a failed deletion is tried again before it is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Pause before the first retry; it doubles for each further one
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Three retries on Windows, where files are often locked for a moment; none elsewhere
+    fn default() -> Self {
+        Self {
+            retries: if cfg!(windows) { 3 } else { 0 },
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Runs `remove` until it succeeds, fails for good or the retries run out
+    pub(crate) fn run<F>(&self, remove: F) -> io::Result<()>
+    where
+        F: Fn() -> io::Result<()>,
+    {
+        let mut delay = self.delay;
+        let mut result = remove();
+        for _ in 0..self.retries {
+            match &result {
+                Err(e) if is_transient(e) => {}
+                _ => break,
+            }
+            thread::sleep(delay);
+            delay = delay.saturating_mul(2);
+            result = remove();
+        }
+        result
+    }
+}
+
+/// Returns false for errors that waiting can't fix
+fn is_transient(e: &io::Error) -> bool {
+    !matches!(
+        e.kind(),
+        io::ErrorKind::NotFound
+            | io::ErrorKind::InvalidInput
+            | io::ErrorKind::Unsupported
+            | io::ErrorKind::ReadOnlyFilesystem
+            | io::ErrorKind::CrossesDevices
+    )
+}
+
+/// Removes a directory and everything inside it, or only the link if it is one
+pub(crate) fn remove_dir_all(path: &Path) -> io::Result<()> {
+    if let Some(target) = relocate::relocated_target(path) {
+        remove_dir_all(&target)?;
+    }
+
+    let path = long_path(path);
+    let metadata = fs::symlink_metadata(&path)?;
+    if is_link(&metadata) {
+        return unlink(&path, &metadata);
+    }
+
+    match remove_tree(&path) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            clear_readonly(&path);
+            remove_tree(&path)
+        }
+        result => result,
+    }
+}
+
+/// Removes a directory tree without following links inside it
+fn remove_tree(dir: &Path) -> io::Result<()> {
+    match pool() {
+        Some(pool) => pool.install(|| remove_contents(dir)),
+        None => remove_contents(dir),
+    }
+}
+
+/// Removes the entries of `dir` in parallel chunks, then `dir` itself
+fn remove_contents(dir: &Path) -> io::Result<()> {
+    let entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    entries
+        .par_chunks(CHUNK_SIZE)
+        .try_for_each(|chunk| chunk.iter().try_for_each(|path| remove_entry(path)))?;
+    fs::remove_dir(dir)
+}
+
+/// Removes one entry of a directory being emptied
+fn remove_entry(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if is_link(&metadata) {
+        unlink(path, &metadata)
+    } else if metadata.is_dir() {
+        remove_contents(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Returns the thread pool deletions run on, or `None` if it can't be started
+///
+/// It is kept apart from rayon's global pool so a clean running next to a scan
+/// doesn't starve it, and so the thread count can suit the file system.
+fn pool() -> Option<&'static rayon::ThreadPool> {
+    static POOL: OnceLock<Option<rayon::ThreadPool>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(thread_count())
+            .thread_name(|i| format!("devdust-remove-{}", i))
+            .build()
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Number of deletion threads for this platform
+fn thread_count() -> usize {
+    let cores = thread::available_parallelism().map_or(4, |n| n.get());
+    if cfg!(target_os = "macos") {
+        // APFS serializes metadata updates per volume; more threads only contend
+        cores.min(4)
+    } else if cfg!(windows) {
+        // NTFS deletes finish when the last handle closes, so threads mostly wait
+        (cores * 2).min(32)
+    } else {
+        cores.min(16)
+    }
+}
+
+/// Renames `path` to a hidden sibling, freeing its name at once, and returns the new path
+///
+/// For a relocated artifact the moved tree is renamed instead and the link
+/// removed, since the link would no longer lead to it.
+pub(crate) fn stage(path: &Path) -> io::Result<PathBuf> {
+    static STAGED: AtomicUsize = AtomicUsize::new(0);
+
+    let target = relocate::relocated_target(path);
+    let source = target.as_deref().unwrap_or(path);
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no name"))?;
+    let mut staged_name = OsString::from(format!(
+        "{}{}-{}-",
+        STAGING_PREFIX,
+        process::id(),
+        STAGED.fetch_add(1, Ordering::Relaxed)
+    ));
+    staged_name.push(name);
+    let staged = source.with_file_name(staged_name);
+
+    fs::rename(long_path(source), long_path(&staged))?;
+    if target.is_some() {
+        let metadata = fs::symlink_metadata(path)?;
+        unlink(path, &metadata)?;
+    }
+    Ok(staged)
+}
+
+/// Removes a file or a directory tree
+pub(crate) fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        return remove_dir_all(path);
+    }
+
+    let path = long_path(path);
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            clear_readonly(&path);
+            fs::remove_file(&path)
+        }
+        result => result,
+    }
+}
+
+/// Returns true for symbolic links, junctions and other reparse points
+#[cfg(windows)]
+fn is_link(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    metadata.file_type().is_symlink()
+        || metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+/// Returns true for symbolic links
+#[cfg(not(windows))]
+fn is_link(metadata: &fs::Metadata) -> bool {
+    metadata.file_type().is_symlink()
+}
+
+/// Removes a link itself; directory links and junctions are removed like empty directories
+#[cfg(windows)]
+fn unlink(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+    if metadata.file_attributes() & FILE_ATTRIBUTE_DIRECTORY != 0 {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Removes a link itself
+#[cfg(not(windows))]
+fn unlink(path: &Path, _metadata: &fs::Metadata) -> io::Result<()> {
+    fs::remove_file(path)
+}
+
+/// Makes `path` and everything below it writable, ignoring entries that can't be changed
+///
+/// Symbolic links are not followed, so nothing outside `path` is touched.
+fn clear_readonly(path: &Path) {
+    for entry in walkdir::WalkDir::new(path).into_iter().flatten() {
+        if !entry.path_is_symlink() {
+            make_writable(entry.path());
+        }
+    }
+}
+
+#[cfg(windows)]
+fn make_writable(path: &Path) {
+    if let Ok(metadata) = fs::metadata(path) {
+        let mut permissions = metadata.permissions();
+        if permissions.readonly() {
+            // Clears FILE_ATTRIBUTE_READONLY; Windows has no other permission bits here
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            let _ = fs::set_permissions(path, permissions);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn make_writable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Only directories matter: removing an entry needs write access to its parent
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        let mode = metadata.permissions().mode();
+        if metadata.is_dir() && mode & 0o200 == 0 {
+            let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode | 0o700));
+        }
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn make_writable(_path: &Path) {}
+
+/// Returns the `\\?\` form of an absolute path so it may exceed `MAX_PATH`
+#[cfg(windows)]
+fn long_path(path: &Path) -> PathBuf {
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let absolute = absolute.as_os_str().to_string_lossy();
+
+    if absolute.starts_with(r"\\?\") {
+        PathBuf::from(absolute.as_ref())
+    } else if let Some(share) = absolute.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", share))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", absolute))
+    }
+}
+
+#[cfg(not(windows))]
+fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_readonly_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let node_modules = root.path().join("node_modules");
+        let package = node_modules.join("pkg");
+        fs::create_dir_all(&package).unwrap();
+        let file = package.join("index.js");
+        fs::write(&file, "").unwrap();
+
+        let mut permissions = fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions.clone()).unwrap();
+        fs::set_permissions(&package, permissions).unwrap();
+
+        remove_dir_all(&node_modules).unwrap();
+        assert!(!node_modules.exists());
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        use std::cell::Cell;
+
+        let policy = RetryPolicy {
+            retries: 3,
+            delay: Duration::from_millis(1),
+        };
+        let attempts = Cell::new(0);
+        let locked_twice = || {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 | 2 => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+                _ => Ok(()),
+            }
+        };
+        assert!(policy.run(locked_twice).is_ok());
+        assert_eq!(attempts.get(), 3);
+
+        // Missing paths stay missing
+        attempts.set(0);
+        let missing = || {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        };
+        assert!(policy.run(missing).is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_remove_wide_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let node_modules = root.path().join("node_modules");
+        for package in 0..20 {
+            let dir = node_modules.join(format!("pkg-{}", package)).join("lib");
+            fs::create_dir_all(&dir).unwrap();
+            for file in 0..CHUNK_SIZE * 2 + 1 {
+                fs::write(dir.join(format!("{}.js", file)), "").unwrap();
+            }
+        }
+
+        remove_dir_all(&node_modules).unwrap();
+        assert!(!node_modules.exists());
+        assert!(root.path().exists());
+    }
+
+    #[test]
+    fn test_remove_deep_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let node_modules = root.path().join("node_modules");
+        let mut deep = node_modules.clone();
+        while deep.as_os_str().len() < 400 {
+            deep = deep.join("node_modules").join("some-package");
+        }
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(deep.join("package.json"), "{}").unwrap();
+
+        remove_dir_all(&node_modules).unwrap();
+        assert!(!node_modules.exists());
+    }
+
+    /// Creates a directory link: a symbolic link on Unix, a junction on Windows
+    fn link_dir(target: &Path, link: &Path) {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, link).unwrap();
+        #[cfg(windows)]
+        {
+            let status = std::process::Command::new("cmd")
+                .args(["/C", "mklink", "/J"])
+                .arg(link)
+                .arg(target)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        }
+    }
+
+    #[test]
+    fn test_links_are_unlinked_not_followed() {
+        let root = tempfile::tempdir().unwrap();
+        let store = root.path().join("store");
+        fs::create_dir_all(store.join("pkg")).unwrap();
+        fs::write(store.join("pkg").join("index.js"), "").unwrap();
+
+        // A link inside the artifact directory
+        let node_modules = root.path().join("app").join("node_modules");
+        fs::create_dir_all(&node_modules).unwrap();
+        link_dir(&store.join("pkg"), &node_modules.join("pkg"));
+        remove_dir_all(&node_modules).unwrap();
+        assert!(!node_modules.exists());
+        assert!(store.join("pkg").join("index.js").exists());
+
+        // The artifact directory itself is a link
+        link_dir(&store, &node_modules);
+        remove_dir_all(&node_modules).unwrap();
+        assert!(fs::symlink_metadata(&node_modules).is_err());
+        assert!(store.join("pkg").join("index.js").exists());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_path() {
+        assert_eq!(
+            long_path(Path::new(r"C:\code\app")),
+            PathBuf::from(r"\\?\C:\code\app")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\server\share\app")),
+            PathBuf::from(r"\\?\UNC\server\share\app")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\?\C:\code")),
+            PathBuf::from(r"\\?\C:\code")
+        );
+    }
+}
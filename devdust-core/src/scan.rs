@@ -0,0 +1,3102 @@
+//! Walking directories, sizing and cleaning projects
+//!
+//! Everything that touches the filesystem beyond reading a single directory
+//! lives here, behind the default `fs` feature. Without it, the crate only
+//! provides project detection and the formatting helpers, which also build for
+//! `wasm32-unknown-unknown`.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{mpsc, Arc, OnceLock},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, trace, warn};
+
+use crate::{
+    artifacts::{self, KnownArtifacts},
+    background::BackgroundRemover,
+    bazel, cachedir,
+    cargo_target::{self, SharedTargets},
+    custom,
+    disk_usage::{self, calculate_disk_usage, DiskUsage},
+    exclude::ExcludeSet,
+    fs_kind::{device_id, fs_kind, FsKind},
+    godot,
+    ignore_file::IgnoreMatcher,
+    in_use,
+    inspect::{self, ArtifactEntry},
+    is_latex_leftover, matches_artifact,
+    metadata::{self, ProjectMetadata},
+    native, orphan, partial, relocate,
+    remove::{self, RetryPolicy},
+    stale, unreal, vcs, xcode, ArtifactCategory, CustomRule, GitPolicy, GitStatus, PartialClean,
+    ProgressCallback, ProjectType, QuarantineBatch, ScanObserver, ScanTimings, SizeCache,
+    SkipReason, VcsInfo,
+};
+
+// ============================================================================
+// Project Structure
+// ============================================================================
+
+/// Represents a detected development project
+#[derive(Debug, Clone, Serialize)]
+pub struct Project {
+    /// The type of project detected
+    pub project_type: ProjectType,
+    /// Further types sharing the project root (e.g. Rust next to Node.js in a
+    /// Tauri app), whose artifacts are sized and cleaned along with the others
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub additional_types: Vec<ProjectType>,
+    /// The root path of the project
+    pub path: PathBuf,
+    /// The rule that matched, for `ProjectType::Custom` projects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_rule: Option<CustomRule>,
+    /// Artifact directories held back by an exclude pattern, the lockfile policy or
+    /// a disabled artifact category; never sized or cleaned
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_artifacts: Vec<String>,
+    /// Matched a protected pattern; reported but never cleaned
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub protected: bool,
+    /// Artifact directories found without a project marker next to them
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub orphaned: bool,
+    /// Every artifact directory, resolved on first use
+    ///
+    /// Resolving `**` patterns walks the project's tree, and the list is needed
+    /// for sizing, display and cleaning alike. Changing the project's types
+    /// afterwards doesn't update it.
+    #[serde(skip)]
+    listed: OnceLock<Vec<(Option<ProjectType>, String)>>,
+}
+
+/// Name of the file that keeps the artifact directory holding it from being cleaned
+///
+/// Like `.gitkeep`, the file's contents don't matter.
+pub const KEEP_FILE_NAME: &str = ".devdustkeep";
+
+/// Deepest level below a project that [`Project::last_modified`] looks at
+const LAST_MODIFIED_MAX_DEPTH: usize = 12;
+
+/// Returns the directories in `dirs` that hold a keep file
+fn kept_among(root: &Path, dirs: &[(Option<ProjectType>, String)]) -> Vec<String> {
+    dirs.iter()
+        .filter(|(_, dir)| root.join(dir).join(KEEP_FILE_NAME).is_file())
+        .map(|(_, dir)| dir.clone())
+        .collect()
+}
+
+/// An artifact directory (or part of one) and its size
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Artifact {
+    /// Relative to the project root, or absolute if it lies outside (a relocated target)
+    pub path: PathBuf,
+    /// Size in bytes, measured as selected by the scan options
+    pub size: u64,
+}
+
+impl Project {
+    /// Creates a new Project instance
+    pub fn new(project_type: ProjectType, path: PathBuf) -> Self {
+        Self {
+            project_type,
+            additional_types: Vec::new(),
+            path,
+            custom_rule: None,
+            excluded_artifacts: Vec::new(),
+            protected: false,
+            orphaned: false,
+            listed: OnceLock::new(),
+        }
+    }
+
+    /// Creates a project matched by a user-defined rule
+    pub fn from_rule(rule: CustomRule, path: PathBuf) -> Self {
+        Self {
+            project_type: ProjectType::Custom,
+            additional_types: Vec::new(),
+            path,
+            custom_rule: Some(rule),
+            excluded_artifacts: Vec::new(),
+            protected: false,
+            orphaned: false,
+            listed: OnceLock::new(),
+        }
+    }
+
+    /// Detects the project in a directory, trying custom rules before built-in types
+    pub fn detect(path: &Path, custom_rules: &[CustomRule]) -> Option<Self> {
+        if let Some(rule) = custom::detect(path, custom_rules) {
+            return Some(Self::from_rule(rule.clone(), path.to_path_buf()));
+        }
+
+        let mut types = ProjectType::detect_all_from_directory(path).into_iter();
+        let mut project = Self::new(types.next()?, path.to_path_buf());
+        project.additional_types = types.collect();
+        Some(project)
+    }
+
+    /// Returns the primary project type followed by the additional ones
+    pub fn project_types(&self) -> Vec<ProjectType> {
+        std::iter::once(self.project_type)
+            .chain(self.additional_types.iter().copied())
+            .collect()
+    }
+
+    /// Returns true if the project is of `project_type`, primary or not
+    fn is_type(&self, project_type: ProjectType) -> bool {
+        self.custom_rule.is_none() && self.project_types().contains(&project_type)
+    }
+
+    /// Returns the name of the project type, or of the custom rule that matched
+    ///
+    /// The names of all types are listed for a project of several, e.g.
+    /// "Node.js, Rust".
+    pub fn type_name(&self) -> Cow<'_, str> {
+        match &self.custom_rule {
+            Some(rule) => Cow::Borrowed(&rule.name),
+            None if self.additional_types.is_empty() => Cow::Borrowed(self.project_type.name()),
+            None => {
+                let names: Vec<&str> = self.project_types().iter().map(|t| t.name()).collect();
+                Cow::Owned(names.join(", "))
+            }
+        }
+    }
+
+    /// Returns the artifact directories for this project, relative to its root
+    ///
+    /// Wildcard patterns such as `*.egg-info` are expanded to the matching
+    /// directories. A Rust target directory relocated with `CARGO_TARGET_DIR` or
+    /// `.cargo/config.toml` is included as an absolute path. Directories listed
+    /// in `excluded_artifacts` are left out, as are those holding a
+    /// [`KEEP_FILE_NAME`] file and directories inside another listed one (e.g.
+    /// `node_modules/.cache` next to `node_modules`).
+    pub fn artifact_directories(&self) -> Vec<String> {
+        let dirs = self.all_artifact_directories();
+        dirs.iter()
+            .filter(|dir| {
+                !dirs
+                    .iter()
+                    .any(|other| other != *dir && Path::new(dir).starts_with(other))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the artifact directories, including those nested in another one
+    fn all_artifact_directories(&self) -> Vec<String> {
+        self.typed_artifact_directories()
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .collect()
+    }
+
+    /// Returns the artifact directories along with the type that lists them
+    ///
+    /// Includes directories nested in another one. For a directory listed by
+    /// several types, the first of them is returned. Directories listed by no
+    /// type but holding a `CACHEDIR.TAG` come last, without a type.
+    pub(crate) fn typed_artifact_directories(&self) -> Vec<(Option<ProjectType>, String)> {
+        let mut typed = self.listed_artifact_directories();
+        let kept = kept_among(&self.path, &typed);
+        typed.retain(|(_, dir)| {
+            !self.excluded_artifacts.contains(dir)
+                && !kept.iter().any(|kept| Path::new(dir).starts_with(kept))
+        });
+        typed
+    }
+
+    /// Returns the artifact directories holding a [`KEEP_FILE_NAME`] file
+    ///
+    /// They and everything inside them are never sized or cleaned.
+    pub fn kept_artifacts(&self) -> Vec<String> {
+        kept_among(&self.path, &self.listed_artifact_directories())
+    }
+
+    /// Returns every artifact directory of the project, kept or excluded ones too
+    fn listed_artifact_directories(&self) -> Vec<(Option<ProjectType>, String)> {
+        self.listed
+            .get_or_init(|| self.list_artifact_directories())
+            .clone()
+    }
+
+    /// Resolves every artifact directory of the project, kept or excluded ones too
+    fn list_artifact_directories(&self) -> Vec<(Option<ProjectType>, String)> {
+        let mut typed: Vec<(Option<ProjectType>, String)> = Vec::new();
+        if let Some(rule) = &self.custom_rule {
+            let patterns: Vec<&str> = rule.artifacts.iter().map(String::as_str).collect();
+            typed.extend(
+                artifacts::resolve(&self.path, &patterns)
+                    .into_iter()
+                    .map(|dir| (Some(ProjectType::Custom), dir)),
+            );
+        } else {
+            for project_type in self.project_types() {
+                for dir in self.type_artifact_directories(project_type) {
+                    if !typed.iter().any(|(_, listed)| *listed == dir) {
+                        typed.push((Some(project_type), dir));
+                    }
+                }
+            }
+        }
+        for dir in cachedir::tagged_children(&self.path) {
+            if !typed.iter().any(|(_, listed)| *listed == dir) {
+                typed.push((None, dir));
+            }
+        }
+        typed
+    }
+
+    /// Returns the artifact directories of one of the project's built-in types
+    fn type_artifact_directories(&self, project_type: ProjectType) -> Vec<String> {
+        let mut dirs = artifacts::resolve(&self.path, project_type.artifact_directories());
+
+        match project_type {
+            ProjectType::Rust => {
+                if let Some(target) = cargo_target::target_dir(&self.path) {
+                    dirs.push(target.to_string_lossy().into_owned());
+                }
+            }
+            ProjectType::Godot => {
+                if let Some(godot_dirs) = godot::artifact_directories(&self.path) {
+                    dirs = godot_dirs;
+                }
+            }
+            ProjectType::Unreal => dirs.extend(unreal::artifact_directories(&self.path)),
+            ProjectType::Xcode => {
+                for derived in xcode::derived_data(&self.path) {
+                    dirs.push(derived.to_string_lossy().into_owned());
+                }
+            }
+            ProjectType::LaTeX => {
+                // `*.log` and friends only count next to a `.tex` file of the same stem
+                let names: Vec<String> = fs::read_dir(&self.path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect();
+                dirs.retain(|dir| is_latex_leftover(&names, dir));
+            }
+            ProjectType::Bazel => {
+                if let Some(base) = bazel::output_base(&self.path) {
+                    // The `bazel-*` links all point into the output base
+                    dirs = vec![base.to_string_lossy().into_owned()];
+                }
+            }
+            _ => {}
+        }
+
+        dirs
+    }
+
+    /// Returns the artifact patterns of the project's types or custom rule
+    fn artifact_patterns(&self) -> Vec<&str> {
+        match &self.custom_rule {
+            Some(rule) => rule.artifacts.iter().map(String::as_str).collect(),
+            None => self
+                .project_types()
+                .into_iter()
+                .flat_map(|t| t.artifact_directories().iter().copied())
+                .collect(),
+        }
+    }
+
+    /// Returns true if `relative` matches one of the project's artifact patterns
+    ///
+    /// Unlike [`Project::artifact_directories`], nothing is read from disk.
+    pub(crate) fn owns_artifact(&self, relative: &Path) -> bool {
+        let relative = relative.to_string_lossy();
+        self.artifact_patterns()
+            .iter()
+            .any(|pattern| matches_artifact(pattern, &relative))
+    }
+
+    /// Returns the display name of the project (usually the directory name)
+    pub fn display_name(&self) -> String {
+        self.path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string()
+    }
+
+    /// Calculates the total size of artifact directories in bytes
+    ///
+    /// With `options.partial_clean` set, only the parts a partial clean would remove
+    /// are counted for project types that support it.
+    pub fn calculate_artifact_size(&self, options: &ScanOptions) -> u64 {
+        let total_size = self
+            .artifacts(options)
+            .iter()
+            .map(|artifact| artifact.size)
+            .sum();
+
+        options.notify(|observer| observer.size_computed(self, total_size));
+
+        total_size
+    }
+
+    /// Calculates both the apparent and on-disk size of the artifacts
+    ///
+    /// Covers the same paths as [`Project::calculate_artifact_size`] but always
+    /// walks them, so frontends can show both figures.
+    pub fn artifact_disk_usage(&self, options: &ScanOptions) -> DiskUsage {
+        self.artifact_paths(options)
+            .iter()
+            .map(|path| calculate_disk_usage(path, options))
+            .sum()
+    }
+
+    /// Returns the existing paths that cleaning would remove
+    ///
+    /// These are the artifact directories, or the parts selected by
+    /// `options.partial_clean` for project types that support it.
+    pub fn artifact_paths(&self, options: &ScanOptions) -> Vec<PathBuf> {
+        let partial_plan = options
+            .partial_clean
+            .as_ref()
+            .and_then(|partial| partial::plan(self, partial));
+
+        partial_plan.unwrap_or_else(|| {
+            self.artifact_directories()
+                .into_iter()
+                .map(|dir| self.path.join(dir))
+                .filter(|path| path.exists())
+                .collect()
+        })
+    }
+
+    /// Lists what cleaning would remove, with the size of each directory
+    ///
+    /// With `options.partial_clean` set, these are the parts a partial clean
+    /// removes, so the sizes add up to [`Project::calculate_artifact_size`].
+    pub fn artifacts(&self, options: &ScanOptions) -> Vec<Artifact> {
+        let partial_plan = options
+            .partial_clean
+            .as_ref()
+            .and_then(|partial| partial::plan(self, partial));
+
+        match partial_plan {
+            Some(paths) => paths
+                .into_iter()
+                .map(|path| Artifact {
+                    size: calculate_directory_size(&path, options),
+                    path: path
+                        .strip_prefix(&self.path)
+                        .map(Path::to_path_buf)
+                        .unwrap_or(path),
+                })
+                .collect(),
+            None => self
+                .artifact_sizes(options)
+                .into_iter()
+                .map(|(dir, size)| Artifact {
+                    path: PathBuf::from(dir),
+                    size,
+                })
+                .collect(),
+        }
+    }
+
+    /// Lists the files and directories `depth` levels inside the artifacts, largest first
+    ///
+    /// Shows what takes up the space, e.g. which package in `node_modules` or
+    /// which profile in `target/`. A depth of 0 is treated as 1.
+    pub fn largest_entries(&self, options: &ScanOptions, depth: usize) -> Vec<ArtifactEntry> {
+        inspect::entries(self, options, depth)
+    }
+
+    /// Calculates the size of each existing artifact directory
+    pub fn artifact_sizes(&self, options: &ScanOptions) -> Vec<(String, u64)> {
+        self.artifact_directories()
+            .into_iter()
+            .filter_map(|artifact_dir| {
+                let artifact_path = self.path.join(&artifact_dir);
+                if !artifact_path.exists() {
+                    return None;
+                }
+
+                let size_cache = options
+                    .size_cache
+                    .as_ref()
+                    .filter(|_| !options.disk_usage && options.dedupe_hardlinks)
+                    .filter(|_| !options.skip_sizes);
+                let cached = size_cache.and_then(|cache| cache.get(&artifact_path));
+                let size = cached.unwrap_or_else(|| {
+                    let size = calculate_directory_size(&artifact_path, options);
+                    if let Some(cache) = size_cache.filter(|_| !options.estimate_sizes) {
+                        cache.insert(&artifact_path, size);
+                    }
+                    size
+                });
+                Some((artifact_dir, size))
+            })
+            .collect()
+    }
+
+    /// Returns a copy of the project that only cleans the given artifact directories
+    ///
+    /// The other directories are added to `excluded_artifacts`.
+    pub fn with_only_artifacts(&self, keep: &[&str]) -> Self {
+        let mut project = self.clone();
+        let dropped: Vec<String> = self
+            .artifact_directories()
+            .into_iter()
+            .filter(|dir| !keep.contains(&dir.as_str()))
+            .collect();
+        project.excluded_artifacts.extend(dropped);
+        project
+    }
+
+    /// Gets the last modified time of the project
+    ///
+    /// Measured as configured by [`ScanOptions::age_source`]. Artifact directories
+    /// are left out, since a build touches them without the project changing, and
+    /// nothing deeper than `LAST_MODIFIED_MAX_DEPTH` levels is looked at.
+    pub fn last_modified(&self, options: &ScanOptions) -> Result<SystemTime, std::io::Error> {
+        if options.age_source == AgeSource::Commit {
+            if let Some(last_commit) = vcs::last_commit(&self.path) {
+                return Ok(last_commit);
+            }
+        }
+
+        let time = |metadata: &fs::Metadata| match options.age_source {
+            AgeSource::Atime => metadata.accessed(),
+            AgeSource::Mtime | AgeSource::Commit => metadata.modified(),
+        };
+        let metadata = fs::metadata(&self.path)?;
+        let mut most_recent = time(&metadata)?;
+
+        // Walk through the project's sources to find the most recent modification
+        let artifacts: HashSet<PathBuf> = self
+            .listed_artifact_directories()
+            .into_iter()
+            .map(|(_, dir)| self.path.join(dir))
+            .collect();
+        let walker = walkdir::WalkDir::new(&self.path)
+            .max_depth(LAST_MODIFIED_MAX_DEPTH)
+            .follow_links(options.follow_symlinks)
+            .same_file_system(options.same_filesystem);
+
+        for entry in walker
+            .into_iter()
+            .filter_entry(|e| !artifacts.contains(e.path()))
+            .filter_map(|e| e.ok())
+        {
+            if let Ok(metadata) = entry.metadata() {
+                if let Ok(modified) = time(&metadata) {
+                    if modified > most_recent {
+                        most_recent = modified;
+                    }
+                }
+            }
+        }
+
+        Ok(most_recent)
+    }
+
+    /// Returns the first artifact directory on a network or FUSE filesystem, with its kind
+    pub fn remote_artifact_directory(&self) -> Option<(PathBuf, FsKind)> {
+        self.artifact_directories()
+            .into_iter()
+            .map(|dir| self.path.join(dir))
+            .filter(|dir| dir.exists())
+            .map(|dir| {
+                let kind = fs_kind(&dir);
+                (dir, kind)
+            })
+            .find(|(_, kind)| kind.is_remote())
+    }
+
+    /// Returns true if a running process has files open inside the artifact directories
+    ///
+    /// Checks `/proc` on Linux and uses `lsof` on macOS; on other platforms, or if
+    /// the process table cannot be read, this always returns false.
+    pub fn is_in_use(&self) -> bool {
+        let dirs: Vec<PathBuf> = self
+            .artifact_directories()
+            .into_iter()
+            .map(|dir| self.path.join(dir))
+            .collect();
+        in_use::any_in_use(&dirs)
+    }
+
+    /// Inspects the git repository containing the project
+    ///
+    /// Returns `None` if the project is not inside a git working tree.
+    pub fn git_status(&self) -> Option<GitStatus> {
+        vcs::git_status(&self.path)
+    }
+
+    /// Returns the branch, last commit time and dirty state of the project's repository
+    ///
+    /// Returns `None` if the project is not inside a git working tree.
+    pub fn vcs_info(&self) -> Option<VcsInfo> {
+        vcs::vcs_info(&self.path)
+    }
+
+    /// Returns the artifact directories last written before their lockfile changed
+    ///
+    /// The next install or build replaces them anyway, so they are safe to clean.
+    pub fn stale_artifacts(&self) -> Vec<String> {
+        stale::stale_artifacts(self)
+    }
+
+    /// Returns the name and version declared in the project's manifest
+    ///
+    /// Empty for custom rules and for types whose manifest doesn't name the project.
+    pub fn metadata(&self) -> ProjectMetadata {
+        match self.custom_rule {
+            Some(_) => ProjectMetadata::default(),
+            None => metadata::read(&self.path, self.project_type),
+        }
+    }
+
+    /// Writes a `CACHEDIR.TAG` into each existing artifact directory lacking one
+    ///
+    /// Backup tools that honor the tag then skip the artifacts. Returns the
+    /// number of directories tagged.
+    pub fn tag_artifacts(&self) -> std::io::Result<usize> {
+        let mut tagged = 0;
+        for dir in self.artifact_directories() {
+            let path = self.path.join(dir);
+            if path.is_dir() && cachedir::tag(&path)? {
+                tagged += 1;
+            }
+        }
+        Ok(tagged)
+    }
+
+    /// Cleans (deletes) all artifact directories for this project
+    ///
+    /// Failed deletions are retried as the default [`RetryPolicy`] says before
+    /// they are listed in the report's `failures`; only a project that can't be
+    /// cleaned at all returns an error.
+    pub fn clean(&self) -> Result<CleanReport, CleanError> {
+        self.clean_with_retry(&RetryPolicy::default())
+    }
+
+    /// Cleans like [`Project::clean`], retrying each failed deletion as `retry` says
+    pub fn clean_with_retry(&self, retry: &RetryPolicy) -> Result<CleanReport, CleanError> {
+        self.clean_with(|path, _| retry.run(|| remove::remove_path(path)), None)
+    }
+
+    /// Cleans like [`Project::clean`], reporting each deleted directory and error
+    pub fn clean_observed(&self, observer: &dyn ScanObserver) -> Result<CleanReport, CleanError> {
+        let retry = RetryPolicy::default();
+        self.clean_with(
+            |path, _| retry.run(|| remove::remove_path(path)),
+            Some(observer),
+        )
+    }
+
+    /// Renames all artifact directories out of the way and deletes them on `remover`'s thread
+    ///
+    /// Returns as soon as the directories are renamed, so builds can recreate them
+    /// right away; the freed bytes are counted before the deletion finishes.
+    pub fn clean_in_background(
+        &self,
+        remover: &BackgroundRemover,
+    ) -> Result<CleanReport, CleanError> {
+        self.clean_with(|path, _| remover.remove(path), None)
+    }
+
+    /// Moves all artifact directories to the system trash instead of deleting them
+    ///
+    /// Uses the recycle bin on Windows, the Trash on macOS and the freedesktop.org
+    /// trash on Linux, so the artifacts can be restored until the trash is emptied.
+    pub fn clean_to_trash(&self) -> Result<CleanReport, CleanError> {
+        self.clean_with(
+            |path, _| trash::delete(path).map_err(std::io::Error::other),
+            None,
+        )
+    }
+
+    /// Moves all artifact directories into a quarantine batch
+    ///
+    /// They can be restored with [`Quarantine::restore_last`](crate::Quarantine::restore_last) until the batch is purged.
+    pub fn clean_to_quarantine(&self, batch: &QuarantineBatch) -> Result<CleanReport, CleanError> {
+        self.clean_with(|path, size| batch.stash(path, size), None)
+    }
+
+    /// Packs each artifact directory into a compressed archive, then deletes it
+    ///
+    /// They can be unpacked again with [`Archive::restore_last`](crate::Archive::restore_last).
+    #[cfg(feature = "archive")]
+    pub fn clean_to_archive(&self, batch: &crate::ArchiveBatch) -> Result<CleanReport, CleanError> {
+        self.clean_with(|path, size| batch.store(path, size), None)
+    }
+
+    /// Moves each artifact directory below `destination` and leaves a symbolic link
+    ///
+    /// Builds keep working through the links, while the space is freed on the
+    /// project's volume. Cleaning the project later deletes the moved directories
+    /// too. Artifact files and directories that are links already stay put.
+    pub fn relocate_artifacts(&self, destination: &Path) -> Result<CleanReport, CleanError> {
+        self.check_protected()?;
+        let paths = self
+            .artifact_directories()
+            .into_iter()
+            .map(|dir| self.path.join(dir))
+            .filter(|path| fs::symlink_metadata(path).is_ok_and(|m| m.is_dir()))
+            .collect();
+        let mut report = remove_paths(
+            &self.path,
+            paths,
+            |path, _| relocate::relocate(path, destination),
+            None,
+        );
+        report.skipped = self.held_back_artifacts();
+        Ok(report)
+    }
+
+    /// Cleans with the ecosystem's own command (e.g., `cargo clean`) where one exists
+    ///
+    /// Falls back to [`Project::clean`] if the type has no such command, the tool is
+    /// missing, fails or runs longer than `timeout`, some artifact directories are
+    /// excluded (the tool would not respect that), or the project has several types.
+    pub fn clean_native(&self, timeout: Duration) -> Result<CleanReport, CleanError> {
+        self.check_protected()?;
+        let native = self.custom_rule.is_none()
+            && self.additional_types.is_empty()
+            && self.excluded_artifacts.is_empty()
+            && self.project_type.native_clean_command().is_some();
+
+        if native {
+            let started = Instant::now();
+            let options = ScanOptions::default();
+            let before = self.calculate_artifact_size(&options);
+            if native::run(self.project_type, &self.path, timeout).is_ok() {
+                // The tool doesn't say what it removed, only the sizes tell
+                let bytes_freed = before.saturating_sub(self.calculate_artifact_size(&options));
+                info!(path = %self.path.display(), bytes = bytes_freed, "cleaned with native command");
+                return Ok(CleanReport {
+                    bytes_freed,
+                    duration: started.elapsed(),
+                    ..CleanReport::default()
+                });
+            }
+            debug!(path = %self.path.display(), "native clean failed, deleting instead");
+        }
+
+        self.clean()
+    }
+
+    /// Deletes only the stale artifacts selected by `partial`
+    ///
+    /// Project types without a partial cleaning strategy are cleaned completely.
+    /// Failed deletions are retried as `retry` says, like [`Project::clean_with_retry`].
+    pub fn clean_partial(
+        &self,
+        partial: &PartialClean,
+        retry: &RetryPolicy,
+    ) -> Result<CleanReport, CleanError> {
+        self.check_protected()?;
+        match partial::plan(self, partial) {
+            Some(paths) => Ok(remove_paths(
+                &self.path,
+                paths,
+                |path, _| retry.run(|| remove::remove_path(path)),
+                None,
+            )),
+            None => self.clean_with_retry(retry),
+        }
+    }
+
+    /// Removes each existing artifact directory with the given function
+    ///
+    /// The function receives the directory and its size in bytes.
+    fn clean_with<F>(
+        &self,
+        remove: F,
+        observer: Option<&dyn ScanObserver>,
+    ) -> Result<CleanReport, CleanError>
+    where
+        F: Fn(&Path, u64) -> std::io::Result<()>,
+    {
+        self.check_protected()?;
+        let paths: Vec<PathBuf> = self
+            .artifact_directories()
+            .into_iter()
+            .map(|dir| self.path.join(dir))
+            .collect();
+        if self.is_type(ProjectType::Bazel) && paths.iter().any(|path| path.exists()) {
+            bazel::shutdown_server(&self.path);
+        }
+        let mut report = remove_paths(&self.path, paths, remove, observer);
+        report.skipped = self.held_back_artifacts();
+
+        if self.is_type(ProjectType::Bazel) {
+            bazel::remove_dangling_links(&self.path);
+        }
+        Ok(report)
+    }
+
+    /// Returns the existing artifact directories that are excluded or hold a keep file
+    fn held_back_artifacts(&self) -> Vec<PathBuf> {
+        let listed = self.listed_artifact_directories();
+        let kept = kept_among(&self.path, &listed);
+        listed
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .filter(|dir| {
+                self.excluded_artifacts.contains(dir)
+                    || kept.iter().any(|kept| Path::new(dir).starts_with(kept))
+            })
+            .filter(|dir| self.path.join(dir).exists())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Refuses to clean projects matching a protected pattern
+    fn check_protected(&self) -> Result<(), CleanError> {
+        if self.protected {
+            info!(path = %self.path.display(), reason = "protected", "not cleaning project");
+            return Err(CleanError::Protected {
+                path: self.path.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// What cleaning a project did
+///
+/// File and directory counts are only known when devdust removes the artifacts
+/// itself; a native clean command only reports the bytes freed.
+#[derive(Debug, Default, Serialize)]
+pub struct CleanReport {
+    /// Bytes the cleaned artifacts took up
+    pub bytes_freed: u64,
+    /// Files and links removed
+    pub files_removed: u64,
+    /// Directories removed, the artifact directories themselves included
+    pub dirs_removed: u64,
+    /// How long cleaning took, in seconds in JSON
+    #[serde(serialize_with = "serialize_seconds")]
+    pub duration: Duration,
+    /// Each artifact directory cleaned, with its size
+    pub cleaned: Vec<Artifact>,
+    /// Existing artifact directories left alone: excluded, or holding a keep file
+    pub skipped: Vec<PathBuf>,
+    /// One error per artifact directory that could not be cleaned
+    #[serde(serialize_with = "serialize_failures")]
+    pub failures: Vec<CleanError>,
+}
+
+impl CleanReport {
+    /// Returns true if no artifact directory failed to clean
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Turns failures into [`CleanError::PartialFailure`], for callers treating them as errors
+    pub fn into_result(self) -> Result<Self, CleanError> {
+        if self.is_complete() {
+            Ok(self)
+        } else {
+            Err(CleanError::PartialFailure {
+                deleted: self.bytes_freed,
+                errors: self.failures,
+            })
+        }
+    }
+}
+
+fn serialize_seconds<S: serde::Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(duration.as_secs_f64())
+}
+
+fn serialize_failures<S: serde::Serializer>(
+    failures: &[CleanError],
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Failure<'a> {
+        path: Option<&'a Path>,
+        error: String,
+    }
+
+    s.collect_seq(failures.iter().map(|failure| Failure {
+        path: failure.path(),
+        error: failure.to_string(),
+    }))
+}
+
+/// Removes each existing path with the given function and reports what it freed
+///
+/// Paths below `root` are reported relative to it.
+fn remove_paths<F>(
+    root: &Path,
+    paths: Vec<PathBuf>,
+    remove: F,
+    observer: Option<&dyn ScanObserver>,
+) -> CleanReport
+where
+    F: Fn(&Path, u64) -> std::io::Result<()>,
+{
+    let started = Instant::now();
+    let mut report = CleanReport::default();
+
+    for artifact_path in paths {
+        let relative = artifact_path
+            .strip_prefix(root)
+            .unwrap_or(&artifact_path)
+            .to_path_buf();
+        if !artifact_path.exists() {
+            continue;
+        }
+
+        // Measure before deletion
+        let tally = disk_usage::tally(&artifact_path);
+
+        match remove(&artifact_path, tally.bytes) {
+            Ok(_) => {
+                info!(path = %artifact_path.display(), bytes = tally.bytes, "removed artifact");
+                report.bytes_freed += tally.bytes;
+                report.files_removed += tally.files;
+                report.dirs_removed += tally.dirs;
+                if let Some(observer) = observer {
+                    observer.artifact_deleted(&artifact_path, tally.bytes);
+                }
+                report.cleaned.push(Artifact {
+                    path: relative,
+                    size: tally.bytes,
+                });
+            }
+            Err(e) => {
+                let error = CleanError::at(artifact_path, e);
+                warn!(error = %error, "could not remove artifact");
+                if let Some(observer) = observer {
+                    observer.error(error.path(), &error);
+                }
+                report.failures.push(error);
+            }
+        }
+    }
+
+    report.duration = started.elapsed();
+    report
+}
+
+// ============================================================================
+// Scanning Configuration
+// ============================================================================
+
+/// Options for scanning directories
+///
+/// New options may be added in any release; construct it with
+/// [`ScanOptions::builder`] or start from [`ScanOptions::default`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ScanOptions {
+    /// Whether to follow symbolic links
+    pub follow_symlinks: bool,
+    /// Whether to stay on the same filesystem
+    pub same_filesystem: bool,
+    /// How many levels below the scan root to look for projects (`None` = unlimited)
+    pub max_depth: Option<usize>,
+    /// Minimum age in seconds for projects to be included
+    pub min_age_seconds: u64,
+    /// What the age of a project is measured from
+    pub age_source: AgeSource,
+    /// Minimum artifact size in bytes, applied by [`scan_with_sizes`]
+    pub min_artifact_size: u64,
+    /// Glob patterns for directories to skip while scanning and cleaning
+    ///
+    /// Patterns without a path separator match directory names, the rest match
+    /// full paths (`**` crosses directories).
+    pub exclude: Vec<String>,
+    /// Glob patterns for projects that are reported but never cleaned
+    ///
+    /// Patterns are matched against project directories like `exclude` patterns.
+    pub protected: Vec<String>,
+    /// Only report projects of these types (empty = all types)
+    pub project_types: Vec<ProjectType>,
+    /// Per-project-type settings that take precedence over the global ones
+    pub type_overrides: HashMap<ProjectType, TypeOverride>,
+    /// Number of worker threads used for project detection (1 = sequential)
+    pub parallelism: usize,
+    /// Whether the directory walk itself runs on the `parallelism` workers
+    ///
+    /// Otherwise a single thread walks and hands directories to the workers.
+    pub parallel_walk: bool,
+    /// Whether to skip directories excluded by git: `.gitignore` files,
+    /// `.git/info/exclude` and the global `core.excludesFile`
+    ///
+    /// Large ignored trees (datasets, vendored toolchains) are then never
+    /// walked, but neither are projects inside them.
+    pub respect_gitignore: bool,
+    /// User-defined project types, checked before the built-in ones
+    pub custom_rules: Vec<CustomRule>,
+    /// Whether to honor `.devdustignore` files in scan roots and their parents
+    pub ignore_files: bool,
+    /// How to treat projects with uncommitted changes or unpushed commits
+    pub git_policy: GitPolicy,
+    /// Only clean dependency folders (e.g., `node_modules`) next to a lockfile
+    pub require_lockfile: bool,
+    /// Whether to clean [`ArtifactCategory::Build`] directories
+    pub clean_builds: bool,
+    /// Whether to clean [`ArtifactCategory::Cache`] directories
+    pub clean_caches: bool,
+    /// Whether to clean [`ArtifactCategory::Environment`] directories
+    pub clean_environments: bool,
+    /// Whether to clean [`ArtifactCategory::Experiment`] directories
+    pub clean_experiments: bool,
+    /// Count only stale artifacts for project types with a partial cleaning strategy
+    pub partial_clean: Option<PartialClean>,
+    /// Called as the scan makes progress
+    pub progress: Option<ProgressCallback>,
+    /// Notified as the scan makes progress, in addition to `progress`
+    pub observer: Option<Arc<dyn ScanObserver>>,
+    /// Remembered artifact sizes; unchanged directories are not walked again
+    ///
+    /// Only apparent sizes with hardlinks counted once are cached; with
+    /// `disk_usage` set or `dedupe_hardlinks` unset it is not consulted.
+    pub size_cache: Option<Arc<SizeCache>>,
+    /// Records how long detection and sizing take, per root and per project
+    pub timings: Option<ScanTimings>,
+    /// Measure allocated blocks instead of file lengths (see [`DiskUsage`])
+    pub disk_usage: bool,
+    /// Count a file hardlinked several times within a directory only once
+    pub dedupe_hardlinks: bool,
+    /// Estimate sizes from a sample of each artifact directory instead of walking it all
+    ///
+    /// Much faster on big trees, and usually within a few percent; estimates are
+    /// not stored in the size cache. Cleaning always measures exactly.
+    pub estimate_sizes: bool,
+    /// Don't size artifacts at all; every size is 0 and `min_artifact_size` is ignored
+    ///
+    /// For a bare inventory where the size pass would dominate, as on spinning
+    /// disks. Projects are still only reported if an artifact directory exists.
+    pub skip_sizes: bool,
+    /// Also report artifact directories left behind without a project marker
+    pub orphans: bool,
+    /// Also report Cargo target directories shared by several projects
+    ///
+    /// A target directory set by `CARGO_TARGET_DIR`, `$CARGO_HOME` or a parent's
+    /// `.cargo/config.toml` is never sized or cleaned with the projects building
+    /// into it. With this set, each one found is reported once, after the walk,
+    /// as a project of its own.
+    pub shared_targets: bool,
+    /// Only report projects with artifacts older than their lockfile
+    pub stale_only: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: false,
+            same_filesystem: true,
+            max_depth: None,
+            min_age_seconds: 0,
+            age_source: AgeSource::Mtime,
+            min_artifact_size: 0,
+            exclude: Vec::new(),
+            protected: Vec::new(),
+            project_types: Vec::new(),
+            type_overrides: HashMap::new(),
+            parallelism: 1,
+            parallel_walk: true,
+            respect_gitignore: false,
+            custom_rules: Vec::new(),
+            ignore_files: true,
+            git_policy: GitPolicy::Ignore,
+            require_lockfile: false,
+            clean_builds: true,
+            clean_caches: true,
+            clean_environments: false,
+            clean_experiments: false,
+            partial_clean: None,
+            progress: None,
+            observer: None,
+            size_cache: None,
+            timings: None,
+            disk_usage: false,
+            dedupe_hardlinks: true,
+            estimate_sizes: false,
+            skip_sizes: false,
+            orphans: false,
+            shared_targets: false,
+            stale_only: false,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Returns a builder starting from the default options
+    pub fn builder() -> ScanOptionsBuilder {
+        ScanOptionsBuilder::default()
+    }
+
+    /// Checks that all exclude and protected patterns are valid globs
+    pub fn validate(&self) -> Result<(), ScanError> {
+        ExcludeSet::new(&self.exclude)?;
+        ExcludeSet::new(&self.protected)?;
+        Ok(())
+    }
+
+    /// Passes the progress callback and the observer, if set, to `f`
+    pub(crate) fn notify<F: Fn(&dyn ScanObserver)>(&self, f: F) {
+        if let Some(progress) = &self.progress {
+            f(progress);
+        }
+        if let Some(observer) = &self.observer {
+            f(observer.as_ref());
+        }
+    }
+}
+
+/// Builder for [`ScanOptions`]
+///
+/// ```
+/// use std::time::Duration;
+/// use devdust_core::ScanOptions;
+///
+/// let options = ScanOptions::builder()
+///     .follow_symlinks(true)
+///     .min_age(Duration::from_secs(30 * 24 * 60 * 60))
+///     .build();
+/// assert_eq!(options.min_age_seconds, 30 * 24 * 60 * 60);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptionsBuilder {
+    options: ScanOptions,
+}
+
+impl ScanOptionsBuilder {
+    /// Whether to follow symbolic links
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.options.follow_symlinks = follow;
+        self
+    }
+
+    /// Whether to stay on the same filesystem
+    pub fn same_filesystem(mut self, same: bool) -> Self {
+        self.options.same_filesystem = same;
+        self
+    }
+
+    /// Only look for projects this many levels below the scan root
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    /// Only include projects not modified for at least this long
+    pub fn min_age(mut self, age: Duration) -> Self {
+        self.options.min_age_seconds = age.as_secs();
+        self
+    }
+
+    /// What the age of a project is measured from
+    pub fn age_source(mut self, source: AgeSource) -> Self {
+        self.options.age_source = source;
+        self
+    }
+
+    /// Skip projects with fewer bytes of artifacts (see [`scan_with_sizes`])
+    pub fn min_artifact_size(mut self, bytes: u64) -> Self {
+        self.options.min_artifact_size = bytes;
+        self
+    }
+
+    /// Glob patterns for directories to skip while scanning and cleaning
+    pub fn exclude<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.exclude = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Report orphaned artifact directories (see [`Project::orphaned`])
+    pub fn orphans(mut self, enabled: bool) -> Self {
+        self.options.orphans = enabled;
+        self
+    }
+
+    /// Report shared Cargo target directories once each (see [`ScanOptions::shared_targets`])
+    pub fn shared_targets(mut self, enabled: bool) -> Self {
+        self.options.shared_targets = enabled;
+        self
+    }
+
+    /// Only report projects with stale artifacts (see [`Project::stale_artifacts`])
+    pub fn stale_only(mut self, enabled: bool) -> Self {
+        self.options.stale_only = enabled;
+        self
+    }
+
+    /// Glob patterns for projects that are reported but never cleaned
+    pub fn protected<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.protected = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only report projects of these types (empty = all types)
+    pub fn project_types(mut self, types: impl IntoIterator<Item = ProjectType>) -> Self {
+        self.options.project_types = types.into_iter().collect();
+        self
+    }
+
+    /// Per-project-type settings that take precedence over the global ones
+    pub fn type_overrides(mut self, overrides: HashMap<ProjectType, TypeOverride>) -> Self {
+        self.options.type_overrides = overrides;
+        self
+    }
+
+    /// Number of worker threads used for project detection (1 = sequential)
+    pub fn parallelism(mut self, threads: usize) -> Self {
+        self.options.parallelism = threads;
+        self
+    }
+
+    /// Whether the directory walk itself runs on the worker threads
+    pub fn parallel_walk(mut self, parallel: bool) -> Self {
+        self.options.parallel_walk = parallel;
+        self
+    }
+
+    /// Whether to skip directories excluded by git ignore rules
+    pub fn respect_gitignore(mut self, respect: bool) -> Self {
+        self.options.respect_gitignore = respect;
+        self
+    }
+
+    /// User-defined project types, checked before the built-in ones
+    pub fn custom_rules(mut self, rules: Vec<CustomRule>) -> Self {
+        self.options.custom_rules = rules;
+        self
+    }
+
+    /// Whether to honor `.devdustignore` files
+    pub fn ignore_files(mut self, honor: bool) -> Self {
+        self.options.ignore_files = honor;
+        self
+    }
+
+    /// How to treat projects with uncommitted changes or unpushed commits
+    pub fn git_policy(mut self, policy: GitPolicy) -> Self {
+        self.options.git_policy = policy;
+        self
+    }
+
+    /// Only clean dependency folders (e.g., `node_modules`) next to a lockfile
+    pub fn require_lockfile(mut self, require: bool) -> Self {
+        self.options.require_lockfile = require;
+        self
+    }
+
+    /// Whether to clean [`ArtifactCategory::Build`] directories
+    pub fn clean_builds(mut self, clean: bool) -> Self {
+        self.options.clean_builds = clean;
+        self
+    }
+
+    /// Whether to clean [`ArtifactCategory::Cache`] directories
+    pub fn clean_caches(mut self, clean: bool) -> Self {
+        self.options.clean_caches = clean;
+        self
+    }
+
+    /// Whether to clean [`ArtifactCategory::Environment`] directories
+    pub fn clean_environments(mut self, clean: bool) -> Self {
+        self.options.clean_environments = clean;
+        self
+    }
+
+    /// Whether to clean [`ArtifactCategory::Experiment`] directories
+    pub fn clean_experiments(mut self, clean: bool) -> Self {
+        self.options.clean_experiments = clean;
+        self
+    }
+
+    /// Count only stale artifacts for project types with a partial cleaning strategy
+    pub fn partial_clean(mut self, partial: Option<PartialClean>) -> Self {
+        self.options.partial_clean = partial;
+        self
+    }
+
+    /// Called as the scan makes progress
+    pub fn progress(mut self, callback: ProgressCallback) -> Self {
+        self.options.progress = Some(callback);
+        self
+    }
+
+    /// Notified as the scan makes progress, in addition to the progress callback
+    pub fn observer(mut self, observer: Arc<dyn ScanObserver>) -> Self {
+        self.options.observer = Some(observer);
+        self
+    }
+
+    /// Remembered artifact sizes; unchanged directories are not walked again
+    pub fn size_cache(mut self, cache: Arc<SizeCache>) -> Self {
+        self.options.size_cache = Some(cache);
+        self
+    }
+
+    /// Records how long detection and sizing take into `timings`
+    pub fn timings(mut self, timings: ScanTimings) -> Self {
+        self.options.timings = Some(timings);
+        self
+    }
+
+    /// Measure allocated blocks instead of file lengths
+    pub fn disk_usage(mut self, on_disk: bool) -> Self {
+        self.options.disk_usage = on_disk;
+        self
+    }
+
+    /// Estimate artifact sizes from a sample instead of walking every file
+    pub fn estimate_sizes(mut self, estimate: bool) -> Self {
+        self.options.estimate_sizes = estimate;
+        self
+    }
+
+    /// Don't size artifacts at all, only find them
+    pub fn skip_sizes(mut self, skip: bool) -> Self {
+        self.options.skip_sizes = skip;
+        self
+    }
+
+    /// Count a file hardlinked several times within a directory only once
+    pub fn dedupe_hardlinks(mut self, dedupe: bool) -> Self {
+        self.options.dedupe_hardlinks = dedupe;
+        self
+    }
+
+    /// Returns the configured options
+    pub fn build(self) -> ScanOptions {
+        self.options
+    }
+}
+
+/// What the age of a project is measured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgeSource {
+    /// Most recent modification time of any file in the project
+    #[default]
+    Mtime,
+    /// Most recent access time of any file in the project
+    ///
+    /// Only meaningful on filesystems that record access times (not `noatime`).
+    Atime,
+    /// Time of the last commit in the git repository containing the project
+    ///
+    /// Falls back to `Mtime` for projects outside a repository or without commits.
+    Commit,
+}
+
+impl AgeSource {
+    /// All age sources
+    pub const ALL: &'static [AgeSource] = &[Self::Mtime, Self::Atime, Self::Commit];
+
+    /// Returns the identifier used on the command line and in config files
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Mtime => "mtime",
+            Self::Atime => "atime",
+            Self::Commit => "commit",
+        }
+    }
+}
+
+impl fmt::Display for AgeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for AgeSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|source| source.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown age source: {}. Use mtime, atime, or commit", s))
+    }
+}
+
+/// Settings that apply to a single project type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeOverride {
+    /// Whether projects of this type are reported at all
+    pub enabled: bool,
+    /// Minimum age in seconds, replacing `ScanOptions::min_age_seconds`
+    pub min_age_seconds: Option<u64>,
+}
+
+impl Default for TypeOverride {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_age_seconds: None,
+        }
+    }
+}
+
+// ============================================================================
+// Scanning Functions
+// ============================================================================
+
+/// Scans a directory recursively to find development projects
+///
+/// When `options.parallelism` is greater than one, project detection (and with
+/// `options.parallel_walk`, the walk) runs on that many threads, and results are
+/// yielded in completion order rather than walk order.
+pub fn scan_directory<P: AsRef<Path>>(
+    path: P,
+    options: &ScanOptions,
+) -> impl Iterator<Item = Result<Project, ScanError>> {
+    scan_with(path.as_ref(), options, |project, _| Some(project))
+}
+
+/// Scans like [`scan_directory`], also calculating each project's artifact size
+///
+/// Sizes are calculated on the scan's worker threads. Projects without artifacts
+/// or with less than `options.min_artifact_size` bytes of them are skipped.
+pub fn scan_with_sizes<P: AsRef<Path>>(
+    path: P,
+    options: &ScanOptions,
+) -> impl Iterator<Item = Result<(Project, u64), ScanError>> {
+    scan_with(path.as_ref(), options, with_size)
+}
+
+/// Scans several roots concurrently, like [`scan_with_sizes`] for each of them
+///
+/// Every root is walked on its own thread, so roots on different drives are
+/// scanned at the same time. Results are merged in completion order; a project
+/// reachable from more than one root is reported once.
+pub fn scan_many<P: AsRef<Path>>(
+    paths: &[P],
+    options: &ScanOptions,
+) -> impl Iterator<Item = Result<(Project, u64), ScanError>> {
+    let (sender, receiver) = mpsc::channel();
+
+    for path in paths {
+        let scan = scan_with(path.as_ref(), options, with_size);
+        let sender = sender.clone();
+        thread::spawn(move || {
+            for result in scan {
+                // The receiver may have been dropped; stop walking then
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    let mut seen = HashSet::new();
+    receiver.into_iter().filter(move |result| match result {
+        Ok((project, _)) => seen.insert(project.path.clone()),
+        Err(_) => true,
+    })
+}
+
+/// Per-project step run on the scan workers after detection and filtering
+type Finish<T> = fn(Project, &ScanOptions) -> Option<T>;
+
+/// Calculates the artifact size, dropping projects below `options.min_artifact_size`
+///
+/// With `options.skip_sizes` set, only projects without artifacts are dropped.
+fn with_size(project: Project, options: &ScanOptions) -> Option<(Project, u64)> {
+    if options.skip_sizes {
+        return (!project.artifact_paths(options).is_empty()).then_some((project, 0));
+    }
+    let artifact_size = match &options.timings {
+        Some(timings) => timings.size(&project.path, || project.calculate_artifact_size(options)),
+        None => project.calculate_artifact_size(options),
+    };
+    if artifact_size == 0 {
+        skip(options, &project.path, SkipReason::NoArtifacts);
+        return None;
+    }
+    if artifact_size < options.min_artifact_size {
+        skip(options, &project.path, SkipReason::TooSmall);
+        return None;
+    }
+    Some((project, artifact_size))
+}
+
+/// Walks `path` and yields every detected project that `finish` keeps
+fn scan_with<T: Send + 'static>(
+    path: &Path,
+    options: &ScanOptions,
+    finish: Finish<T>,
+) -> Box<dyn Iterator<Item = Result<T, ScanError>> + Send> {
+    let path = path.to_path_buf();
+    let mut options = options.clone();
+    if let Some(timings) = &mut options.timings {
+        *timings = timings.for_root(&path);
+    }
+
+    let ignore_matcher = if options.ignore_files {
+        IgnoreMatcher::for_root(&path)
+    } else {
+        IgnoreMatcher::default()
+    };
+
+    let patterns = match Patterns::new(&options) {
+        Ok(patterns) => patterns,
+        Err(e) => return Box::new(std::iter::once(Err(ScanError::InvalidPattern(e)))),
+    };
+
+    let walk_exclude = patterns.exclude.clone();
+    let known_artifacts = KnownArtifacts::new(&options.custom_rules);
+    let owners = known_artifacts.clone();
+    let orphans = options.orphans;
+    let gitignore = options.respect_gitignore;
+    // The walker stops at other filesystems silently; checking here tells why
+    let root_device = options.same_filesystem.then(|| device_id(&path)).flatten();
+    let walk_options = options.clone();
+    let mut walker = ignore::WalkBuilder::new(&path);
+    walker
+        // Hidden directories are walked, only projects directly in them are skipped
+        .standard_filters(false)
+        .parents(gitignore)
+        .git_ignore(gitignore)
+        .git_exclude(gitignore)
+        .git_global(gitignore)
+        .follow_links(options.follow_symlinks)
+        .same_file_system(options.same_filesystem)
+        .max_depth(options.max_depth)
+        .threads(options.parallelism)
+        // Prune excluded and ignored directories so their subtrees are never visited,
+        // as well as artifact directories (node_modules, target, ...) of their parent project
+        .filter_entry(move |e| {
+            if !e.file_type().is_some_and(|t| t.is_dir()) {
+                return true;
+            }
+            let reason = if walk_exclude.is_match(e.path()) {
+                SkipReason::Excluded
+            } else if ignore_matcher.is_ignored(e.path(), true) {
+                SkipReason::Ignored
+            } else if root_device.is_some() && device_id(e.path()) != root_device {
+                SkipReason::OtherFilesystem
+            } else if e.depth() > 0
+                && (known_artifacts.is_artifact_dir(e.path())
+                    || (orphans && orphan::is_artifact_like(e.path())))
+            {
+                // Walked as part of their project, nothing to report
+                trace!(path = %e.path().display(), "not walking artifact directory");
+                return false;
+            } else {
+                return true;
+            };
+            skip(&walk_options, e.path(), reason);
+            false
+        });
+
+    let shared = SharedTargets::default();
+    let walk: Box<dyn Iterator<Item = Result<T, ScanError>> + Send> =
+        if options.parallelism > 1 && options.parallel_walk {
+            Box::new(walk_parallel(
+                walker.build_parallel(),
+                options.clone(),
+                patterns,
+                owners,
+                shared.clone(),
+                finish,
+            ))
+        } else if options.parallelism > 1 {
+            Box::new(scan_parallel(
+                walker.build(),
+                options.clone(),
+                patterns,
+                owners,
+                shared.clone(),
+                finish,
+            ))
+        } else {
+            // Filter and map entries to projects
+            let (options, shared) = (options.clone(), shared.clone());
+            Box::new(walker.build().filter_map(move |entry| {
+                scan_entry(entry, &options, &patterns, &owners, &shared, finish)
+            }))
+        };
+
+    // Shared target directories are only known once every project was seen
+    let shared_projects = std::iter::once(()).flat_map(move |()| {
+        let options = options.clone();
+        shared.take().into_iter().filter_map(move |dir| {
+            let project = cargo_target::shared_target_project(dir);
+            options.notify(|observer| observer.project_found(&project));
+            finish(project, &options).map(Ok)
+        })
+    });
+    Box::new(walk.chain(shared_projects))
+}
+
+/// Runs project detection for walked entries on a dedicated thread pool
+///
+/// The walk itself stays sequential; the expensive per-directory work (reading
+/// entries for marker files, computing ages and sizes) is spread across the workers.
+fn scan_parallel<I, T>(
+    walker: I,
+    options: ScanOptions,
+    patterns: Patterns,
+    owners: KnownArtifacts,
+    shared: SharedTargets,
+    finish: Finish<T>,
+) -> mpsc::IntoIter<Result<T, ScanError>>
+where
+    I: Iterator<Item = Result<ignore::DirEntry, ignore::Error>> + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let pool = match rayon::ThreadPoolBuilder::new()
+            .num_threads(options.parallelism)
+            .build()
+        {
+            Ok(pool) => pool,
+            Err(e) => {
+                let error = std::io::Error::other(e);
+                let _ = sender.send(Err(ScanError::IoError(error)));
+                return;
+            }
+        };
+
+        pool.install(|| {
+            walker
+                .par_bridge()
+                .filter_map(|entry| {
+                    scan_entry(entry, &options, &patterns, &owners, &shared, finish)
+                })
+                .for_each_with(sender, |sender, result| {
+                    // The receiver may have been dropped; nothing left to do then
+                    let _ = sender.send(result);
+                });
+        });
+    });
+
+    receiver.into_iter()
+}
+
+/// Walks and runs project detection on the walker's own threads
+///
+/// Each thread detects projects in the directories it reads, so wide trees are
+/// listed by several threads at once.
+fn walk_parallel<T: Send + 'static>(
+    walker: ignore::WalkParallel,
+    options: ScanOptions,
+    patterns: Patterns,
+    owners: KnownArtifacts,
+    shared: SharedTargets,
+    finish: Finish<T>,
+) -> mpsc::IntoIter<Result<T, ScanError>> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        walker.run(|| {
+            let sender = sender.clone();
+            let (options, patterns, owners, shared) = (&options, &patterns, &owners, &shared);
+            Box::new(move |entry| {
+                match scan_entry(entry, options, patterns, owners, shared, finish)
+                    .map(|result| sender.send(result))
+                {
+                    // The receiver may have been dropped; stop walking then
+                    Some(Err(_)) => ignore::WalkState::Quit,
+                    _ => ignore::WalkState::Continue,
+                }
+            })
+        });
+    });
+
+    receiver.into_iter()
+}
+
+/// Detects the project at a walked entry and applies the finishing step to it
+fn scan_entry<T>(
+    entry: Result<ignore::DirEntry, ignore::Error>,
+    options: &ScanOptions,
+    patterns: &Patterns,
+    owners: &KnownArtifacts,
+    shared: &SharedTargets,
+    finish: Finish<T>,
+) -> Option<Result<T, ScanError>> {
+    let detected = match &options.timings {
+        Some(timings) => timings.detect(|| process_entry(entry, options, patterns, owners)),
+        None => process_entry(entry, options, patterns, owners),
+    };
+    if let Some(Ok(project)) = &detected {
+        if options.shared_targets && project.is_type(ProjectType::Rust) {
+            shared.record(project);
+        }
+    }
+    detected.and_then(|result| finish_result(result, options, finish))
+}
+
+/// Applies the finishing step to a detected project, passing errors through
+fn finish_result<T>(
+    result: Result<Project, ScanError>,
+    options: &ScanOptions,
+    finish: Finish<T>,
+) -> Option<Result<T, ScanError>> {
+    match result {
+        Ok(project) => finish(project, options).map(Ok),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// Compiled exclude and protected patterns of a scan
+#[derive(Debug, Clone)]
+struct Patterns {
+    exclude: ExcludeSet,
+    protected: ExcludeSet,
+}
+
+impl Patterns {
+    fn new(options: &ScanOptions) -> Result<Self, globset::Error> {
+        Ok(Self {
+            exclude: ExcludeSet::new(&options.exclude)?,
+            protected: ExcludeSet::new(&options.protected)?,
+        })
+    }
+}
+
+/// Turns a single walked entry into a project, if it is one that passes the filters
+fn process_entry(
+    entry: Result<ignore::DirEntry, ignore::Error>,
+    options: &ScanOptions,
+    patterns: &Patterns,
+    owners: &KnownArtifacts,
+) -> Option<Result<Project, ScanError>> {
+    let entry = match entry {
+        Ok(e) => e,
+        Err(e) => {
+            warn!(error = %e, "could not walk directory");
+            let denied = e
+                .io_error()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied);
+            if let (true, Some(path)) = (denied, error_path(&e)) {
+                skip(options, path, SkipReason::PermissionDenied);
+            }
+            options.notify(|observer| observer.error(error_path(&e), &e));
+            return Some(Err(e.into()));
+        }
+    };
+
+    // Only process directories
+    if !entry.file_type().is_some_and(|t| t.is_dir()) {
+        return None;
+    }
+
+    options.notify(|observer| observer.directory_entered(entry.path()));
+    if let Some(timings) = &options.timings {
+        timings.directory_visited();
+    }
+
+    // Skip hidden directories (starting with .)
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        skip(options, entry.path(), SkipReason::Hidden);
+        return None;
+    }
+
+    let dir_path = entry.path();
+
+    // Try to detect project type, then look for artifacts left behind by a deleted one
+    let mut project = match Project::detect(dir_path, &options.custom_rules) {
+        Some(project) => project,
+        None if options.orphans => orphan::detect(dir_path)?,
+        None => return None,
+    };
+    debug!(
+        path = %dir_path.display(),
+        project_type = %project.type_name(),
+        "detected project"
+    );
+
+    // Disabled types don't contribute their artifacts to a project of several
+    project
+        .additional_types
+        .retain(|t| options.type_overrides.get(t).is_none_or(|o| o.enabled));
+
+    // Keep excluded artifact directories out of sizing and cleaning
+    let exclude = &patterns.exclude;
+    if !exclude.is_empty() {
+        project.excluded_artifacts = project
+            .all_artifact_directories()
+            .into_iter()
+            .filter(|dir| exclude.is_match(&dir_path.join(dir)))
+            .collect();
+    }
+
+    // Sized and cleaned with the enclosing project inside the scan (e.g. a React Native app)
+    let claimed = owners.claimed_by_enclosing(&project, entry.depth());
+    project.excluded_artifacts.extend(claimed);
+
+    project.protected = !patterns.protected.is_empty() && patterns.protected.is_match(dir_path);
+
+    // Leave out artifact categories that were not asked for
+    if project.custom_rule.is_none()
+        && !(options.clean_builds
+            && options.clean_caches
+            && options.clean_environments
+            && options.clean_experiments)
+    {
+        let skipped: Vec<String> = project
+            .typed_artifact_directories()
+            .into_iter()
+            .filter(|(project_type, dir)| {
+                // Tagged directories no type lists are caches by definition
+                let category =
+                    project_type.map_or(ArtifactCategory::Cache, |t| t.artifact_category(dir));
+                let wanted = match category {
+                    ArtifactCategory::Build => options.clean_builds,
+                    ArtifactCategory::Cache => options.clean_caches,
+                    ArtifactCategory::Environment => options.clean_environments,
+                    ArtifactCategory::Experiment => options.clean_experiments,
+                };
+                !wanted && dir_path.join(dir).exists()
+            })
+            .map(|(_, dir)| dir)
+            .collect();
+        project.excluded_artifacts.extend(skipped);
+    }
+
+    // Dependency folders without a lockfile cannot be restored exactly
+    if options.require_lockfile && project.custom_rule.is_none() {
+        let unlocked: Vec<String> = project
+            .typed_artifact_directories()
+            .into_iter()
+            .filter(|(project_type, dir)| {
+                let lockfiles = project_type
+                    .as_ref()
+                    .map_or(&[][..], |t| t.required_lockfiles(dir));
+                !lockfiles.is_empty()
+                    && dir_path.join(dir).exists()
+                    && !lockfiles.iter().any(|f| dir_path.join(f).is_file())
+            })
+            .map(|(_, dir)| dir)
+            .collect();
+        project.excluded_artifacts.extend(unlocked);
+    }
+
+    if !options.project_types.is_empty()
+        && !project
+            .project_types()
+            .iter()
+            .any(|t| options.project_types.contains(t))
+    {
+        skip(options, dir_path, SkipReason::TypeFiltered);
+        return None;
+    }
+
+    // Apply per-type overrides, falling back to the global settings
+    let type_override = options.type_overrides.get(&project.project_type);
+    if type_override.is_some_and(|o| !o.enabled) {
+        skip(options, dir_path, SkipReason::TypeFiltered);
+        return None;
+    }
+    let min_age_seconds = type_override
+        .and_then(|o| o.min_age_seconds)
+        .unwrap_or(options.min_age_seconds);
+
+    // Check age filter if specified
+    if min_age_seconds > 0 {
+        if let Ok(last_modified) = project.last_modified(options) {
+            if let Ok(elapsed) = last_modified.elapsed() {
+                if elapsed.as_secs() < min_age_seconds {
+                    skip(options, dir_path, SkipReason::TooNew);
+                    return None;
+                }
+            }
+        }
+    }
+
+    if options.stale_only && project.stale_artifacts().is_empty() {
+        skip(options, dir_path, SkipReason::NothingStale);
+        return None;
+    }
+
+    // Projects with work in progress are probably about to be rebuilt
+    if options.git_policy == GitPolicy::Skip
+        && project
+            .git_status()
+            .is_some_and(|status| !status.is_clean())
+    {
+        skip(options, dir_path, SkipReason::UncommittedWork);
+        return None;
+    }
+
+    if project.protected {
+        skip(options, dir_path, SkipReason::Protected);
+    }
+    options.notify(|observer| observer.project_found(&project));
+
+    Some(Ok(project))
+}
+
+/// Logs why the scan passed over `path` and tells the observers
+fn skip(options: &ScanOptions, path: &Path, reason: SkipReason) {
+    // Every dot directory is one, which would drown out the rest
+    if reason == SkipReason::Hidden {
+        trace!(path = %path.display(), %reason, "skipping");
+    } else {
+        debug!(path = %path.display(), %reason, "skipping");
+    }
+    options.notify(|observer| observer.skipped(path, reason));
+}
+
+/// Calculates the total size of a directory in bytes
+///
+/// With `options.disk_usage` set, the allocated size is returned instead of the
+/// sum of file lengths; with `options.estimate_sizes` set, an estimate of it.
+/// With `options.skip_sizes` set, it is always 0.
+pub fn calculate_directory_size<P: AsRef<Path>>(path: P, options: &ScanOptions) -> u64 {
+    if options.skip_sizes {
+        return 0;
+    }
+    if options.estimate_sizes {
+        return disk_usage::estimate(path.as_ref(), options);
+    }
+    if options.disk_usage {
+        return calculate_disk_usage(path, options).on_disk;
+    }
+
+    disk_usage::files(path.as_ref(), options)
+        .map(|(_, metadata)| metadata.len())
+        .sum()
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Errors that can occur during scanning
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ScanError {
+    /// A directory could not be read for lack of permissions
+    #[error("Permission denied: {}", path.display())]
+    PermissionDenied {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A path disappeared while it was being scanned
+    #[error("Path not found: {}", path.display())]
+    NotFound {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Any other error from the directory walk, such as a symlink loop
+    #[error("Walk error: {0}")]
+    WalkError(ignore::Error),
+    /// IO error
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    /// An exclude or protected pattern is not a valid glob
+    #[error("Invalid exclude or protected pattern: {0}")]
+    InvalidPattern(#[from] globset::Error),
+}
+
+impl From<ignore::Error> for ScanError {
+    fn from(e: ignore::Error) -> Self {
+        let path = error_path(&e).map(Path::to_path_buf);
+        let kind = e.io_error().map(std::io::Error::kind);
+
+        match (path, kind) {
+            (Some(path), Some(kind @ std::io::ErrorKind::PermissionDenied)) => {
+                Self::PermissionDenied {
+                    path,
+                    source: std::io::Error::new(kind, e),
+                }
+            }
+            (Some(path), Some(kind @ std::io::ErrorKind::NotFound)) => Self::NotFound {
+                path,
+                source: std::io::Error::new(kind, e),
+            },
+            _ => Self::WalkError(e),
+        }
+    }
+}
+
+/// Returns the path a walk error occurred at, if it has one
+fn error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
+        }
+        _ => None,
+    }
+}
+
+/// Errors that can occur during cleaning
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CleanError {
+    /// The artifacts could not be removed for lack of permissions
+    #[error("Permission denied: {}", path.display())]
+    PermissionDenied {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The path disappeared before it could be removed
+    #[error("Path not found: {}", path.display())]
+    NotFound {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// A rename failed because it would cross filesystems
+    ///
+    /// No clean method returns this: quarantine and relocation copy across
+    /// filesystems, and trash errors are reported as [`CleanError::Io`]. It
+    /// comes from [`CleanError::at`] classifying such an error.
+    #[error("Cannot move across filesystems: {}", path.display())]
+    CrossDevice {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The artifacts are on a read-only filesystem
+    #[error("Read-only filesystem: {}", path.display())]
+    ReadOnly {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The artifacts are in use by a running process
+    #[error("In use by a running process: {}", path.display())]
+    InUse { path: PathBuf },
+    /// The artifacts are on a network or FUSE filesystem and cleaning there was not allowed
+    #[error("On a {kind} filesystem: {}", path.display())]
+    RemoteFilesystem { path: PathBuf, kind: FsKind },
+    /// The project matches a protected pattern
+    #[error("Protected: {}", path.display())]
+    Protected { path: PathBuf },
+    /// A hook command failed; before a clean, the project was left alone
+    #[error("Hook `{command}` failed: {reason}")]
+    Hook {
+        path: PathBuf,
+        command: String,
+        reason: String,
+    },
+    /// Any other error while removing a path
+    #[error("Clean error: {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Complete failure to clean
+    #[error("Clean error: {0}")]
+    IoError(#[from] std::io::Error),
+    /// Some directories were cleaned, but others failed
+    #[error("Partially cleaned ({deleted} bytes), {} errors occurred", errors.len())]
+    PartialFailure {
+        deleted: u64,
+        /// One error per path that could not be removed
+        errors: Vec<CleanError>,
+    },
+}
+
+impl CleanError {
+    /// Classifies an IO error that occurred while removing `path`
+    pub fn at(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let path = path.into();
+        match source.kind() {
+            ErrorKind::PermissionDenied => Self::PermissionDenied { path, source },
+            ErrorKind::NotFound => Self::NotFound { path, source },
+            ErrorKind::CrossesDevices => Self::CrossDevice { path, source },
+            ErrorKind::ReadOnlyFilesystem => Self::ReadOnly { path, source },
+            ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy => Self::InUse { path },
+            _ => Self::Io { path, source },
+        }
+    }
+
+    /// Returns the path the error is about, if it is about a single one
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::PermissionDenied { path, .. }
+            | Self::NotFound { path, .. }
+            | Self::CrossDevice { path, .. }
+            | Self::ReadOnly { path, .. }
+            | Self::InUse { path }
+            | Self::RemoteFilesystem { path, .. }
+            | Self::Protected { path }
+            | Self::Hook { path, .. }
+            | Self::Io { path, .. } => Some(path),
+            Self::IoError(_) | Self::PartialFailure { .. } => None,
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProgressEvent;
+
+    /// Creates a project fixture with a marker file and one artifact file
+    fn make_project(root: &Path, name: &str, marker: &str, artifact: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(dir.join(artifact)).unwrap();
+        fs::write(dir.join(marker), "").unwrap();
+        fs::write(dir.join(artifact).join("output.bin"), [0u8; 64]).unwrap();
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_sequential() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "crate-a", "Cargo.toml", "target");
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(&root.path().join("nested"), "svc", "go.mod", "vendor");
+
+        let scan = |parallelism, parallel_walk| {
+            let options = ScanOptions {
+                parallelism,
+                parallel_walk,
+                ..ScanOptions::default()
+            };
+            let mut paths: Vec<_> = scan_directory(root.path(), &options)
+                .filter_map(Result::ok)
+                .map(|p| p.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let sequential = scan(1, true);
+        assert_eq!(sequential.len(), 3);
+        assert_eq!(sequential, scan(4, true));
+        assert_eq!(sequential, scan(4, false));
+    }
+
+    #[test]
+    fn test_respect_gitignore() {
+        let root = tempfile::tempdir().unwrap();
+        git2::Repository::init(root.path()).unwrap();
+        fs::write(root.path().join(".gitignore"), "/third_party/\n").unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(
+            &root.path().join("third_party"),
+            "zlib",
+            "Cargo.toml",
+            "target",
+        );
+
+        let scan = |respect_gitignore| {
+            let options = ScanOptions {
+                respect_gitignore,
+                ..ScanOptions::default()
+            };
+            scan_directory(root.path(), &options)
+                .filter_map(Result::ok)
+                .count()
+        };
+        assert_eq!(scan(false), 2);
+        assert_eq!(scan(true), 1);
+    }
+
+    #[test]
+    fn test_project_type_filter() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "crate-a", "Cargo.toml", "target");
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(root.path(), "svc", "go.mod", "vendor");
+
+        let mut options = ScanOptions {
+            project_types: vec![ProjectType::Rust, ProjectType::Go],
+            ..ScanOptions::default()
+        };
+        options
+            .type_overrides
+            .entry(ProjectType::Go)
+            .or_default()
+            .enabled = false;
+
+        let types: Vec<_> = scan_directory(root.path(), &options)
+            .filter_map(Result::ok)
+            .map(|p| p.project_type)
+            .collect();
+        assert_eq!(types, vec![ProjectType::Rust]);
+    }
+
+    #[test]
+    fn test_require_lockfile() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "locked", "package.json", "node_modules");
+        make_project(root.path(), "unlocked", "package.json", "node_modules");
+        fs::write(root.path().join("locked").join("yarn.lock"), "").unwrap();
+        fs::create_dir(root.path().join("unlocked").join("dist")).unwrap();
+
+        let options = ScanOptions {
+            require_lockfile: true,
+            ..ScanOptions::default()
+        };
+        let mut projects: Vec<_> = scan_directory(root.path(), &options)
+            .filter_map(Result::ok)
+            .collect();
+        projects.sort_by_key(|p| p.display_name());
+
+        assert!(projects[0]
+            .artifact_directories()
+            .contains(&"node_modules".to_string()));
+        assert_eq!(projects[1].excluded_artifacts, vec!["node_modules"]);
+        assert!(projects[1]
+            .artifact_directories()
+            .contains(&"dist".to_string()));
+    }
+
+    #[test]
+    fn test_artifact_categories() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "app", "main.py", "__pycache__");
+        fs::create_dir(root.path().join("app").join(".venv")).unwrap();
+
+        let scan = |options: &ScanOptions| -> Vec<String> {
+            let project = scan_directory(root.path(), options)
+                .find_map(Result::ok)
+                .unwrap();
+            project.excluded_artifacts
+        };
+
+        assert_eq!(scan(&ScanOptions::default()), vec![".venv"]);
+        let options = ScanOptions {
+            clean_caches: false,
+            clean_environments: true,
+            ..ScanOptions::default()
+        };
+        assert_eq!(scan(&options), vec!["__pycache__"]);
+        assert_eq!(
+            ProjectType::Python.artifact_category("venv"),
+            ArtifactCategory::Environment
+        );
+        assert_eq!(
+            ProjectType::Rust.artifact_category("target"),
+            ArtifactCategory::Build
+        );
+    }
+
+    #[test]
+    fn test_tool_caches_inside_node_modules() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(
+            root.path(),
+            "web",
+            "package.json",
+            "node_modules/.cache/babel",
+        );
+        let web = root.path().join("web");
+        fs::create_dir_all(web.join(".turbo")).unwrap();
+        fs::write(web.join("node_modules").join("react.js"), [0u8; 32]).unwrap();
+
+        let scan = |options: &ScanOptions| -> Vec<String> {
+            let project = scan_directory(root.path(), options)
+                .find_map(Result::ok)
+                .unwrap();
+            project
+                .artifacts(options)
+                .into_iter()
+                .map(|artifact| artifact.path.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        // Cleaned along with node_modules, not counted twice
+        assert_eq!(
+            scan(&ScanOptions::default()),
+            vec!["node_modules", ".turbo"]
+        );
+        let caches_only = ScanOptions {
+            clean_builds: false,
+            ..ScanOptions::default()
+        };
+        assert_eq!(scan(&caches_only), vec![".turbo", "node_modules/.cache"]);
+    }
+
+    #[test]
+    fn test_project_with_several_types() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "desktop", "package.json", "node_modules");
+        let app = root.path().join("desktop");
+        fs::write(app.join("Cargo.toml"), "[package]").unwrap();
+        fs::create_dir_all(app.join("target").join("debug")).unwrap();
+        fs::write(app.join("target").join("debug").join("app"), [0u8; 64]).unwrap();
+
+        let project = Project::detect(&app, &[]).unwrap();
+        let mut types = project.project_types();
+        types.sort_by_key(|t| t.id());
+        assert_eq!(types, vec![ProjectType::Node, ProjectType::Rust]);
+        assert!(project.type_name().contains(", "));
+
+        let dirs = project.artifact_directories();
+        assert!(dirs.contains(&"node_modules".to_string()));
+        assert!(dirs.contains(&"target".to_string()));
+
+        // Found by a filter for either type
+        let options = ScanOptions {
+            project_types: vec![ProjectType::Rust],
+            ..ScanOptions::default()
+        };
+        assert_eq!(scan_directory(root.path(), &options).count(), 1);
+
+        project.clean().unwrap();
+        assert!(!app.join("node_modules").exists());
+        assert!(!app.join("target").exists());
+    }
+
+    #[test]
+    fn test_cache_directory_tags() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "site", "package.json", "node_modules");
+        let site = root.path().join("site");
+        fs::create_dir_all(site.join("thumbnails")).unwrap();
+        cachedir::tag(&site.join("thumbnails")).unwrap();
+
+        let caches_only = ScanOptions {
+            clean_builds: false,
+            ..ScanOptions::default()
+        };
+        let project = scan_directory(root.path(), &caches_only)
+            .find_map(Result::ok)
+            .unwrap();
+        assert_eq!(project.excluded_artifacts, vec!["node_modules"]);
+        assert!(project
+            .artifact_directories()
+            .contains(&"thumbnails".to_string()));
+
+        let project = Project::detect(&site, &[]).unwrap();
+        assert_eq!(project.tag_artifacts().unwrap(), 1);
+        assert!(cachedir::is_tagged(&site.join("node_modules")));
+        assert_eq!(project.tag_artifacts().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_keep_file() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules/.cache");
+        let web = root.path().join("web");
+        fs::create_dir_all(web.join("dist")).unwrap();
+        fs::write(web.join("node_modules").join(KEEP_FILE_NAME), "").unwrap();
+
+        let project = Project::detect(&web, &[]).unwrap();
+        assert_eq!(project.kept_artifacts(), vec!["node_modules"]);
+        // Nothing inside the kept directory is cleaned either
+        assert!(!project
+            .artifact_directories()
+            .iter()
+            .any(|dir| dir.starts_with("node_modules")));
+
+        project.clean().unwrap();
+        assert!(!web.join("dist").exists());
+        assert!(web.join("node_modules").join(".cache").exists());
+    }
+
+    #[test]
+    fn test_nested_python_caches() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "app", "main.py", "__pycache__");
+        let app = root.path().join("app");
+        for dir in [
+            "pkg/__pycache__",
+            "pkg/sub/__pycache__",
+            "src/app.egg-info",
+            ".venv/lib/__pycache__",
+            "web/node_modules/gyp/__pycache__",
+        ] {
+            fs::create_dir_all(app.join(dir)).unwrap();
+        }
+        fs::write(app.join("pkg").join("__init__.py"), "").unwrap();
+
+        let options = ScanOptions::default();
+        let mut projects: Vec<Project> = scan_directory(root.path(), &options)
+            .filter_map(Result::ok)
+            .collect();
+        projects.sort_by_key(|p| p.display_name());
+        let artifacts: Vec<String> = projects[0]
+            .artifacts(&options)
+            .into_iter()
+            .map(|artifact| artifact.path.to_string_lossy().into_owned())
+            .collect();
+
+        // Nothing is picked up inside the virtualenv or node_modules
+        assert_eq!(
+            artifacts,
+            vec![
+                "__pycache__",
+                "pkg/__pycache__",
+                "pkg/sub/__pycache__",
+                "src/app.egg-info"
+            ]
+        );
+        // The package is a project of its own, but its cache belongs to the app
+        assert_eq!(
+            projects[1].excluded_artifacts,
+            vec!["__pycache__", "sub/__pycache__"]
+        );
+        assert_eq!(
+            ProjectType::Python.artifact_category("pkg/sub/__pycache__"),
+            ArtifactCategory::Cache
+        );
+    }
+
+    #[test]
+    fn test_clean_report() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        let dir = root.path().join("web");
+        fs::create_dir_all(dir.join("node_modules").join("pkg")).unwrap();
+        fs::write(
+            dir.join("node_modules").join("pkg").join("index.js"),
+            [0u8; 16],
+        )
+        .unwrap();
+        let rule = CustomRule {
+            name: "web".into(),
+            markers: vec!["package.json".into()],
+            artifacts: vec!["node_modules".into(), "dist".into()],
+        };
+        let mut project = Project::from_rule(rule, dir.clone());
+        fs::create_dir(dir.join("dist")).unwrap();
+        project.excluded_artifacts.push("dist".into());
+
+        let report = project.clean().unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.bytes_freed, 80);
+        assert_eq!(report.files_removed, 2);
+        assert_eq!(report.dirs_removed, 2);
+        assert_eq!(report.cleaned[0].path, PathBuf::from("node_modules"));
+        assert_eq!(report.skipped, vec![PathBuf::from("dist")]);
+        assert!(dir.join("dist").exists());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["bytes_freed"], 80);
+        assert!(json["duration"].is_f64());
+    }
+
+    #[test]
+    fn test_clean_native_falls_back_to_delete() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(root.path(), "broken", "Cargo.toml", "target");
+
+        // Node has no native command; `cargo clean` fails on the empty manifest
+        for (name, artifact) in [("web", "node_modules"), ("broken", "target")] {
+            let dir = root.path().join(name);
+            let project = Project::detect(&dir, &[]).unwrap();
+            let report = project.clean_native(Duration::from_secs(30)).unwrap();
+            assert_eq!(report.bytes_freed, 64);
+            assert!(!dir.join(artifact).exists());
+        }
+    }
+
+    #[test]
+    fn test_with_only_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        fs::create_dir(root.path().join("web").join("dist")).unwrap();
+        fs::write(
+            root.path().join("web").join("dist").join("app.js"),
+            [0u8; 16],
+        )
+        .unwrap();
+
+        let project = Project::detect(&root.path().join("web"), &[]).unwrap();
+        let options = ScanOptions::default();
+        assert_eq!(
+            project.artifact_sizes(&options),
+            vec![("node_modules".to_string(), 64), ("dist".to_string(), 16)]
+        );
+
+        let dist_only = project.with_only_artifacts(&["dist"]);
+        assert_eq!(
+            dist_only.artifact_sizes(&options),
+            vec![("dist".to_string(), 16)]
+        );
+        assert_eq!(dist_only.clean().unwrap().bytes_freed, 16);
+        assert!(root.path().join("web").join("node_modules").exists());
+    }
+
+    #[test]
+    fn test_artifacts_breakdown() {
+        let root = tempfile::tempdir().unwrap();
+        let web = root.path().join("web");
+        fs::create_dir_all(web.join("node_modules")).unwrap();
+        fs::create_dir_all(web.join("dist")).unwrap();
+        fs::write(web.join("package.json"), "{}").unwrap();
+        fs::write(web.join("node_modules").join("index.js"), [0u8; 48]).unwrap();
+        fs::write(web.join("dist").join("app.js"), [0u8; 8]).unwrap();
+
+        let project = Project::detect(&web, &[]).unwrap();
+        let options = ScanOptions::default();
+        let artifacts = project.artifacts(&options);
+        assert_eq!(
+            artifacts,
+            vec![
+                Artifact {
+                    path: PathBuf::from("node_modules"),
+                    size: 48
+                },
+                Artifact {
+                    path: PathBuf::from("dist"),
+                    size: 8
+                },
+            ]
+        );
+        assert_eq!(project.calculate_artifact_size(&options), 56);
+    }
+
+    #[test]
+    fn test_wildcard_artifacts_are_cleaned() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "pkg", "setup.py", "pkg.egg-info");
+
+        let project = Project::detect(&root.path().join("pkg"), &[]).unwrap();
+        assert_eq!(project.project_type, ProjectType::Python);
+        let dirs = project.artifact_directories();
+        assert!(dirs.contains(&"pkg.egg-info".to_string()));
+        assert!(!dirs.iter().any(|dir| dir.contains('*')));
+        assert_eq!(project.clean().unwrap().bytes_freed, 64);
+    }
+
+    #[test]
+    fn test_ruby_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "shop", "Gemfile", "vendor/bundle");
+        let shop = root.path().join("shop");
+        fs::create_dir_all(shop.join(".bundle")).unwrap();
+        fs::create_dir_all(shop.join("tmp").join("cache").join("bootsnap")).unwrap();
+        fs::create_dir_all(shop.join("tmp").join("pids")).unwrap();
+
+        let project = Project::detect(&shop, &[]).unwrap();
+        assert_eq!(project.project_type, ProjectType::Ruby);
+        assert_eq!(
+            project.artifact_directories(),
+            vec!["vendor/bundle", ".bundle", "tmp/cache"]
+        );
+        assert_eq!(
+            ProjectType::Ruby.artifact_category("tmp/cache"),
+            ArtifactCategory::Cache
+        );
+
+        project.clean().unwrap();
+        assert!(!shop.join("tmp").join("cache").exists());
+        assert!(shop.join("tmp").join("pids").exists());
+    }
+
+    #[test]
+    fn test_ocaml_and_nim_projects() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "parser", "dune-project", "_build");
+        make_project(root.path(), "cli", "cli.nimble", "nimcache");
+
+        let mut projects: Vec<Project> = scan_directory(root.path(), &ScanOptions::default())
+            .filter_map(Result::ok)
+            .collect();
+        projects.sort_by_key(|p| p.display_name());
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].project_type, ProjectType::Nim);
+        assert_eq!(projects[0].artifact_directories(), vec!["nimcache"]);
+        assert_eq!(projects[1].project_type, ProjectType::OCaml);
+        assert_eq!(projects[1].artifact_directories(), vec!["_build"]);
+        assert_eq!(projects[1].clean().unwrap().bytes_freed, 64);
+    }
+
+    #[test]
+    fn test_meson_and_autotools_projects() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "libfoo", "meson.build", "builddir");
+        make_project(root.path(), "libbar", "configure.ac", "autom4te.cache");
+        fs::create_dir_all(root.path().join("libbar").join("_build")).unwrap();
+
+        let mut projects: Vec<Project> = scan_directory(root.path(), &ScanOptions::default())
+            .filter_map(Result::ok)
+            .collect();
+        projects.sort_by_key(|p| p.display_name());
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].project_type, ProjectType::Autotools);
+        assert_eq!(
+            projects[0].artifact_paths(&ScanOptions::default()),
+            vec![
+                root.path().join("libbar").join("autom4te.cache"),
+                root.path().join("libbar").join("_build"),
+            ]
+        );
+        assert_eq!(projects[1].project_type, ProjectType::Meson);
+        assert_eq!(projects[1].clean().unwrap().bytes_freed, 64);
+    }
+
+    #[test]
+    fn test_julia_and_r_projects() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "fit", "Project.toml", ".julia/compiled");
+        make_project(root.path(), "report", "renv.lock", "renv/library");
+        fs::create_dir_all(root.path().join("report").join("renv").join("staging")).unwrap();
+
+        let scan = |options: &ScanOptions| -> Vec<(ProjectType, Vec<String>)> {
+            let mut found: Vec<(ProjectType, Vec<String>)> = scan_directory(root.path(), options)
+                .filter_map(Result::ok)
+                .map(|p| (p.project_type, p.artifact_directories()))
+                .collect();
+            found.sort_by_key(|(t, _)| t.id());
+            found
+        };
+
+        // The renv library is an environment, kept unless asked for
+        assert_eq!(
+            scan(&ScanOptions::default()),
+            vec![
+                (
+                    ProjectType::Julia,
+                    vec![
+                        ".julia/compiled".to_string(),
+                        ".julia/scratchspaces".to_string()
+                    ]
+                ),
+                (ProjectType::R, vec!["renv/staging".to_string()]),
+            ]
+        );
+        let options = ScanOptions {
+            clean_environments: true,
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            scan(&options)[1].1,
+            vec!["renv/library".to_string(), "renv/staging".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_machine_learning_experiments() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(
+            root.path(),
+            "vit",
+            "train.py",
+            "wandb/run-20260301_101500-x1",
+        );
+        let vit = root.path().join("vit");
+        fs::create_dir_all(vit.join("checkpoints")).unwrap();
+        fs::write(vit.join("checkpoints").join("epoch=9.ckpt"), [0u8; 256]).unwrap();
+
+        // Runs and checkpoints are only cleaned when asked for
+        let project = scan_directory(root.path(), &ScanOptions::default())
+            .find_map(Result::ok)
+            .unwrap();
+        assert_eq!(project.project_type, ProjectType::MachineLearning);
+        assert_eq!(project.calculate_artifact_size(&ScanOptions::default()), 0);
+
+        let options = ScanOptions {
+            clean_experiments: true,
+            ..ScanOptions::default()
+        };
+        let project = scan_directory(root.path(), &options)
+            .find_map(Result::ok)
+            .unwrap();
+        let artifacts = project.artifacts(&options);
+        assert_eq!(
+            artifacts,
+            vec![
+                Artifact {
+                    path: PathBuf::from("wandb"),
+                    size: 64
+                },
+                Artifact {
+                    path: PathBuf::from("checkpoints"),
+                    size: 256
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latex_leftovers_are_files() {
+        let root = tempfile::tempdir().unwrap();
+        let paper = root.path().join("paper");
+        fs::create_dir_all(paper.join("_minted-paper")).unwrap();
+        fs::write(paper.join("paper.tex"), "").unwrap();
+        fs::write(paper.join("paper.pdf"), [0u8; 8]).unwrap();
+        fs::write(paper.join("paper.aux"), [0u8; 16]).unwrap();
+        fs::write(paper.join("paper.synctex.gz"), [0u8; 32]).unwrap();
+
+        let project = Project::detect(&paper, &[]).unwrap();
+        assert_eq!(project.project_type, ProjectType::LaTeX);
+        assert_eq!(
+            project.artifact_directories(),
+            vec!["paper.aux", "paper.synctex.gz", "_minted-paper"]
+        );
+        assert_eq!(project.clean().unwrap().bytes_freed, 48);
+        assert!(paper.join("paper.pdf").exists());
+        assert!(!paper.join("_minted-paper").exists());
+    }
+
+    #[test]
+    fn test_latex_keeps_unrelated_files() {
+        let root = tempfile::tempdir().unwrap();
+        let notes = root.path().join("notes");
+        fs::create_dir_all(&notes).unwrap();
+        fs::write(notes.join("notes.tex"), "").unwrap();
+        fs::write(notes.join("notes.log"), [0u8; 16]).unwrap();
+        fs::write(notes.join("server.log"), [0u8; 32]).unwrap();
+        fs::write(notes.join("slurm-42.out"), [0u8; 32]).unwrap();
+        fs::write(notes.join("notes.bbl"), [0u8; 8]).unwrap();
+
+        let project = Project::detect(&notes, &[]).unwrap();
+        assert_eq!(project.project_type, ProjectType::LaTeX);
+        assert_eq!(project.artifact_directories(), vec!["notes.log"]);
+        assert_eq!(project.clean().unwrap().bytes_freed, 16);
+        assert!(notes.join("server.log").exists());
+        assert!(notes.join("slurm-42.out").exists());
+        // Without a .bib to rebuild it from, the bibliography is kept
+        assert!(notes.join("notes.bbl").exists());
+
+        // Unrelated logs alone don't make a LaTeX project
+        fs::remove_file(notes.join("notes.tex")).unwrap();
+        fs::write(notes.join("draft.tex"), "").unwrap();
+        assert!(Project::detect(&notes, &[]).is_none());
+    }
+
+    #[test]
+    fn test_scan_skips_projects_inside_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(root.path(), "app", "Cargo.toml", "target");
+        make_project(
+            &root.path().join("web").join("node_modules"),
+            "left-pad",
+            "package.json",
+            "dist",
+        );
+        make_project(
+            &root.path().join("app").join("target").join("package"),
+            "app-0.1.0",
+            "Cargo.toml",
+            "target",
+        );
+
+        let mut names: Vec<_> = scan_directory(root.path(), &ScanOptions::default())
+            .filter_map(Result::ok)
+            .map(|p| p.display_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["app", "web"]);
+    }
+
+    #[test]
+    fn test_age_source() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "app", "Cargo.toml", "target");
+        let project = Project::new(ProjectType::Rust, root.path().join("app"));
+        let age = |source| {
+            let options = ScanOptions::builder().age_source(source).build();
+            let last = project.last_modified(&options).unwrap();
+            last.elapsed().unwrap_or_default().as_secs()
+        };
+
+        // Without a repository, commit falls back to modification times
+        assert!(age(AgeSource::Commit) < 60);
+
+        let repo = git2::Repository::init(root.path()).unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        // 2001-09-09
+        let time = git2::Time::new(1_000_000_000, 0);
+        let signature = git2::Signature::new("test", "test@example.com", &time).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "old", &tree, &[])
+            .unwrap();
+
+        assert!(age(AgeSource::Mtime) < 60);
+        assert!(age(AgeSource::Commit) > 365 * 24 * 60 * 60);
+
+        // A build since the last edit doesn't make the project any younger
+        #[cfg(unix)]
+        {
+            let app = root.path().join("app");
+            let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+            for path in [app.join("Cargo.toml"), app.clone()] {
+                fs::File::open(path).unwrap().set_modified(old).unwrap();
+            }
+            fs::write(app.join("target").join("output.bin"), [1u8; 64]).unwrap();
+            assert!(age(AgeSource::Mtime) > 365 * 24 * 60 * 60);
+        }
+        assert_eq!("Commit".parse(), Ok(AgeSource::Commit));
+        assert!("ctime".parse::<AgeSource>().is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "app", "Cargo.toml", "target");
+        make_project(root.path(), "group/lib", "Cargo.toml", "target");
+        make_project(root.path(), "group/nested/deep", "Cargo.toml", "target");
+
+        let found = |options: ScanOptions| {
+            let mut names: Vec<String> = scan_directory(root.path(), &options)
+                .map(|p| p.unwrap().display_name())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(found(ScanOptions::default()), ["app", "deep", "lib"]);
+        assert_eq!(
+            found(ScanOptions::builder().max_depth(2).build()),
+            ["app", "lib"]
+        );
+        assert!(found(ScanOptions::builder().max_depth(0).build()).is_empty());
+    }
+
+    #[test]
+    fn test_scan_many() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        make_project(first.path(), "app", "Cargo.toml", "target");
+        make_project(first.path(), "nested/web", "package.json", "node_modules");
+        make_project(second.path(), "lib", "Cargo.toml", "target");
+
+        // The second root is also inside the first one's results
+        let roots = [
+            first.path().to_path_buf(),
+            second.path().to_path_buf(),
+            first.path().join("nested"),
+        ];
+        let mut names: Vec<String> = scan_many(&roots, &ScanOptions::default())
+            .map(|result| result.unwrap().0.display_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["app", "lib", "web"]);
+    }
+
+    #[test]
+    fn test_shared_target_listed_once() {
+        // A target directory from the environment would take precedence
+        if std::env::var_os("CARGO_TARGET_DIR").is_some()
+            || std::env::var_os("CARGO_BUILD_TARGET_DIR").is_some()
+        {
+            return;
+        }
+        let root = tempfile::tempdir().unwrap();
+        let workspace = root.path().join("workspace");
+        make_project(&workspace, "a", "Cargo.toml", "target");
+        make_project(&workspace, "b", "Cargo.toml", "target");
+        fs::create_dir_all(workspace.join(".cargo")).unwrap();
+        fs::write(
+            workspace.join(".cargo/config.toml"),
+            "[build]\ntarget-dir = \"../shared\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.path().join("shared/debug")).unwrap();
+        fs::write(root.path().join("shared/debug/app"), [0u8; 100]).unwrap();
+
+        let scan = |options: &ScanOptions| {
+            let mut found: Vec<(String, u64)> = scan_with_sizes(&workspace, options)
+                .map(|result| result.unwrap())
+                .map(|(project, size)| (project.display_name(), size))
+                .collect();
+            found.sort();
+            found
+        };
+
+        // The shared directory belongs to neither project
+        let options = ScanOptions::default();
+        assert_eq!(scan(&options), [("a".into(), 64), ("b".into(), 64)]);
+
+        let options = ScanOptions::builder()
+            .shared_targets(true)
+            .parallelism(4)
+            .build();
+        assert_eq!(
+            scan(&options),
+            [("a".into(), 64), ("b".into(), 64), ("shared".into(), 100)]
+        );
+
+        let shared = cargo_target::shared_target_project(root.path().join("shared"));
+        assert_eq!(shared.clean().unwrap().bytes_freed, 100);
+        assert!(!root.path().join("shared").exists());
+    }
+
+    #[test]
+    fn test_min_artifact_size() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "small", "Cargo.toml", "target");
+        make_project(root.path(), "large", "package.json", "node_modules");
+        fs::write(root.path().join("large/node_modules/big.bin"), [0u8; 256]).unwrap();
+
+        let options = ScanOptions {
+            min_artifact_size: 100,
+            ..ScanOptions::default()
+        };
+        let found: Vec<_> = scan_with_sizes(root.path(), &options)
+            .filter_map(Result::ok)
+            .map(|(p, size)| (p.display_name(), size))
+            .collect();
+        assert_eq!(found, vec![("large".to_string(), 320)]);
+
+        // Without sizes, every project with artifacts is listed
+        let options = ScanOptions {
+            skip_sizes: true,
+            ..options
+        };
+        let mut found: Vec<_> = scan_with_sizes(root.path(), &options)
+            .filter_map(Result::ok)
+            .map(|(p, size)| (p.display_name(), size))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![("large".to_string(), 0), ("small".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_progress_events() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "crate-a", "Cargo.toml", "target");
+        make_project(root.path(), "web", "package.json", "node_modules");
+
+        let projects = Arc::new(AtomicU64::new(0));
+        let bytes = Arc::new(AtomicU64::new(0));
+        let (p, b) = (Arc::clone(&projects), Arc::clone(&bytes));
+        let options = ScanOptions {
+            progress: Some(ProgressCallback::new(move |event| match event {
+                ProgressEvent::ProjectFound(_) => {
+                    p.fetch_add(1, Ordering::Relaxed);
+                }
+                ProgressEvent::ArtifactsSized(_, size) => {
+                    b.fetch_add(size, Ordering::Relaxed);
+                }
+                ProgressEvent::DirectoryScanned(_) => {}
+            })),
+            ..ScanOptions::default()
+        };
+
+        for project in scan_directory(root.path(), &options).filter_map(Result::ok) {
+            project.calculate_artifact_size(&options);
+        }
+
+        assert_eq!(projects.load(Ordering::Relaxed), 2);
+        assert_eq!(bytes.load(Ordering::Relaxed), 128);
+    }
+
+    #[test]
+    fn test_scan_observer() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl ScanObserver for Recorder {
+            fn project_found(&self, project: &Project) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("found {}", project.display_name()));
+            }
+
+            fn size_computed(&self, project: &Project, bytes: u64) {
+                let event = format!("sized {} {}", project.display_name(), bytes);
+                self.0.lock().unwrap().push(event);
+            }
+
+            fn artifact_deleted(&self, path: &Path, bytes: u64) {
+                let name = path.file_name().unwrap().to_string_lossy();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("deleted {} {}", name, bytes));
+            }
+        }
+
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "crate-a", "Cargo.toml", "target");
+
+        let recorder = Arc::new(Recorder::default());
+        let options = ScanOptions::builder().observer(recorder.clone()).build();
+        for (project, _) in scan_with_sizes(root.path(), &options).filter_map(Result::ok) {
+            project.clean_observed(recorder.as_ref()).unwrap();
+        }
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["found crate-a", "sized crate-a 64", "deleted target 64"]
+        );
+    }
+
+    #[test]
+    fn test_skip_reasons() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<(String, SkipReason)>>);
+
+        impl ScanObserver for Recorder {
+            fn skipped(&self, path: &Path, reason: SkipReason) {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                self.0.lock().unwrap().push((name, reason));
+            }
+        }
+
+        // The temporary directory's own name starts with a dot
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("code");
+        make_project(&root, "kept", "Cargo.toml", "target");
+        make_project(&root.join("archive"), "old", "Cargo.toml", "target");
+        fs::create_dir(root.join(".hidden")).unwrap();
+        fs::create_dir(root.join("fresh")).unwrap();
+        fs::write(root.join("fresh/Cargo.toml"), "").unwrap();
+
+        let skipped = |options: ScanOptions| {
+            let recorder = Arc::new(Recorder::default());
+            let options = ScanOptions {
+                observer: Some(recorder.clone()),
+                ..options
+            };
+            scan_with_sizes(&root, &options).for_each(drop);
+            let mut skipped = recorder.0.lock().unwrap().clone();
+            skipped.sort_by(|a, b| a.0.cmp(&b.0));
+            skipped
+        };
+
+        let options = ScanOptions::builder()
+            .exclude(["archive"])
+            .protected(["kept"])
+            .build();
+        assert_eq!(
+            skipped(options),
+            [
+                (".hidden".to_string(), SkipReason::Hidden),
+                ("archive".to_string(), SkipReason::Excluded),
+                ("fresh".to_string(), SkipReason::NoArtifacts),
+                ("kept".to_string(), SkipReason::Protected),
+            ]
+        );
+
+        let options = ScanOptions::builder()
+            .exclude(["archive", ".*"])
+            .min_age(Duration::from_secs(86_400))
+            .build();
+        assert_eq!(
+            skipped(options),
+            [
+                (".hidden".to_string(), SkipReason::Excluded),
+                ("archive".to_string(), SkipReason::Excluded),
+                ("fresh".to_string(), SkipReason::TooNew),
+                ("kept".to_string(), SkipReason::TooNew),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exclude_prunes_trees_and_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "web", "package.json", "node_modules");
+        make_project(&root.path().join("archive"), "old", "Cargo.toml", "target");
+
+        let options = ScanOptions {
+            exclude: vec!["arch*".to_string(), "node_modules".to_string()],
+            ..ScanOptions::default()
+        };
+        let projects: Vec<_> = scan_directory(root.path(), &options)
+            .filter_map(Result::ok)
+            .collect();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].excluded_artifacts, vec!["node_modules"]);
+        assert!(!projects[0]
+            .artifact_directories()
+            .contains(&"node_modules".to_string()));
+        assert_eq!(projects[0].calculate_artifact_size(&options), 0);
+    }
+
+    #[test]
+    fn test_protected_projects_refuse_to_clean() {
+        let root = tempfile::tempdir().unwrap();
+        make_project(root.path(), "production-app", "Cargo.toml", "target");
+        make_project(root.path(), "scratch", "Cargo.toml", "target");
+
+        let options = ScanOptions::builder()
+            .protected([root.path().join("production-*").to_string_lossy()])
+            .build();
+        for (project, size) in scan_with_sizes(root.path(), &options).filter_map(Result::ok) {
+            assert_eq!(size, 64);
+            let result = project.clean();
+            if project.display_name() == "production-app" {
+                assert!(project.protected);
+                assert!(matches!(result, Err(CleanError::Protected { .. })));
+            } else {
+                assert!(!project.protected);
+                assert_eq!(result.unwrap().bytes_freed, 64);
+            }
+        }
+        assert!(root.path().join("production-app/target").exists());
+        assert!(!root.path().join("scratch/target").exists());
+    }
+
+    #[test]
+    fn test_clean_errors_are_classified() {
+        use std::io::{Error, ErrorKind};
+
+        let path = Path::new("/projects/app/target");
+        assert!(matches!(
+            CleanError::at(path, Error::from(ErrorKind::PermissionDenied)),
+            CleanError::PermissionDenied { .. }
+        ));
+        assert!(matches!(
+            CleanError::at(path, Error::from(ErrorKind::ReadOnlyFilesystem)),
+            CleanError::ReadOnly { .. }
+        ));
+        assert!(matches!(
+            CleanError::at(path, Error::from(ErrorKind::CrossesDevices)),
+            CleanError::CrossDevice { .. }
+        ));
+        let busy = CleanError::at(path, Error::from(ErrorKind::ResourceBusy));
+        assert!(matches!(busy, CleanError::InUse { .. }));
+        assert_eq!(busy.path(), Some(path));
+        assert!(matches!(
+            CleanError::at(path, Error::other("disk on fire")),
+            CleanError::Io { .. }
+        ));
+    }
+
+    #[test]
+    fn test_missing_root_is_not_found() {
+        let root = tempfile::tempdir().unwrap();
+        let missing = root.path().join("missing");
+
+        let errors: Vec<_> = scan_directory(&missing, &ScanOptions::default())
+            .filter_map(Result::err)
+            .collect();
+        assert!(matches!(&errors[..], [ScanError::NotFound { path, .. }] if *path == missing));
+    }
+}
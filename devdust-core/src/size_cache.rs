@@ -0,0 +1,173 @@
+//! Persistent artifact size cache
+//!
+//! Walking large artifact directories is the slowest part of a scan. Sizes are
+//! remembered between runs, keyed by the directory path and its modification
+//! time, so unchanged directories are not walked again.
+//!
+//! A directory's mtime only changes when entries directly inside it are added,
+//! removed or renamed. Build tools recreate their top-level outputs on every
+//! build, which is what makes this key good enough in practice.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
+    path::{self, Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::UNIX_EPOCH,
+};
+
+/// File name of the cache inside the devdust cache directory
+const CACHE_FILE_NAME: &str = "sizes.tsv";
+
+/// Modification time as (seconds, nanoseconds) since the Unix epoch
+type Mtime = (u64, u32);
+
+/// Remembered sizes of artifact directories
+#[derive(Debug, Default)]
+pub struct SizeCache {
+    /// Where the cache is persisted, if anywhere
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<PathBuf, (Mtime, u64)>>,
+}
+
+impl SizeCache {
+    /// Returns the default cache location (`$XDG_CACHE_HOME/devdust/sizes.tsv`)
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))?;
+
+        Some(base.join("devdust").join(CACHE_FILE_NAME))
+    }
+
+    /// Loads the cache from `path`
+    ///
+    /// A missing or unreadable file yields an empty cache; malformed lines are skipped.
+    pub fn load(path: &Path) -> Self {
+        let entries = fs::read_to_string(path)
+            .map(|contents| contents.lines().filter_map(parse_line).collect())
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path.to_path_buf()),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Loads the cache from the default location, or an in-memory one if there is none
+    pub fn load_default() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the remembered size of `dir` if it has not been modified since
+    pub fn get(&self, dir: &Path) -> Option<u64> {
+        let mtime = mtime(dir)?;
+        let key = path::absolute(dir).ok()?;
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match entries.get(&key) {
+            Some((cached, size)) if *cached == mtime => Some(*size),
+            _ => None,
+        }
+    }
+
+    /// Remembers the size of `dir` at its current modification time
+    ///
+    /// Entries are keyed by absolute path so relative scan roots share them.
+    pub fn insert(&self, dir: &Path, size: u64) {
+        let (Some(mtime), Ok(key)) = (mtime(dir), path::absolute(dir)) else {
+            return;
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, (mtime, size));
+    }
+
+    /// Writes the cache back to disk, dropping directories that no longer exist
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        for (dir, ((secs, nanos), size)) in entries.iter() {
+            if !dir.is_dir() {
+                continue;
+            }
+            // Paths that cannot be written as a single UTF-8 line are simply not cached
+            let Some(dir) = dir.to_str().filter(|d| !d.contains('\n')) else {
+                continue;
+            };
+            writeln!(out, "{}\t{}\t{}\t{}", size, secs, nanos, dir)?;
+        }
+        out.flush()
+    }
+}
+
+/// Parses a `size<TAB>secs<TAB>nanos<TAB>path` line
+fn parse_line(line: &str) -> Option<(PathBuf, (Mtime, u64))> {
+    let mut fields = line.splitn(4, '\t');
+    let size = fields.next()?.parse().ok()?;
+    let secs = fields.next()?.parse().ok()?;
+    let nanos = fields.next()?.parse().ok()?;
+    let path = PathBuf::from(fields.next()?);
+
+    Some((path, ((secs, nanos), size)))
+}
+
+/// Reads the modification time of a path
+fn mtime(path: &Path) -> Option<Mtime> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_invalidation() {
+        let root = tempfile::tempdir().unwrap();
+        let artifacts = root.path().join("target");
+        fs::create_dir(&artifacts).unwrap();
+        let cache_file = root.path().join("cache").join(CACHE_FILE_NAME);
+
+        let cache = SizeCache::load(&cache_file);
+        assert_eq!(cache.get(&artifacts), None);
+        cache.insert(&artifacts, 4096);
+        cache.save().unwrap();
+
+        let cache = SizeCache::load(&cache_file);
+        assert_eq!(cache.get(&artifacts), Some(4096));
+
+        // Pretend the size was recorded at a different modification time
+        cache
+            .entries
+            .lock()
+            .unwrap()
+            .insert(artifacts.clone(), ((0, 0), 4096));
+        assert_eq!(cache.get(&artifacts), None);
+    }
+
+    #[test]
+    fn test_malformed_lines_are_skipped() {
+        assert_eq!(parse_line("12\t1\t2\t/tmp/x").unwrap().1, ((1, 2), 12));
+        assert!(parse_line("garbage").is_none());
+        assert!(parse_line("12\tx\t2\t/tmp/x").is_none());
+    }
+}
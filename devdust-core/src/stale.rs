@@ -0,0 +1,92 @@
+//! Artifacts older than the lockfile they were installed from
+//!
+//! When `package-lock.json` or `Cargo.lock` changed after `node_modules` or
+//! `target/` was last written, the next install or build replaces most of the
+//! directory anyway, so cleaning it costs next to nothing. A directory's age is
+//! the newest modification time of the directory itself and its direct
+//! entries: package managers and cargo touch those on every install or build,
+//! while walking the whole tree would be far too slow.
+
+use std::{fs, path::Path, time::SystemTime};
+
+use crate::Project;
+
+/// Returns the project's artifact directories that are older than their lockfile
+pub(crate) fn stale_artifacts(project: &Project) -> Vec<String> {
+    if project.custom_rule.is_some() {
+        return Vec::new();
+    }
+
+    let dirs = project.artifact_directories();
+    project
+        .typed_artifact_directories()
+        .into_iter()
+        .filter(|(_, dir)| dirs.contains(dir))
+        .filter(|(project_type, dir)| {
+            let lockfiles = project_type
+                .as_ref()
+                .map_or(&[][..], |t| t.source_lockfiles(dir));
+            let locked = lockfiles
+                .iter()
+                .filter_map(|file| modified(&project.path.join(file)))
+                .max();
+            let written = last_written(&project.path.join(dir));
+            matches!((locked, written), (Some(locked), Some(written)) if written < locked)
+        })
+        .map(|(_, dir)| dir)
+        .collect()
+}
+
+/// Returns the newest modification time of `dir` and its direct entries
+fn last_written(dir: &Path) -> Option<SystemTime> {
+    let own = modified(dir)?;
+    let entries = fs::read_dir(dir).ok()?;
+    let newest = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max();
+    Some(newest.map_or(own, |newest| newest.max(own)))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        fs::File::open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    // Directories can't be opened as files to set their times on Windows
+    #[cfg(unix)]
+    #[test]
+    fn test_stale_when_lockfile_is_newer() {
+        let root = tempfile::tempdir().unwrap();
+        let web = root.path().join("web");
+        fs::create_dir_all(web.join("node_modules").join("react")).unwrap();
+        fs::write(web.join("package.json"), "{}").unwrap();
+        fs::write(web.join("package-lock.json"), "{}").unwrap();
+
+        let project = Project::detect(&web, &[]).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(7 * 24 * 3600);
+        set_modified(&web.join("node_modules").join("react"), old);
+        set_modified(&web.join("node_modules"), old);
+        assert_eq!(stale_artifacts(&project), vec!["node_modules"]);
+
+        // Reinstalled after the lockfile changed
+        set_modified(
+            &web.join("package-lock.json"),
+            old - Duration::from_secs(60),
+        );
+        assert!(stale_artifacts(&project).is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Timings and counters for diagnosing slow scans
+//!
+//! A scan can be slow because a wide tree takes long to walk, or because a few
+//! huge artifact directories take long to size, and over a network share every
+//! `stat` counts. A [`ScanTimings`] set on `ScanOptions` records where the time
+//! went, per scan root and per project, along with how many directories were
+//! visited and how many files were stat'ed.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::disk_usage;
+
+/// Records where a scan spends its time
+///
+/// Clones share their records. Times are summed over the scan's worker threads,
+/// so with several workers they can add up to more than the time the scan took.
+#[derive(Debug, Clone, Default)]
+pub struct ScanTimings {
+    shared: Arc<Shared>,
+    /// Counters of the root being scanned, in each root's copy of the options
+    root: Option<Arc<RootCounters>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    roots: Mutex<Vec<(PathBuf, Arc<RootCounters>)>>,
+    projects: Mutex<Vec<ProjectTimings>>,
+}
+
+#[derive(Debug, Default)]
+struct RootCounters {
+    detection_nanos: AtomicU64,
+    sizing_nanos: AtomicU64,
+    dirs_visited: AtomicU64,
+    files_stated: AtomicU64,
+}
+
+/// Time spent on one scan root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootTimings {
+    pub path: PathBuf,
+    /// Walking directories and detecting and filtering projects in them
+    pub detection: Duration,
+    /// Calculating the artifact sizes of its projects
+    pub sizing: Duration,
+    pub dirs_visited: u64,
+    /// Files whose metadata was read to calculate sizes
+    pub files_stated: u64,
+}
+
+/// Time spent sizing one project's artifacts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectTimings {
+    pub path: PathBuf,
+    pub sizing: Duration,
+    /// Files whose metadata was read; 0 if every size was cached
+    pub files_stated: u64,
+}
+
+impl ScanTimings {
+    /// Creates empty timings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the timings of every root scanned so far, in the order they were started
+    pub fn roots(&self) -> Vec<RootTimings> {
+        lock(&self.shared.roots)
+            .iter()
+            .map(|(path, counters)| RootTimings {
+                path: path.clone(),
+                detection: Duration::from_nanos(counters.detection_nanos.load(Ordering::Relaxed)),
+                sizing: Duration::from_nanos(counters.sizing_nanos.load(Ordering::Relaxed)),
+                dirs_visited: counters.dirs_visited.load(Ordering::Relaxed),
+                files_stated: counters.files_stated.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Returns the timings of every project sized so far, slowest first
+    pub fn projects(&self) -> Vec<ProjectTimings> {
+        let mut projects = lock(&self.shared.projects).clone();
+        projects.sort_by_key(|project| std::cmp::Reverse(project.sizing));
+        projects
+    }
+
+    /// Returns a copy that attributes what it records to `root`
+    pub(crate) fn for_root(&self, root: &Path) -> Self {
+        let counters = Arc::new(RootCounters::default());
+        lock(&self.shared.roots).push((root.to_path_buf(), Arc::clone(&counters)));
+        Self {
+            shared: Arc::clone(&self.shared),
+            root: Some(counters),
+        }
+    }
+
+    /// Runs project detection for one walked entry, timing it
+    pub(crate) fn detect<T>(&self, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        if let Some(root) = &self.root {
+            add_duration(&root.detection_nanos, started.elapsed());
+        }
+        result
+    }
+
+    pub(crate) fn directory_visited(&self) {
+        if let Some(root) = &self.root {
+            root.dirs_visited.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Sizes the artifacts of the project at `path` with `f`, timing it and
+    /// counting the files it stats
+    ///
+    /// `f` must size on the calling thread, or its files are not counted.
+    pub(crate) fn size<T>(&self, path: &Path, f: impl FnOnce() -> T) -> T {
+        let files_before = disk_usage::files_stated();
+        let started = Instant::now();
+        let result = f();
+        let sizing = started.elapsed();
+        let files_stated = disk_usage::files_stated() - files_before;
+
+        if let Some(root) = &self.root {
+            add_duration(&root.sizing_nanos, sizing);
+            root.files_stated.fetch_add(files_stated, Ordering::Relaxed);
+        }
+        lock(&self.shared.projects).push(ProjectTimings {
+            path: path.to_path_buf(),
+            sizing,
+            files_stated,
+        });
+        result
+    }
+}
+
+fn add_duration(counter: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    counter.fetch_add(nanos, Ordering::Relaxed);
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{scan_with_sizes, ScanOptions};
+
+    #[test]
+    fn test_scan_timings() {
+        let root = tempfile::tempdir().unwrap();
+        for name in ["app", "lib"] {
+            let target = root.path().join(name).join("target");
+            fs::create_dir_all(target.join("debug")).unwrap();
+            fs::write(root.path().join(name).join("Cargo.toml"), "").unwrap();
+            fs::write(target.join("debug").join("app"), [0u8; 64]).unwrap();
+            fs::write(target.join("CACHEDIR.TAG"), "").unwrap();
+        }
+
+        let timings = ScanTimings::new();
+        let options = ScanOptions::builder().timings(timings.clone()).build();
+        assert_eq!(scan_with_sizes(root.path(), &options).count(), 2);
+
+        let roots = timings.roots();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].path, root.path());
+        // The root and both projects; their targets are never walked
+        assert_eq!(roots[0].dirs_visited, 3);
+        assert_eq!(roots[0].files_stated, 4);
+        assert!(roots[0].detection > Duration::ZERO);
+
+        let projects = timings.projects();
+        assert_eq!(projects.len(), 2);
+        assert!(projects.iter().all(|project| project.files_stated == 2));
+    }
+}
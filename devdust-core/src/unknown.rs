@@ -0,0 +1,156 @@
+//! Large git-ignored directories no project type knows
+//!
+//! Whatever a repository's `.gitignore` excludes is, more often than not,
+//! generated: build output of an in-house tool, downloaded datasets, caches of
+//! some plugin. Ignored directories that are big and not an artifact of any
+//! detected project are reported as possible artifacts, for a person to review.
+//! They are never cleaned automatically.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use git2::Repository;
+use serde::Serialize;
+
+use crate::{artifacts::KnownArtifacts, cachedir, calculate_directory_size, ScanOptions};
+
+/// A big git-ignored directory that may hold generated files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnknownArtifact {
+    /// The ignored directory
+    pub path: PathBuf,
+    /// Work tree of the repository whose ignore rules match it
+    pub repository: PathBuf,
+    /// Size in bytes, measured as selected by the scan options
+    pub size: u64,
+}
+
+/// Finds git-ignored directories of at least `min_size` bytes below `root`
+///
+/// Artifact directories of detected projects and custom rules are left out, as
+/// are tagged caches, and nothing inside an ignored directory is searched. The
+/// largest come first.
+pub fn find_unknown_artifacts(
+    root: &Path,
+    min_size: u64,
+    options: &ScanOptions,
+) -> Vec<UnknownArtifact> {
+    // Work trees are reported canonical, paths must match them
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let known = KnownArtifacts::new(&options.custom_rules);
+    let mut found = Vec::new();
+    let repository = Repository::discover(&root).ok();
+    visit(
+        &root,
+        repository.as_ref(),
+        &known,
+        min_size,
+        options,
+        &mut found,
+    );
+
+    found.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    found
+}
+
+fn visit(
+    dir: &Path,
+    repository: Option<&Repository>,
+    known: &KnownArtifacts,
+    min_size: u64,
+    options: &ScanOptions,
+    found: &mut Vec<UnknownArtifact>,
+) {
+    // Nested repositories and submodules bring their own ignore rules
+    let nested = dir
+        .join(".git")
+        .exists()
+        .then(|| Repository::open(dir).ok())
+        .flatten();
+    let repository = nested.as_ref().or(repository);
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        // Symlinks are not followed
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| e.file_name() != ".git")
+        .map(|e| e.path())
+        .collect();
+    subdirs.sort();
+
+    for subdir in subdirs {
+        if known.is_artifact_dir(&subdir) || cachedir::is_tagged(&subdir) {
+            continue;
+        }
+
+        let ignored_by = repository.and_then(|repo| {
+            let workdir = repo.workdir()?;
+            let relative = subdir.strip_prefix(workdir).ok()?;
+            repo.is_path_ignored(relative)
+                .unwrap_or(false)
+                .then(|| workdir.to_path_buf())
+        });
+        match ignored_by {
+            Some(workdir) => {
+                let size = calculate_directory_size(&subdir, options);
+                if size >= min_size {
+                    found.push(UnknownArtifact {
+                        path: subdir,
+                        repository: workdir,
+                        size,
+                    });
+                }
+            }
+            None => visit(&subdir, repository, known, min_size, options, found),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_unknown_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let repo = root.path().join("tool");
+        Repository::init(&repo).unwrap();
+        fs::write(
+            repo.join(".gitignore"),
+            "/datasets/\n/out/\nnode_modules/\n",
+        )
+        .unwrap();
+        fs::write(repo.join("package.json"), "{}").unwrap();
+        for dir in ["datasets/mnist", "out", "node_modules/react", "src"] {
+            fs::create_dir_all(repo.join(dir)).unwrap();
+        }
+        fs::write(
+            repo.join("datasets").join("mnist").join("train"),
+            [0u8; 4096],
+        )
+        .unwrap();
+        fs::write(repo.join("out").join("report.html"), [0u8; 16]).unwrap();
+        fs::write(
+            repo.join("node_modules").join("react").join("index.js"),
+            [0u8; 4096],
+        )
+        .unwrap();
+        fs::write(repo.join("src").join("main.js"), [0u8; 4096]).unwrap();
+
+        let found = find_unknown_artifacts(root.path(), 1024, &ScanOptions::default());
+        // node_modules is a known artifact, out is too small, src is tracked
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, repo.canonicalize().unwrap().join("datasets"));
+        assert_eq!(found[0].repository, repo.canonicalize().unwrap());
+        assert_eq!(found[0].size, 4096);
+    }
+}
@@ -0,0 +1,149 @@
+//! Unreal Engine plugins and packaged builds
+//!
+//! Every code plugin under `Plugins/` compiles into its own `Binaries/` and
+//! `Intermediate/`, next to its `.uplugin` file. Plugins may be grouped in
+//! subfolders, so `Plugins/` is searched a few levels deep. Packaged builds are
+//! staged in `Saved/StagedBuilds` unless the project's packaging settings name
+//! another folder, which is included as long as it lies inside the project.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// How deep below `Plugins/` to look for `.uplugin` files
+const MAX_PLUGIN_DEPTH: usize = 4;
+
+/// Folders of the project itself, never treated as a staging directory
+const SOURCE_DIRS: &[&str] = &["Config", "Content", "Plugins", "Source"];
+
+/// Returns the plugin build folders and the configured staging directory
+pub(crate) fn artifact_directories(project_dir: &Path) -> Vec<String> {
+    let mut plugins = Vec::new();
+    find_plugins(&project_dir.join("Plugins"), MAX_PLUGIN_DEPTH, &mut plugins);
+    plugins.sort();
+
+    let mut dirs: Vec<String> = plugins
+        .iter()
+        .flat_map(|plugin| [plugin.join("Binaries"), plugin.join("Intermediate")])
+        .filter(|dir| dir.is_dir())
+        .filter_map(|dir| relative(project_dir, &dir))
+        .collect();
+    if let Some(staging) = staging_directory(project_dir) {
+        dirs.push(staging);
+    }
+    dirs
+}
+
+/// Collects the folders below `dir` that contain a `.uplugin` file
+fn find_plugins(dir: &Path, depth: usize, plugins: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+
+    if paths
+        .iter()
+        .any(|path| path.extension().is_some_and(|ext| ext == "uplugin"))
+    {
+        plugins.push(dir.to_path_buf());
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    for path in paths.iter().filter(|path| path.is_dir()) {
+        find_plugins(path, depth - 1, plugins);
+    }
+}
+
+/// Reads `StagingDirectory` from the project's packaging settings
+///
+/// Returns `None` for the default location, which `Saved` already covers, and
+/// for folders outside the project.
+fn staging_directory(project_dir: &Path) -> Option<String> {
+    let config = fs::read_to_string(project_dir.join("Config").join("DefaultGame.ini")).ok()?;
+    // StagingDirectory=(Path="D:/Builds/Shooter")
+    let path = config.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("StagingDirectory=")?;
+        let start = value.find("Path=\"")? + "Path=\"".len();
+        let end = value[start..].find('"')? + start;
+        Some(value[start..end].replace('\\', "/"))
+    })?;
+
+    let path = Path::new(&path);
+    let staging = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        project_dir.join(path)
+    };
+    if !staging.is_dir() {
+        return None;
+    }
+    let name = relative(project_dir, &staging)?;
+    let first = name.split('/').next().unwrap_or_default();
+    (!SOURCE_DIRS.contains(&first)).then_some(name)
+}
+
+/// Returns `path` relative to `project_dir`, if it lies strictly inside it
+fn relative(project_dir: &Path, path: &Path) -> Option<String> {
+    let rest = path.strip_prefix(project_dir).ok()?;
+    let inside = rest.components().all(|c| matches!(c, Component::Normal(_)));
+    (inside && !rest.as_os_str().is_empty()).then(|| rest.to_string_lossy().replace('\\', "/"))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugins_and_staging_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let game = root.path();
+        fs::write(game.join("Shooter.uproject"), "{}").unwrap();
+
+        let weapons = game.join("Plugins").join("Gameplay").join("Weapons");
+        fs::create_dir_all(weapons.join("Binaries").join("Win64")).unwrap();
+        fs::create_dir_all(weapons.join("Intermediate")).unwrap();
+        fs::create_dir_all(weapons.join("Source")).unwrap();
+        fs::write(weapons.join("Weapons.uplugin"), "{}").unwrap();
+        // Content-only plugin, never compiled
+        let maps = game.join("Plugins").join("Maps");
+        fs::create_dir_all(maps.join("Content")).unwrap();
+        fs::write(maps.join("Maps.uplugin"), "{}").unwrap();
+
+        fs::create_dir_all(game.join("Config")).unwrap();
+        fs::create_dir_all(game.join("Packaged").join("Windows")).unwrap();
+        fs::write(
+            game.join("Config").join("DefaultGame.ini"),
+            format!(
+                "[/Script/UnrealEd.ProjectPackagingSettings]\nBuild=IfProjectHasCode\n\
+                 StagingDirectory=(Path=\"{}\")\n",
+                game.join("Packaged").display()
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            artifact_directories(game),
+            vec![
+                "Plugins/Gameplay/Weapons/Binaries",
+                "Plugins/Gameplay/Weapons/Intermediate",
+                "Packaged"
+            ]
+        );
+
+        // Staging into the project's own content is never cleaned
+        fs::write(
+            game.join("Config").join("DefaultGame.ini"),
+            "StagingDirectory=(Path=\"Content\")\n",
+        )
+        .unwrap();
+        fs::create_dir_all(game.join("Content")).unwrap();
+        assert_eq!(artifact_directories(game).len(), 2);
+    }
+}
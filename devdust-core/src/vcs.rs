@@ -0,0 +1,290 @@
+//! Version control checks
+//!
+//! A project with uncommitted changes or commits that were never pushed is
+//! probably being worked on, so its artifacts are likely to be rebuilt right
+//! away. These checks let the frontend warn about or skip such projects, and
+//! [`VcsInfo`] summarizes the repository state for display.
+
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use git2::{BranchType, Commit, Repository, StatusOptions};
+use serde::Deserialize;
+
+/// What to do with projects whose git working tree is not clean
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitPolicy {
+    /// Don't inspect git repositories
+    #[default]
+    Ignore,
+    /// Include the project, but let the frontend warn about it
+    Warn,
+    /// Leave the project out of scan results
+    Skip,
+}
+
+/// Git state of the repository containing a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GitStatus {
+    /// Modified, staged or untracked (but not ignored) files below the project root
+    pub uncommitted_changes: bool,
+    /// The current branch has commits its upstream doesn't, or was never pushed
+    pub unpushed_commits: bool,
+}
+
+/// Repository details shown next to a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VcsInfo {
+    /// Checked-out branch, or `None` for a detached HEAD
+    pub branch: Option<String>,
+    /// Commit time of HEAD, or `None` if nothing has been committed yet
+    pub last_commit: Option<SystemTime>,
+    /// Whether there are uncommitted changes below the project root
+    pub dirty: bool,
+}
+
+impl GitPolicy {
+    /// All policies
+    pub const ALL: &'static [GitPolicy] = &[Self::Ignore, Self::Warn, Self::Skip];
+
+    /// Returns the identifier used on the command line and in config files
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Warn => "warn",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+impl fmt::Display for GitPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for GitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|p| p.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown git policy: {}. Use ignore, warn, or skip", s))
+    }
+}
+
+impl GitStatus {
+    /// Returns true if there is nothing to lose or in progress
+    pub fn is_clean(&self) -> bool {
+        !self.uncommitted_changes && !self.unpushed_commits
+    }
+
+    /// Describes what is not clean (e.g., "uncommitted changes, unpushed commits")
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.uncommitted_changes {
+            parts.push("uncommitted changes");
+        }
+        if self.unpushed_commits {
+            parts.push("unpushed commits");
+        }
+        parts.join(", ")
+    }
+}
+
+/// Inspects the git repository containing `path`
+///
+/// Returns `None` if `path` is not inside a git working tree or the repository
+/// cannot be read.
+pub(crate) fn git_status(path: &Path) -> Option<GitStatus> {
+    let repo = Repository::discover(path).ok()?;
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+
+    Some(GitStatus {
+        uncommitted_changes: has_uncommitted_changes(&repo, &workdir, &path)?,
+        unpushed_commits: has_unpushed_commits(&repo),
+    })
+}
+
+/// Reads the branch, last commit time and dirty state of the repository containing `path`
+pub(crate) fn vcs_info(path: &Path) -> Option<VcsInfo> {
+    let repo = Repository::discover(path).ok()?;
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+
+    // An unborn branch has no HEAD commit yet, but still has a name
+    let (branch, last_commit) = match repo.head() {
+        Ok(head) => (
+            head.is_branch()
+                .then(|| head.shorthand().map(String::from))
+                .flatten(),
+            head.peel_to_commit()
+                .ok()
+                .map(|commit| commit_time(&commit)),
+        ),
+        Err(_) => (
+            repo.find_reference("HEAD")
+                .ok()
+                .and_then(|r| r.symbolic_target().map(String::from))
+                .map(|target| target.trim_start_matches("refs/heads/").to_string()),
+            None,
+        ),
+    };
+
+    Some(VcsInfo {
+        branch,
+        last_commit,
+        dirty: has_uncommitted_changes(&repo, &workdir, &path)?,
+    })
+}
+
+/// Returns the commit time of HEAD in the repository containing `path`
+pub(crate) fn last_commit(path: &Path) -> Option<SystemTime> {
+    let repo = Repository::discover(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit_time(&commit))
+}
+
+fn commit_time(commit: &Commit) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(commit.time().seconds().max(0) as u64)
+}
+
+/// Checks for changes below `path`, so other projects in a monorepo don't count
+fn has_uncommitted_changes(repo: &Repository, workdir: &Path, path: &Path) -> Option<bool> {
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .include_ignored(false)
+        .exclude_submodules(true);
+
+    let relative = path.strip_prefix(workdir).ok()?;
+    if !relative.as_os_str().is_empty() {
+        status_options.pathspec(relative);
+    }
+
+    let statuses = repo.statuses(Some(&mut status_options)).ok()?;
+    Some(!statuses.is_empty())
+}
+
+/// Checks whether the checked-out branch is ahead of its upstream
+///
+/// A branch without an upstream counts as unpushed if the repository has any
+/// remote to push to. A detached HEAD or an unborn branch never does.
+fn has_unpushed_commits(repo: &Repository) -> bool {
+    let Ok(head) = repo.head() else {
+        return false;
+    };
+    let (Some(name), Some(local)) = (head.shorthand(), head.target()) else {
+        return false;
+    };
+    if !head.is_branch() {
+        return false;
+    }
+
+    let upstream = repo
+        .find_branch(name, BranchType::Local)
+        .and_then(|branch| branch.upstream());
+    match upstream {
+        Ok(upstream) => upstream
+            .get()
+            .target()
+            .and_then(|remote| repo.graph_ahead_behind(local, remote).ok())
+            .is_some_and(|(ahead, _)| ahead > 0),
+        Err(_) => repo.remotes().is_ok_and(|remotes| !remotes.is_empty()),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn commit_all(repo: &Repository) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "commit",
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_git_status() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(git_status(root.path()), None);
+
+        let repo = Repository::init(root.path()).unwrap();
+        let app = root.path().join("app");
+        let lib = root.path().join("lib");
+        fs::create_dir_all(&app).unwrap();
+        fs::create_dir_all(&lib).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        fs::write(lib.join("Cargo.toml"), "").unwrap();
+        commit_all(&repo);
+        assert_eq!(git_status(&app), Some(GitStatus::default()));
+
+        // Changes in a sibling project don't affect this one
+        fs::write(lib.join("Cargo.toml"), "[package]").unwrap();
+        assert!(git_status(&app).unwrap().is_clean());
+        assert!(git_status(&lib).unwrap().uncommitted_changes);
+
+        // A local branch that was never pushed counts once there is a remote
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        assert!(git_status(&app).unwrap().unpushed_commits);
+    }
+
+    #[test]
+    fn test_vcs_info() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(vcs_info(root.path()), None);
+
+        let repo = Repository::init(root.path()).unwrap();
+        fs::write(root.path().join("Cargo.toml"), "").unwrap();
+        let unborn = vcs_info(root.path()).unwrap();
+        assert!(unborn.branch.is_some());
+        assert_eq!(unborn.last_commit, None);
+        assert!(unborn.dirty);
+
+        commit_all(&repo);
+        let info = vcs_info(root.path()).unwrap();
+        assert_eq!(info.branch, unborn.branch);
+        assert!(info.last_commit.is_some());
+        assert!(!info.dirty);
+
+        // Detached HEAD has no branch
+        let head = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head).unwrap();
+        assert_eq!(vcs_info(root.path()).unwrap().branch, None);
+    }
+
+    #[test]
+    fn test_git_policy_from_str() {
+        assert_eq!("WARN".parse(), Ok(GitPolicy::Warn));
+        assert!("maybe".parse::<GitPolicy>().is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Xcode DerivedData and simulators
+//!
+//! Xcode builds into `~/Library/Developer/Xcode/DerivedData/<Name>-<hash>`
+//! instead of next to the project. Each of those folders has an `info.plist`
+//! whose `WorkspacePath` names the `.xcodeproj` or `.xcworkspace` it was built
+//! from, which is how a folder is matched to the project it belongs to.
+//! Simulator devices are never deleted directly but through `xcrun simctl`, so
+//! CoreSimulator's device registry stays consistent.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Extensions of the bundles Xcode opens as a workspace
+const WORKSPACE_EXTENSIONS: &[&str] = &["xcodeproj", "xcworkspace"];
+
+/// Returns `~/Library/Developer`, where Xcode and CoreSimulator keep their data
+pub(crate) fn developer_dir() -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    dirs::home_dir().map(|home| home.join("Library").join("Developer"))
+}
+
+/// Returns the DerivedData folders built from the project in `project_dir`
+pub(crate) fn derived_data(project_dir: &Path) -> Vec<PathBuf> {
+    match developer_dir() {
+        Some(developer) => {
+            find_derived_data(&developer.join("Xcode").join("DerivedData"), project_dir)
+        }
+        None => Vec::new(),
+    }
+}
+
+fn find_derived_data(derived_data_root: &Path, project_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(project_dir) else {
+        return Vec::new();
+    };
+    let workspaces: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| WORKSPACE_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    if workspaces.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(entries) = fs::read_dir(derived_data_root) else {
+        return Vec::new();
+    };
+    let mut found: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|dir| {
+            let name = dir.file_name().unwrap_or_default().to_string_lossy();
+            workspaces.iter().any(|workspace| {
+                let stem = workspace.file_stem().unwrap_or_default().to_string_lossy();
+                name.strip_prefix(stem.as_ref())
+                    .is_some_and(|rest| rest.starts_with('-'))
+                    && built_from(dir, workspace)
+            })
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Returns true if the DerivedData folder's `info.plist` names `workspace`
+///
+/// Paths are stored as plain strings in both XML and binary property lists,
+/// so a substring search is enough.
+fn built_from(derived_data: &Path, workspace: &Path) -> bool {
+    let Ok(plist) = fs::read(derived_data.join("info.plist")) else {
+        return false;
+    };
+    let needle = workspace.to_string_lossy();
+    let needle = needle.as_bytes();
+    plist.windows(needle.len()).any(|window| window == needle)
+}
+
+/// Returns the device folders of simulators whose runtime is no longer installed
+pub(crate) fn unavailable_simulators(devices_root: &Path) -> Vec<PathBuf> {
+    let Ok(output) = simctl(&["list", "devices", "--json"]) else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&output) else {
+        return Vec::new();
+    };
+
+    let mut devices: Vec<PathBuf> = json["devices"]
+        .as_object()
+        .into_iter()
+        .flat_map(|runtimes| runtimes.values())
+        .filter_map(|devices| devices.as_array())
+        .flatten()
+        .filter(|device| device["isAvailable"] == false)
+        .filter_map(|device| device["udid"].as_str())
+        .map(|udid| devices_root.join(udid))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    devices.sort();
+    devices
+}
+
+/// Deletes the simulator stored in `device_dir`
+pub(crate) fn delete_simulator(device_dir: &Path) -> io::Result<()> {
+    let udid = device_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    simctl(&["delete", &udid]).map(|_| ())
+}
+
+/// Runs `xcrun simctl` and returns its standard output
+fn simctl(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("xcrun")
+        .arg("simctl")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "`xcrun simctl {}` failed: {}",
+            args[0],
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_derived_data() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("Weather");
+        fs::create_dir_all(app.join("Weather.xcodeproj")).unwrap();
+        let derived = root.path().join("DerivedData");
+
+        let plist = |workspace: &Path| {
+            format!(
+                "<plist><dict><key>WorkspacePath</key><string>{}</string></dict></plist>",
+                workspace.display()
+            )
+        };
+        let ours = derived.join("Weather-bqkzlxbnaxqjvzeyyxdrbhxmpbtw");
+        fs::create_dir_all(&ours).unwrap();
+        fs::write(
+            ours.join("info.plist"),
+            plist(&app.join("Weather.xcodeproj")),
+        )
+        .unwrap();
+
+        // Same name, built from a different checkout
+        let other = derived.join("Weather-fhuedhtqyqlxhyboyxhzkwumaqvd");
+        fs::create_dir_all(&other).unwrap();
+        let elsewhere = root.path().join("old").join("Weather.xcodeproj");
+        fs::write(other.join("info.plist"), plist(&elsewhere)).unwrap();
+
+        assert_eq!(find_derived_data(&derived, &app), vec![ours]);
+        assert!(find_derived_data(&derived, root.path()).is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Dev Dust C API
+ *
+ * Link against libdevdust_ffi (cdylib or staticlib built from devdust-ffi).
+ * Strings passed in are NUL-terminated UTF-8; strings returned by the library
+ * must be released with devdust_string_free.
+ */
+
+#ifndef DEVDUST_H
+#define DEVDUST_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum DevdustStatus {
+    DEVDUST_STATUS_OK = 0,
+    DEVDUST_STATUS_INVALID_ARGUMENT = 1,
+    DEVDUST_STATUS_NOT_FOUND = 2,
+    DEVDUST_STATUS_PERMISSION_DENIED = 3,
+    DEVDUST_STATUS_PARTIAL_FAILURE = 4,
+    DEVDUST_STATUS_IN_USE = 5,
+    DEVDUST_STATUS_ERROR = 6,
+} DevdustStatus;
+
+typedef struct DevdustScanOptions {
+    bool follow_symlinks;
+    bool same_filesystem;
+    uint64_t min_age_seconds;
+    uint64_t min_artifact_size;
+    uint32_t parallelism;
+} DevdustScanOptions;
+
+/* Opaque project handle */
+typedef struct DevdustProject DevdustProject;
+
+/* Called once per project; the handle is only valid during the call.
+ * Return false to stop the scan. */
+typedef bool (*DevdustProjectCallback)(const DevdustProject *project, void *user_data);
+
+DevdustScanOptions devdust_scan_options_default(void);
+
+/* options may be NULL for the defaults */
+DevdustStatus devdust_scan(const char *path,
+                           const DevdustScanOptions *options,
+                           DevdustProjectCallback callback,
+                           void *user_data);
+
+char *devdust_project_path(const DevdustProject *project);
+char *devdust_project_type(const DevdustProject *project);
+uint64_t devdust_project_artifact_size(const DevdustProject *project);
+uint64_t devdust_project_calculate_size(const DevdustProject *project);
+
+/* freed may be NULL; it is also set on DEVDUST_STATUS_PARTIAL_FAILURE */
+DevdustStatus devdust_project_clean(const DevdustProject *project, uint64_t *freed);
+
+DevdustProject *devdust_project_clone(const DevdustProject *project);
+void devdust_project_free(DevdustProject *project);
+void devdust_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DEVDUST_H */
@@ -0,0 +1,378 @@
+//! Dev Dust C ABI
+//!
+//! Exposes scanning, sizing and cleaning to applications written in C, C++,
+//! Swift, C# and other languages that can call C functions. The matching header
+//! is `include/devdust.h`.
+//!
+//! Projects are handed out as opaque `DevdustProject` handles. A scan calls back
+//! once per project with a handle that is only valid during the callback; use
+//! `devdust_project_clone` to keep it, and `devdust_project_free` to release it.
+//! Strings passed in must be NUL-terminated UTF-8; strings handed out must be
+//! released with `devdust_string_free`.
+
+use std::{
+    ffi::{c_char, c_void, CStr, CString},
+    path::Path,
+    ptr,
+};
+
+use devdust_core::{scan_with_sizes, CleanError, CleanReport, Project, ScanError, ScanOptions};
+
+/// Result of an FFI call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevdustStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// A pointer was null or a string was not valid UTF-8
+    InvalidArgument = 1,
+    /// A path does not exist
+    NotFound = 2,
+    /// A path could not be read or removed for lack of permissions
+    PermissionDenied = 3,
+    /// Some artifact directories were removed, others failed
+    PartialFailure = 4,
+    /// The artifacts are in use by a running process
+    InUse = 5,
+    /// Any other error
+    Error = 6,
+}
+
+/// Scan settings; pass null to `devdust_scan` for the defaults
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DevdustScanOptions {
+    /// Whether to follow symbolic links
+    pub follow_symlinks: bool,
+    /// Whether to stay on the same filesystem
+    pub same_filesystem: bool,
+    /// Minimum age in seconds for projects to be included
+    pub min_age_seconds: u64,
+    /// Skip projects with fewer bytes of artifacts
+    pub min_artifact_size: u64,
+    /// Number of worker threads (0 or 1 = sequential)
+    pub parallelism: u32,
+}
+
+/// A detected project with its artifact size
+pub struct DevdustProject {
+    project: Project,
+    artifact_size: u64,
+}
+
+/// Called once per project found; return `false` to stop the scan
+pub type DevdustProjectCallback =
+    extern "C" fn(project: *const DevdustProject, user_data: *mut c_void) -> bool;
+
+// ============================================================================
+// Scanning
+// ============================================================================
+
+/// Returns the default scan settings
+#[no_mangle]
+pub extern "C" fn devdust_scan_options_default() -> DevdustScanOptions {
+    let defaults = ScanOptions::default();
+    DevdustScanOptions {
+        follow_symlinks: defaults.follow_symlinks,
+        same_filesystem: defaults.same_filesystem,
+        min_age_seconds: defaults.min_age_seconds,
+        min_artifact_size: defaults.min_artifact_size,
+        parallelism: defaults.parallelism as u32,
+    }
+}
+
+/// Scans `path` and calls `callback` for every project with artifacts
+///
+/// Errors on individual directories are skipped; only a missing or unreadable
+/// root is reported.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string, `callback` a function pointer
+/// or null, and `options`, if not null,
+/// must point to a `DevdustScanOptions`. The project handle passed to
+/// `callback` is only valid until the callback returns.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_scan(
+    path: *const c_char,
+    options: *const DevdustScanOptions,
+    callback: Option<DevdustProjectCallback>,
+    user_data: *mut c_void,
+) -> DevdustStatus {
+    let (Some(path), Some(callback)) = (str_arg(path), callback) else {
+        return DevdustStatus::InvalidArgument;
+    };
+    let path = Path::new(path);
+    if !path.is_dir() {
+        return DevdustStatus::NotFound;
+    }
+
+    let options = match options.as_ref() {
+        Some(options) => ScanOptions::builder()
+            .follow_symlinks(options.follow_symlinks)
+            .same_filesystem(options.same_filesystem)
+            .min_age(std::time::Duration::from_secs(options.min_age_seconds))
+            .min_artifact_size(options.min_artifact_size)
+            .parallelism(options.parallelism.max(1) as usize)
+            .build(),
+        None => ScanOptions::default(),
+    };
+
+    for result in scan_with_sizes(path, &options) {
+        let (project, artifact_size) = match result {
+            Ok(found) => found,
+            Err(ScanError::PermissionDenied { path: denied, .. }) if denied == path => {
+                return DevdustStatus::PermissionDenied;
+            }
+            Err(_) => continue,
+        };
+
+        let handle = DevdustProject {
+            project,
+            artifact_size,
+        };
+        if !callback(&handle, user_data) {
+            break;
+        }
+    }
+
+    DevdustStatus::Ok
+}
+
+// ============================================================================
+// Projects
+// ============================================================================
+
+/// Returns the project's root path; release it with `devdust_string_free`
+///
+/// # Safety
+///
+/// `project` must be a valid project handle.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_path(project: *const DevdustProject) -> *mut c_char {
+    match project.as_ref() {
+        Some(handle) => into_c_string(handle.project.path.to_string_lossy().into_owned()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the project type name (e.g., "Rust"); release it with `devdust_string_free`
+///
+/// # Safety
+///
+/// `project` must be a valid project handle.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_type(project: *const DevdustProject) -> *mut c_char {
+    match project.as_ref() {
+        Some(handle) => into_c_string(handle.project.type_name().to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the artifact size in bytes calculated during the scan
+///
+/// # Safety
+///
+/// `project` must be a valid project handle.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_artifact_size(project: *const DevdustProject) -> u64 {
+    project.as_ref().map_or(0, |handle| handle.artifact_size)
+}
+
+/// Recalculates the artifact size in bytes
+///
+/// # Safety
+///
+/// `project` must be a valid project handle.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_calculate_size(project: *const DevdustProject) -> u64 {
+    project.as_ref().map_or(0, |handle| {
+        handle
+            .project
+            .calculate_artifact_size(&ScanOptions::default())
+    })
+}
+
+/// Deletes the project's artifact directories
+///
+/// The number of bytes freed is stored in `freed` if it is not null, also on
+/// `DEVDUST_STATUS_PARTIAL_FAILURE`.
+///
+/// # Safety
+///
+/// `project` must be a valid project handle and `freed` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_clean(
+    project: *const DevdustProject,
+    freed: *mut u64,
+) -> DevdustStatus {
+    let Some(handle) = project.as_ref() else {
+        return DevdustStatus::InvalidArgument;
+    };
+
+    let (status, bytes) = match handle.project.clean().and_then(CleanReport::into_result) {
+        Ok(report) => (DevdustStatus::Ok, report.bytes_freed),
+        Err(CleanError::PartialFailure { deleted, .. }) => (DevdustStatus::PartialFailure, deleted),
+        Err(e) => (status_for(&e), 0),
+    };
+
+    if let Some(freed) = freed.as_mut() {
+        *freed = bytes;
+    }
+    status
+}
+
+/// Copies a project handle so it outlives the scan callback
+///
+/// # Safety
+///
+/// `project` must be a valid project handle.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_clone(
+    project: *const DevdustProject,
+) -> *mut DevdustProject {
+    match project.as_ref() {
+        Some(handle) => Box::into_raw(Box::new(DevdustProject {
+            project: handle.project.clone(),
+            artifact_size: handle.artifact_size,
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a handle returned by `devdust_project_clone`
+///
+/// # Safety
+///
+/// `project` must be null or come from `devdust_project_clone`, and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_project_free(project: *mut DevdustProject) {
+    if !project.is_null() {
+        drop(Box::from_raw(project));
+    }
+}
+
+/// Releases a string returned by this library
+///
+/// # Safety
+///
+/// `string` must be null or come from this library, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn devdust_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Borrows a C string argument as UTF-8
+///
+/// # Safety
+///
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+/// Hands a string to the caller; interior NULs cut it short
+fn into_c_string(string: String) -> *mut c_char {
+    let bytes: Vec<u8> = string
+        .into_bytes()
+        .into_iter()
+        .take_while(|&b| b != 0)
+        .collect();
+    CString::new(bytes).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Maps a clean error to a status code
+fn status_for(error: &CleanError) -> DevdustStatus {
+    match error {
+        CleanError::NotFound { .. } => DevdustStatus::NotFound,
+        CleanError::PermissionDenied { .. } => DevdustStatus::PermissionDenied,
+        CleanError::InUse { .. } => DevdustStatus::InUse,
+        CleanError::PartialFailure { .. } => DevdustStatus::PartialFailure,
+        _ => DevdustStatus::Error,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    extern "C" fn keep_first(project: *const DevdustProject, user_data: *mut c_void) -> bool {
+        let kept = user_data as *mut *mut DevdustProject;
+        unsafe { *kept = devdust_project_clone(project) };
+        false
+    }
+
+    #[test]
+    fn test_scan_and_clean() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        fs::create_dir_all(app.join("target")).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        fs::write(app.join("target").join("output.bin"), [0u8; 64]).unwrap();
+
+        let path = CString::new(root.path().to_str().unwrap()).unwrap();
+        let mut kept: *mut DevdustProject = ptr::null_mut();
+        let status = unsafe {
+            devdust_scan(
+                path.as_ptr(),
+                ptr::null(),
+                Some(keep_first),
+                &mut kept as *mut _ as *mut c_void,
+            )
+        };
+        assert_eq!(status, DevdustStatus::Ok);
+        assert!(!kept.is_null());
+
+        unsafe {
+            assert_eq!(devdust_project_artifact_size(kept), 64);
+            let type_name = devdust_project_type(kept);
+            assert_eq!(CStr::from_ptr(type_name).to_str().unwrap(), "Rust");
+            devdust_string_free(type_name);
+
+            let mut freed = 0;
+            assert_eq!(devdust_project_clean(kept, &mut freed), DevdustStatus::Ok);
+            assert_eq!(freed, 64);
+            devdust_project_free(kept);
+        }
+        assert!(!app.join("target").exists());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        extern "C" fn never(_: *const DevdustProject, _: *mut c_void) -> bool {
+            unreachable!()
+        }
+
+        let missing = CString::new("/definitely/not/here").unwrap();
+        unsafe {
+            assert_eq!(
+                devdust_scan(ptr::null(), ptr::null(), Some(never), ptr::null_mut()),
+                DevdustStatus::InvalidArgument
+            );
+            assert_eq!(
+                devdust_scan(missing.as_ptr(), ptr::null(), Some(never), ptr::null_mut()),
+                DevdustStatus::NotFound
+            );
+            assert_eq!(
+                devdust_project_clean(ptr::null(), ptr::null_mut()),
+                DevdustStatus::InvalidArgument
+            );
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Dev Dust Python bindings
+//!
+//! Builds the `devdust` extension module so cleanup scripts can scan and clean
+//! projects without shelling out to the CLI and parsing its output:
+//!
+//! ```python
+//! import devdust
+//!
+//! options = devdust.ScanOptions(min_age=30 * 24 * 3600, project_types=["rust", "node"])
+//! for project in devdust.scan("/home/dev/code", options):
+//!     print(project.path, project.type_name, project.artifact_size)
+//!     project.clean()
+//! ```
+//!
+//! The GIL is released while scanning and cleaning, so other Python threads keep
+//! running.
+
+use std::{path::PathBuf, time::Duration};
+
+use devdust_core::{
+    scan_with_sizes, CleanError, CleanReport, Project as CoreProject, ProjectType, ScanError,
+    ScanOptions as CoreScanOptions,
+};
+use pyo3::{
+    exceptions::{PyFileNotFoundError, PyOSError, PyPermissionError, PyValueError},
+    prelude::*,
+};
+
+// ============================================================================
+// Scan Options
+// ============================================================================
+
+/// Settings for `scan`; every argument is optional and keyword-only
+#[pyclass(module = "devdust", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    options: CoreScanOptions,
+}
+
+#[pymethods]
+impl ScanOptions {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        follow_symlinks = false,
+        same_filesystem = true,
+        min_age = 0,
+        min_artifact_size = 0,
+        project_types = Vec::new(),
+        exclude = Vec::new(),
+        parallelism = 0,
+        ignore_files = true,
+        respect_gitignore = false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        follow_symlinks: bool,
+        same_filesystem: bool,
+        min_age: u64,
+        min_artifact_size: u64,
+        project_types: Vec<String>,
+        exclude: Vec<String>,
+        parallelism: usize,
+        ignore_files: bool,
+        respect_gitignore: bool,
+    ) -> PyResult<Self> {
+        let project_types = project_types
+            .iter()
+            .map(|t| t.parse::<ProjectType>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(PyValueError::new_err)?;
+
+        let mut builder = CoreScanOptions::builder()
+            .follow_symlinks(follow_symlinks)
+            .same_filesystem(same_filesystem)
+            .min_age(Duration::from_secs(min_age))
+            .min_artifact_size(min_artifact_size)
+            .project_types(project_types)
+            .exclude(exclude)
+            .ignore_files(ignore_files)
+            .respect_gitignore(respect_gitignore);
+        if parallelism > 0 {
+            builder = builder.parallelism(parallelism);
+        }
+
+        let options = builder.build();
+        options
+            .validate()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { options })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanOptions(min_age={}, min_artifact_size={}, parallelism={})",
+            self.options.min_age_seconds, self.options.min_artifact_size, self.options.parallelism
+        )
+    }
+}
+
+// ============================================================================
+// Projects
+// ============================================================================
+
+/// A detected project with its artifact size
+#[pyclass(module = "devdust", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct Project {
+    project: CoreProject,
+    artifact_size: u64,
+}
+
+#[pymethods]
+impl Project {
+    /// Root directory of the project
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.project.path.clone()
+    }
+
+    /// Short identifier of the project type (e.g. "rust")
+    #[getter]
+    fn project_type(&self) -> &'static str {
+        self.project.project_type.id()
+    }
+
+    /// Identifiers of all types of a project of several, primary first
+    #[getter]
+    fn project_types(&self) -> Vec<&'static str> {
+        self.project
+            .project_types()
+            .iter()
+            .map(|t| t.id())
+            .collect()
+    }
+
+    /// Human-readable project type (e.g. "Rust"), or the custom rule's name
+    #[getter]
+    fn type_name(&self) -> String {
+        self.project.type_name().to_string()
+    }
+
+    /// Display name of the project
+    #[getter]
+    fn name(&self) -> String {
+        self.project.display_name()
+    }
+
+    /// Artifact directories that exist, relative to the project root
+    #[getter]
+    fn artifact_directories(&self) -> Vec<String> {
+        self.project
+            .artifact_directories()
+            .into_iter()
+            .filter(|dir| self.project.path.join(dir).exists())
+            .collect()
+    }
+
+    /// Artifact size in bytes calculated during the scan
+    #[getter]
+    fn artifact_size(&self) -> u64 {
+        self.artifact_size
+    }
+
+    /// Recalculates the artifact size in bytes
+    fn calculate_size(&self, py: Python<'_>) -> u64 {
+        py.detach(|| {
+            self.project
+                .calculate_artifact_size(&CoreScanOptions::default())
+        })
+    }
+
+    /// Deletes the artifact directories and returns the number of bytes freed
+    fn clean(&self, py: Python<'_>) -> PyResult<u64> {
+        py.detach(|| self.project.clean().and_then(CleanReport::into_result))
+            .map(|report| report.bytes_freed)
+            .map_err(clean_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Project(path={:?}, type_name={:?}, artifact_size={})",
+            self.project.path.display().to_string(),
+            self.project.type_name(),
+            self.artifact_size
+        )
+    }
+}
+
+// ============================================================================
+// Module
+// ============================================================================
+
+/// Scans `path` and returns every project with artifacts
+///
+/// Errors on individual directories are skipped; a missing or unreadable root
+/// raises `FileNotFoundError` or `PermissionError`.
+#[pyfunction]
+#[pyo3(signature = (path, options = None))]
+fn scan(py: Python<'_>, path: PathBuf, options: Option<&ScanOptions>) -> PyResult<Vec<Project>> {
+    let options = options.map(|o| o.options.clone()).unwrap_or_default();
+
+    py.detach(|| {
+        let mut projects = Vec::new();
+        for result in scan_with_sizes(&path, &options) {
+            match result {
+                Ok((project, artifact_size)) => projects.push(Project {
+                    project,
+                    artifact_size,
+                }),
+                Err(ScanError::NotFound { path: missing, .. }) if missing == path => {
+                    return Err(PyFileNotFoundError::new_err(missing.display().to_string()));
+                }
+                Err(ScanError::PermissionDenied { path: denied, .. }) if denied == path => {
+                    return Err(PyPermissionError::new_err(denied.display().to_string()));
+                }
+                Err(_) => continue,
+            }
+        }
+        Ok(projects)
+    })
+}
+
+/// Formats a byte count the way the CLI does (e.g. "1.5 GB")
+#[pyfunction]
+fn format_size(bytes: u64) -> String {
+    devdust_core::format_size(bytes)
+}
+
+#[pymodule]
+fn devdust(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    module.add_class::<ScanOptions>()?;
+    module.add_class::<Project>()?;
+    module.add_function(wrap_pyfunction!(scan, module)?)?;
+    module.add_function(wrap_pyfunction!(format_size, module)?)?;
+    Ok(())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Maps a clean error to the matching built-in Python exception
+fn clean_error(error: CleanError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        CleanError::NotFound { .. } => PyFileNotFoundError::new_err(message),
+        CleanError::PermissionDenied { .. } => PyPermissionError::new_err(message),
+        _ => PyOSError::new_err(message),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_scan_and_clean() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        fs::create_dir_all(app.join("target")).unwrap();
+        fs::write(app.join("Cargo.toml"), "").unwrap();
+        fs::write(app.join("target").join("output.bin"), [0u8; 64]).unwrap();
+
+        Python::initialize();
+        Python::attach(|py| {
+            let options = ScanOptions::new(
+                false,
+                false,
+                0,
+                0,
+                vec!["rust".into()],
+                vec![],
+                0,
+                true,
+                false,
+            )
+            .unwrap();
+            let projects = scan(py, root.path().to_path_buf(), Some(&options)).unwrap();
+            assert_eq!(projects.len(), 1);
+            assert_eq!(projects[0].project_type(), "rust");
+            assert_eq!(projects[0].artifact_size(), 64);
+            assert_eq!(projects[0].clean(py).unwrap(), 64);
+
+            let missing = scan(py, root.path().join("missing"), None).unwrap_err();
+            assert!(missing.is_instance_of::<PyFileNotFoundError>(py));
+        });
+        assert!(!app.join("target").exists());
+    }
+
+    #[test]
+    fn test_unknown_project_type() {
+        Python::initialize();
+        Python::attach(|py| {
+            let error = ScanOptions::new(
+                false,
+                false,
+                0,
+                0,
+                vec!["cobol".into()],
+                vec![],
+                0,
+                true,
+                false,
+            )
+            .unwrap_err();
+            assert!(error.is_instance_of::<PyValueError>(py));
+        });
+    }
+}